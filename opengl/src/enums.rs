@@ -208,3 +208,37 @@ gl_enum!(TextureTarget {
     _2dMultisample: gl::TEXTURE_2D_MULTISAMPLE,
     _2dMultisampleArray: gl::TEXTURE_2D_MULTISAMPLE_ARRAY
 });
+
+gl_enum!(BlendFactor {
+    Zero: gl::ZERO,
+    One: gl::ONE,
+    SrcColor: gl::SRC_COLOR,
+    OneMinusSrcColor: gl::ONE_MINUS_SRC_COLOR,
+    DstColor: gl::DST_COLOR,
+    OneMinusDstColor: gl::ONE_MINUS_DST_COLOR,
+    SrcAlpha: gl::SRC_ALPHA,
+    OneMinusSrcAlpha: gl::ONE_MINUS_SRC_ALPHA,
+    DstAlpha: gl::DST_ALPHA,
+    OneMinusDstAlpha: gl::ONE_MINUS_DST_ALPHA
+});
+
+gl_enum!(PixelFormat {
+    Red: gl::RED,
+    Rg: gl::RG,
+    Rgb: gl::RGB,
+    Rgba: gl::RGBA
+});
+
+gl_enum!(TextureParameter {
+    MinFilter: gl::TEXTURE_MIN_FILTER,
+    MagFilter: gl::TEXTURE_MAG_FILTER,
+    WrapS: gl::TEXTURE_WRAP_S,
+    WrapT: gl::TEXTURE_WRAP_T
+});
+
+gl_enum!(TextureParameterValue {
+    Nearest: gl::NEAREST,
+    Linear: gl::LINEAR,
+    ClampToEdge: gl::CLAMP_TO_EDGE,
+    Repeat: gl::REPEAT
+});
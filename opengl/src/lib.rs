@@ -2,10 +2,10 @@ mod enums;
 mod types;
 
 pub use enums::*;
-use gl::types::GLuint;
+use gl::types::{GLenum, GLuint};
 pub use types::*;
 
-use glam::{Mat4, Vec4};
+use glam::{Mat4, Vec2, Vec4};
 use std::error::Error;
 use std::ffi::{self, c_void, CString};
 use std::ffi::{c_char, CStr};
@@ -61,6 +61,14 @@ impl Context {
         unsafe { gl::Enable(capability.into()) };
     }
 
+    pub fn disable(&self, capability: Capability) {
+        unsafe { gl::Disable(capability.into()) };
+    }
+
+    pub fn blend_func(&self, source: BlendFactor, destination: BlendFactor) {
+        unsafe { gl::BlendFunc(source.into(), destination.into()) };
+    }
+
     pub fn draw_arrays(&self, draw_type: DrawType, offset: u32, count: u32) {
         unsafe { gl::DrawArrays(draw_type.into(), offset as i32, count as i32) };
     }
@@ -132,6 +140,10 @@ impl Context {
         unsafe { gl::Uniform1f(location.into(), value) };
     }
 
+    pub fn uniform_vec2(&self, location: Location, value: &Vec2) {
+        unsafe { gl::Uniform2f(location.into(), value.x, value.y) };
+    }
+
     pub fn uniform_mat4(&self, location: Location, value: &Mat4) {
         unsafe {
             gl::UniformMatrix4fv(
@@ -274,6 +286,25 @@ impl Context {
         };
     }
 
+    pub fn vertex_attribute_pointer_i32(
+        &self,
+        index: u32,
+        count: u32,
+        r#type: DataType,
+        stride: u32,
+        offset: u32,
+    ) {
+        unsafe {
+            gl::VertexAttribIPointer(
+                index,
+                count as i32,
+                r#type.into(),
+                stride as i32,
+                offset as *const c_void,
+            )
+        };
+    }
+
     pub fn buffer_data_u8_slice(&self, target: BufferType, data: &[u8], usage: Usage) {
         unsafe {
             gl::BufferData(
@@ -312,4 +343,64 @@ impl Context {
     pub fn bind_texture(&self, target: TextureTarget, texture: Texture) {
         unsafe { gl::BindTexture(target.into(), texture.into()) };
     }
+
+    /// Allocates storage for the 2D texture bound to the target, leaving its contents undefined.
+    pub fn tex_image_2d(
+        &self,
+        target: TextureTarget,
+        internal_format: ImageFormat,
+        width: u32,
+        height: u32,
+    ) {
+        unsafe {
+            gl::TexImage2D(
+                target.into(),
+                0,
+                GLenum::from(internal_format) as i32,
+                width as i32,
+                height as i32,
+                0,
+                // Nothing is copied, but the format still has to be valid
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                ptr::null(),
+            )
+        };
+    }
+
+    /// Copies pixels into a region of the 2D texture bound to the target.
+    #[allow(clippy::too_many_arguments)]
+    pub fn tex_sub_image_2d_u8_slice(
+        &self,
+        target: TextureTarget,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        format: PixelFormat,
+        pixels: &[u8],
+    ) {
+        unsafe {
+            gl::TexSubImage2D(
+                target.into(),
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                format.into(),
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const c_void,
+            )
+        };
+    }
+
+    pub fn tex_parameter(
+        &self,
+        target: TextureTarget,
+        parameter: TextureParameter,
+        value: TextureParameterValue,
+    ) {
+        unsafe { gl::TexParameteri(target.into(), parameter.into(), GLenum::from(value) as i32) };
+    }
 }
@@ -1,28 +1,110 @@
 use crate::{
-    ogl::{texture_buffer::TextureBufferBuilder, OpenGl, Program},
+    ogl::{
+        texture_buffer::{TextureBuffer, TextureBufferBuilder},
+        DrawArrays, OpenGl, Program,
+    },
+    render_steps::collision::CollisionIndex,
     window::{Window, WindowAction, WindowEvent},
 };
 use glam::{Mat4, Vec2, Vec3};
 use opengl::ImageFormat;
-use std::{cell::RefCell, f32::consts::PI, rc::Rc};
-use winit::event::{ElementState, VirtualKeyCode};
+use std::{
+    cell::RefCell,
+    f32::consts::PI,
+    rc::Rc,
+    time::{Duration, Instant},
+};
+use winit::event::ElementState;
+
+pub use winit::event::VirtualKeyCode;
 
 pub mod ogl;
 pub mod render_steps;
 pub mod window;
 
 pub trait RenderStep {
-    fn build_programs(&self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>>;
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>>;
     fn get_vertices(&self) -> Vec<Vec<u8>>;
     fn get_texture_buffer(&self) -> Option<Vec<u8>> {
         None
     }
+
+    /// Ranges of the vertices to draw for each program, for programs that draw in parts (such as
+    /// programs that take turns drawing). All of the vertices are drawn at once otherwise.
+    fn get_draw_arrays(&self) -> Option<Vec<DrawArrays>> {
+        None
+    }
+
+    /// Called whenever the camera moves, returning true if the render step needs its vertices
+    /// (and texture buffer) to be re-uploaded.
+    fn update_camera(&mut self, _camera: &CameraInfo) -> bool {
+        false
+    }
+}
+
+/// Snapshot of the camera, allowing render steps to work in screen space.
+pub struct CameraInfo {
+    /// Combined projection and view matrix
+    pub projection_view: Mat4,
+
+    /// Size of the viewport in logical pixels
+    pub viewport: Vec2,
+
+    /// Screen space taken up by the symbols (icons and labels) placed so far, shared between
+    /// render steps so that symbols from different steps don't overlap either. Steps earlier in
+    /// the render order are placed first.
+    pub(crate) collisions: RefCell<CollisionIndex>,
 }
+impl CameraInfo {
+    pub fn new(projection_view: Mat4, viewport: Vec2) -> Self {
+        Self {
+            projection_view,
+            viewport,
+            collisions: RefCell::new(CollisionIndex::new()),
+        }
+    }
+
+    /// Projects a world position into screen space, in logical pixels with the origin in the
+    /// bottom left corner. Returns `None` if the position is behind the camera.
+    pub fn to_screen(&self, position: Vec3) -> Option<Vec2> {
+        let clip = self.projection_view * position.extend(1.0);
+
+        if clip.w <= 0.0 {
+            return None;
+        }
+
+        let normalised = Vec2::new(clip.x, clip.y) / clip.w;
+
+        Some((normalised * 0.5 + 0.5) * self.viewport)
+    }
+
+    /// Tests whether a screen position falls within the viewport, with an additional margin.
+    pub fn in_viewport(&self, screen: Vec2, margin: f32) -> bool {
+        screen.cmpge(Vec2::splat(-margin)).all() && screen.cmple(self.viewport + margin).all()
+    }
+}
+
+/// Largest angle (in radians) that the camera can be tilted away from looking straight down.
+const MAX_PITCH: f32 = PI / 4.0;
+
+/// Angle (in radians) that the camera tilts by for each key press.
+const PITCH_STEP: f32 = PI / 36.0;
+
+/// How long it takes the camera to fly somewhere.
+const FLIGHT_DURATION: Duration = Duration::from_millis(1200);
+
+/// How far the camera rises (as a multiple of the distance travelled) in the middle of a flight,
+/// so that where it's going comes into view sooner.
+const FLIGHT_ARC: f32 = 0.3;
 
 struct Camera {
     /// Current position of the camera
     position: Vec3,
 
+    /// Angle (in radians) that the camera is tilted towards the north, from looking straight down
+    /// at the map
+    pitch: f32,
+
     /// Field of view of the camera in radians
     fov: f32,
 
@@ -36,14 +118,69 @@ impl Camera {
     pub fn new(fov: f32, aspect_ratio: f32, z_plane: (f32, f32)) -> Self {
         Self {
             position: Vec3::new(0.0, 0.0, 0.0),
+            pitch: 0.0,
             fov,
             aspect_ratio,
             z_plane,
         }
     }
 
+    /// Direction that the camera is looking in.
+    fn direction(&self) -> Vec3 {
+        Vec3::new(0.0, self.pitch.cos(), self.pitch.sin())
+    }
+
+    /// Direction of the top of the screen, perpendicular to the camera direction.
+    fn up(&self) -> Vec3 {
+        Vec3::new(0.0, -self.pitch.sin(), self.pitch.cos())
+    }
+
     pub fn view(&self) -> Mat4 {
-        Mat4::look_to_rh(self.position, Vec3::Y, Vec3::Z)
+        Mat4::look_to_rh(self.position, self.direction(), self.up())
+    }
+
+    /// Finds where a point on the screen (in normalised device coordinates) lands on the map
+    /// plane (y = 0). Returns `None` if the ray through the point never reaches the map.
+    pub fn screen_to_map(&self, normalised_screen: Vec2) -> Option<Vec3> {
+        // https://gamedev.stackexchange.com/a/150425
+        let vertical_span = f32::tan(0.5 * self.fov);
+
+        // Ray from the camera through the point, in world space
+        let ray = Vec3::X * normalised_screen.x * vertical_span
+            + self.direction()
+            + self.up() * normalised_screen.y * vertical_span;
+
+        if ray.y <= 0.0 {
+            return None;
+        }
+
+        // Distance along the ray to the map plane
+        let depth = -self.position.y / ray.y;
+
+        Some(self.position + ray * depth)
+    }
+
+    /// Position that the camera needs to be at to look at a point on the map from a height, taking
+    /// the pitch into account.
+    fn looking_at(&self, point: Vec3, height: f32) -> Vec3 {
+        let direction = self.direction();
+
+        Vec3::new(point.x, 0.0, point.z) - direction * (height.abs() / direction.y)
+    }
+
+    /// Size (in pixels) of a world unit on the map at the center of the screen.
+    fn map_scale(&self, viewport: Vec2) -> f32 {
+        let Some(center) = self.screen_to_map(Vec2::ZERO) else {
+            return 1.0;
+        };
+
+        let projection_view = self.projection() * self.view();
+        let to_normalised = |position: Vec3| {
+            let clip = projection_view * position.extend(1.0);
+            clip.x / clip.w
+        };
+
+        (to_normalised(center + Vec3::X) - to_normalised(center)).abs() * viewport.x * 0.5
     }
 
     pub fn projection(&self) -> Mat4 {
@@ -51,9 +188,70 @@ impl Camera {
     }
 }
 
+/// Camera moving smoothly from one position to another.
+struct Flight {
+    from: Vec3,
+    to: Vec3,
+    start: Instant,
+}
+impl Flight {
+    /// Flight from where the camera currently is to look at a point on the map from a height.
+    fn new(camera: &Camera, point: Vec3, height: f32) -> Self {
+        Self {
+            from: camera.position,
+            to: camera.looking_at(point, height),
+            start: Instant::now(),
+        }
+    }
+
+    /// Position of the camera at a point in time, and whether the flight has finished.
+    fn position(&self, now: Instant) -> (Vec3, bool) {
+        let t = ((now - self.start).as_secs_f32() / FLIGHT_DURATION.as_secs_f32()).min(1.0);
+
+        // Ease in and out, rising in the middle so that less of the journey is spent close to
+        // the ground
+        let eased = t * t * (3.0 - 2.0 * t);
+        let rise = self.from.distance(self.to) * FLIGHT_ARC * (t * PI).sin();
+
+        (
+            self.from.lerp(self.to, eased) + Vec3::NEG_Y * rise,
+            t >= 1.0,
+        )
+    }
+}
+
+/// Programs (and their shared texture buffer) that have been built for a render step
+struct RenderStepPrograms {
+    programs: Vec<Rc<RefCell<Program>>>,
+    texture_buffer: Option<Rc<TextureBuffer>>,
+}
+impl RenderStepPrograms {
+    /// Uploads the current vertices and texture buffer of the render step
+    fn upload(&self, render_step: &dyn RenderStep) {
+        let vertices = render_step.get_vertices();
+        let mut draw_arrays = render_step.get_draw_arrays().map(Vec::into_iter);
+
+        for (program, vertices) in self.programs.iter().zip(vertices) {
+            // Attach vertices
+            program
+                .borrow_mut()
+                .attach_vertices(vertices, draw_arrays.as_mut().and_then(Iterator::next))
+                .unwrap();
+        }
+
+        if let (Some(texture_buffer), Some(data)) = (
+            self.texture_buffer.as_ref(),
+            render_step.get_texture_buffer(),
+        ) {
+            texture_buffer.set_data(&data);
+        }
+    }
+}
+
 pub struct Renderer {
     window: Window,
     camera: Camera,
+    flight: Option<Flight>,
 
     render_steps: Vec<Rc<RefCell<dyn RenderStep>>>,
 }
@@ -61,9 +259,26 @@ pub struct Renderer {
 #[derive(Debug, Clone, Copy)]
 pub enum Event {
     Keyboard(VirtualKeyCode),
+    /// Text typed on the keyboard, following the key press that produced it
+    Character(char),
     Click(Vec3),
 }
 
+/// What the application did in response to an event.
+#[derive(Default)]
+pub struct EventResponse {
+    /// Whether the application used the event (such as a key typed into a prompt), in which case
+    /// the renderer doesn't also act on it
+    pub handled: bool,
+
+    /// Render steps that were changed by the event, which need their vertices (and texture
+    /// buffer) to be re-uploaded
+    pub changed: Vec<Rc<RefCell<dyn RenderStep>>>,
+
+    /// Point on the map to fly the camera to, along with the height to look at it from
+    pub fly_to: Option<(Vec3, f32)>,
+}
+
 impl Renderer {
     pub fn with_window(window: window::Window) -> Self {
         let aspect_ratio = {
@@ -73,7 +288,10 @@ impl Renderer {
 
         Self {
             window,
-            camera: Camera::new(PI / 2.0, aspect_ratio, (0.000001, 100000.0)),
+            // The near plane can't be too close to the camera, otherwise the depth buffer loses all
+            // of its precision
+            camera: Camera::new(PI / 2.0, aspect_ratio, (0.1, 100000.0)),
+            flight: None,
             render_steps: Vec::new(),
         }
     }
@@ -88,129 +306,226 @@ impl Renderer {
 
     pub fn run<F>(mut self, mut event_callback: F) -> !
     where
-        F: 'static + FnMut(Event),
+        F: 'static + FnMut(Event) -> EventResponse,
     {
-        let programs = self
+        let render_steps = self
             .render_steps
             .iter()
-            .enumerate()
-            .flat_map(|(render_step_id, render_step)| {
-                let render_step = render_step.borrow();
+            .map(|render_step| {
+                let programs = {
+                    let mut render_step = render_step.borrow_mut();
+
+                    let programs = render_step.build_programs(&mut self.window.gl);
+
+                    let texture_buffer = render_step.get_texture_buffer().map(|_| {
+                        let texture_buffer = self
+                            .window
+                            .gl
+                            .create_texture(
+                                TextureBufferBuilder::new().with_format(ImageFormat::R32F),
+                            )
+                            .unwrap();
+
+                        Rc::new(texture_buffer)
+                    });
 
-                let programs = render_step.build_programs(&mut self.window.gl);
-                let vertices = render_step.get_vertices();
+                    if let Some(texture_buffer) = texture_buffer.as_ref() {
+                        for program in programs.iter() {
+                            // Attach texture buffer
+                            program
+                                .borrow_mut()
+                                .attach_texture_buffer(texture_buffer.clone());
+                        }
+                    }
 
-                let texture_buffer = render_step.get_texture_buffer().map(|data| {
-                    let texture_buffer = self
-                        .window
-                        .gl
-                        .create_texture(TextureBufferBuilder::new().with_format(ImageFormat::R32F))
-                        .unwrap();
+                    let programs = RenderStepPrograms {
+                        programs,
+                        texture_buffer,
+                    };
+                    programs.upload(&*render_step);
 
-                    texture_buffer.set_data(&data);
+                    programs
+                };
 
-                    Rc::new(texture_buffer)
-                });
+                (Rc::clone(render_step), programs)
+            })
+            .collect::<Vec<_>>();
 
-                for (program, vertices) in programs.iter().zip(vertices) {
+        let programs = render_steps
+            .iter()
+            .flat_map(|(_, render_step_programs)| render_step_programs.programs.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let update_uniforms =
+            |programs: &[Rc<RefCell<Program>>], camera: &Camera, viewport: &Vec2| {
+                let projection = camera.projection();
+                let view = camera.view();
+                let map_scale = camera.map_scale(*viewport);
+
+                programs.iter().for_each(|program| {
                     let mut program = program.borrow_mut();
+                    program.set_uniform("projection", &projection).unwrap();
+                    program.set_uniform("view", &view).unwrap();
 
-                    // Attach vertices
-                    program.attach_vertices(vertices, None).unwrap();
+                    // Only programs that work in screen space will make use of the viewport
+                    program.set_uniform("viewport", viewport).ok();
+                    program.set_uniform("map_scale", &map_scale).ok();
+                });
+            };
 
-                    if let Some(texture_buffer) = texture_buffer.as_ref() {
-                        // Attach texture buffer
-                        program.attach_texture_buffer(texture_buffer.clone());
+        // Notifies each render step of the new camera, re-uploading any that have changed
+        let update_render_steps =
+            |render_steps: &[(Rc<RefCell<dyn RenderStep>>, RenderStepPrograms)],
+             camera: &Camera,
+             viewport: Vec2| {
+                let camera_info = CameraInfo::new(camera.projection() * camera.view(), viewport);
+
+                for (render_step, programs) in render_steps.iter() {
+                    let mut render_step = render_step.borrow_mut();
+
+                    if render_step.update_camera(&camera_info) {
+                        programs.upload(&*render_step);
                     }
                 }
+            };
 
-                programs
-                    .into_iter()
-                    .enumerate()
-                    .map(move |(program_offset, p)| (render_step_id, program_offset, p))
-            })
-            .collect::<Vec<_>>();
-
-        let update_uniforms =
-            |programs: &[(usize, usize, Rc<RefCell<Program>>)], projection: &Mat4, view: &Mat4| {
-                programs
-                    .iter()
-                    .for_each(|(_render_step_id, _program_offset, program)| {
-                        let mut program = program.borrow_mut();
-                        program.set_uniform("projection", projection).unwrap();
-                        program.set_uniform("view", view).unwrap();
-                    });
+        // Re-uploads the render steps that were changed by an event
+        let upload_changed =
+            |render_steps: &[(Rc<RefCell<dyn RenderStep>>, RenderStepPrograms)],
+             changed: &[Rc<RefCell<dyn RenderStep>>]| {
+                for (render_step, programs) in render_steps.iter().filter(|(render_step, _)| {
+                    changed
+                        .iter()
+                        .any(|changed| Rc::ptr_eq(changed, render_step))
+                }) {
+                    programs.upload(&*render_step.borrow());
+                }
             };
 
+        let viewport = {
+            let (height, width) = self.window.get_size();
+            Vec2::new(width as f32, height as f32)
+        };
+
         // Provide initial uniforms
-        update_uniforms(
-            programs.as_slice(),
-            &self.camera.projection(),
-            &self.camera.view(),
-        );
+        update_uniforms(programs.as_slice(), &self.camera, &viewport);
+        update_render_steps(&render_steps, &self.camera, viewport);
 
         let mut mouse_location = Vec3::new(0.0, 0.0, 0.0);
         let mut dragging = false;
+
+        // Whether the map has been dragged since the mouse was pressed, in which case releasing it
+        // isn't a click
+        let mut dragged = false;
         let mut previous_normalised_screen_cursor = None;
 
         self.window.run(move |event, window_info| {
+            let viewport = Vec2::new(
+                window_info.size.width as f32,
+                window_info.size.height as f32,
+            );
+
             match event {
                 WindowEvent::Keyboard {
                     keycode,
                     state: ElementState::Pressed,
                 } => {
+                    // Event callback, which gets the first chance to use the key (such as when
+                    // typing into a prompt)
+                    let response = event_callback(Event::Keyboard(keycode));
+
                     // Update camera
-                    match keycode {
-                        VirtualKeyCode::Escape => {
-                            return Some(WindowAction::Close);
-                        }
-                        VirtualKeyCode::W => {
-                            self.camera.position.z += 1.0;
-                        }
-                        VirtualKeyCode::S => {
-                            self.camera.position.z -= 1.0;
-                        }
-                        VirtualKeyCode::A => {
-                            self.camera.position.x += 1.0;
+                    if !response.handled {
+                        // Taking control of the camera stops it flying anywhere
+                        self.flight = None;
+
+                        match keycode {
+                            VirtualKeyCode::Escape => {
+                                return Some(WindowAction::Close);
+                            }
+                            VirtualKeyCode::W => {
+                                self.camera.position.z += 1.0;
+                            }
+                            VirtualKeyCode::S => {
+                                self.camera.position.z -= 1.0;
+                            }
+                            VirtualKeyCode::A => {
+                                self.camera.position.x += 1.0;
+                            }
+                            VirtualKeyCode::D => {
+                                self.camera.position.x -= 1.0;
+                            }
+                            VirtualKeyCode::Q => {
+                                self.camera.pitch = (self.camera.pitch - PITCH_STEP).max(0.0);
+                            }
+                            VirtualKeyCode::E => {
+                                self.camera.pitch = (self.camera.pitch + PITCH_STEP).min(MAX_PITCH);
+                            }
+                            _ => (),
                         }
-                        VirtualKeyCode::D => {
-                            self.camera.position.x -= 1.0;
-                        }
-                        _ => (),
                     }
 
-                    update_uniforms(
-                        programs.as_slice(),
-                        &self.camera.projection(),
-                        &self.camera.view(),
-                    );
+                    if let Some((point, height)) = response.fly_to {
+                        self.flight = Some(Flight::new(&self.camera, point, height));
+                    }
 
-                    // Event callback
-                    event_callback(Event::Keyboard(keycode));
+                    update_uniforms(programs.as_slice(), &self.camera, &viewport);
+                    update_render_steps(&render_steps, &self.camera, viewport);
+                    upload_changed(&render_steps, &response.changed);
 
                     // Trigger redraw
                     return Some(WindowAction::RequestRedraw);
                 }
-                WindowEvent::MouseDown => {
-                    dragging = true;
+                WindowEvent::Character(c) => {
+                    let response = event_callback(Event::Character(c));
 
-                    event_callback(Event::Click(mouse_location));
+                    if let Some((point, height)) = response.fly_to {
+                        self.flight = Some(Flight::new(&self.camera, point, height));
+                    }
 
-                    // TODO: Remove
-                    programs
-                        .iter()
-                        .for_each(|(render_step_id, program_offset, program)| {
-                            let render_step = self.render_steps[*render_step_id].borrow();
-                            let vertices = render_step.get_vertices();
+                    // Changed labels have to be placed again before they can be drawn
+                    update_render_steps(&render_steps, &self.camera, viewport);
+                    upload_changed(&render_steps, &response.changed);
 
-                            program
-                                .borrow_mut()
-                                .attach_vertices(vertices[*program_offset].clone(), None)
-                                .unwrap();
-                        });
+                    return Some(WindowAction::RequestRedraw);
+                }
+                WindowEvent::Frame => {
+                    if let Some(flight) = self.flight.as_ref() {
+                        let (position, arrived) = flight.position(Instant::now());
+                        self.camera.position = position;
+
+                        update_uniforms(programs.as_slice(), &self.camera, &viewport);
+                        update_render_steps(&render_steps, &self.camera, viewport);
+
+                        // Keep drawing frames until the camera arrives
+                        if arrived {
+                            self.flight = None;
+                        } else {
+                            return Some(WindowAction::RequestRedraw);
+                        }
+                    }
+                }
+                WindowEvent::MouseDown => {
+                    self.flight = None;
+                    dragging = true;
+                    dragged = false;
                 }
                 WindowEvent::MouseUp => {
                     dragging = false;
+
+                    if !dragged {
+                        let response = event_callback(Event::Click(mouse_location));
+
+                        if let Some((point, height)) = response.fly_to {
+                            self.flight = Some(Flight::new(&self.camera, point, height));
+                        }
+
+                        // Changed labels have to be placed again before they can be drawn
+                        update_render_steps(&render_steps, &self.camera, viewport);
+                        upload_changed(&render_steps, &response.changed);
+
+                        return Some(WindowAction::RequestRedraw);
+                    }
                 }
                 WindowEvent::MouseMove {
                     physical_x,
@@ -220,47 +535,31 @@ impl Renderer {
                     let x = physical_x / window_info.scale;
                     let y = physical_y / window_info.scale;
 
-                    // https://gamedev.stackexchange.com/a/150425
-                    let vertical_span = f32::tan(0.5 * self.camera.fov);
-
                     let normalised_screen_cursor = Vec2::new(
                         ((x / size.width as f32) * 2.0) - 1.0,
                         1.0 - ((y / size.height as f32) * 2.0),
                     );
 
-                    // Convert camera 2D vector to view space
-                    let view_cursor = Vec3::new(
-                        normalised_screen_cursor.x * vertical_span,
-                        1.0,
-                        normalised_screen_cursor.y * vertical_span,
-                    );
-
-                    // Determine camera depth (map plane at y = 0)
-                    let depth = -self.camera.position.y;
-
-                    // Determine world cursor
-                    mouse_location = self.camera.position + (view_cursor * depth);
+                    // Determine world cursor (when tilted, the cursor may be above the horizon)
+                    let world_cursor = self.camera.screen_to_map(normalised_screen_cursor);
+                    if let Some(world_cursor) = world_cursor {
+                        mouse_location = world_cursor;
+                    }
 
-                    if let Some(previous_normalised_screen_cursor) =
+                    if let (Some(previous), Some(current)) = (
                         previous_normalised_screen_cursor
-                    {
-                        let screen_travel: Vec2 =
-                            previous_normalised_screen_cursor - normalised_screen_cursor;
-
-                        let world_travel = Vec3::new(
-                            screen_travel.x * depth * vertical_span,
-                            0.0,
-                            screen_travel.y * depth * vertical_span,
-                        );
-
-                        if dragging {
+                            .and_then(|previous| self.camera.screen_to_map(previous)),
+                        world_cursor,
+                    ) {
+                        // Move the camera so that the point under the cursor follows it
+                        let world_travel = previous - current;
+
+                        if dragging && world_travel != Vec3::ZERO {
+                            dragged = true;
                             self.camera.position += world_travel;
 
-                            update_uniforms(
-                                programs.as_slice(),
-                                &self.camera.projection(),
-                                &self.camera.view(),
-                            );
+                            update_uniforms(programs.as_slice(), &self.camera, &viewport);
+                            update_render_steps(&render_steps, &self.camera, viewport);
                         }
                     }
 
@@ -268,32 +567,28 @@ impl Renderer {
 
                     return Some(WindowAction::RequestRedraw);
                 }
-                WindowEvent::Scroll { x: _, y } => {
-                    if y.abs() != 0.0 {
-                        // Scroll values are kind of arbitrary, but seem to increase with more
-                        // 'momentum' or speed on the mouse wheel/trackpad. `zoom_magnitude_max`
-                        // provides an upperbound for this value, allowing it to be reduced to a
-                        // value between 0 and 1, causing the scroll speed to increase or
-                        // decrease depending on how fast the user is scrolling.
-                        let zoom_magnitude_max = 15.0;
-                        let zoom_speed_threshold =
-                            y.abs().min(zoom_magnitude_max) / zoom_magnitude_max;
-
-                        // Scale will be how much the zoom level will change as a result of the
-                        // zoom event. It must atleast be the same (1.0), and cannot change by more
-                        // than 25% of it's current value (0.25), the percentage of which is
-                        // determined by the scroll speed, as discussed above.
-                        let scale = 1.0 + (-0.25 * zoom_speed_threshold * y.signum());
-                        self.camera.position.y = (self.camera.position.y * scale).min(-1.0);
-
-                        update_uniforms(
-                            programs.as_slice(),
-                            &self.camera.projection(),
-                            &self.camera.view(),
-                        );
+                WindowEvent::Scroll { x: _, y } if y.abs() != 0.0 => {
+                    self.flight = None;
+
+                    // Scroll values are kind of arbitrary, but seem to increase with more
+                    // 'momentum' or speed on the mouse wheel/trackpad. `zoom_magnitude_max`
+                    // provides an upperbound for this value, allowing it to be reduced to a
+                    // value between 0 and 1, causing the scroll speed to increase or
+                    // decrease depending on how fast the user is scrolling.
+                    let zoom_magnitude_max = 15.0;
+                    let zoom_speed_threshold = y.abs().min(zoom_magnitude_max) / zoom_magnitude_max;
+
+                    // Scale will be how much the zoom level will change as a result of the
+                    // zoom event. It must atleast be the same (1.0), and cannot change by more
+                    // than 25% of it's current value (0.25), the percentage of which is
+                    // determined by the scroll speed, as discussed above.
+                    let scale = 1.0 + (-0.25 * zoom_speed_threshold * y.signum());
+                    self.camera.position.y = (self.camera.position.y * scale).min(-1.0);
+
+                    update_uniforms(programs.as_slice(), &self.camera, &viewport);
+                    update_render_steps(&render_steps, &self.camera, viewport);
 
-                        return Some(WindowAction::RequestRedraw);
-                    }
+                    return Some(WindowAction::RequestRedraw);
                 }
                 _ => (),
             }
@@ -302,4 +597,3 @@ impl Renderer {
         });
     }
 }
-
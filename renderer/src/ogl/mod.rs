@@ -1,8 +1,9 @@
 mod program;
+pub mod texture;
 pub mod texture_buffer;
 
-use glam::Vec4;
-use opengl::{BufferMask, Context, StringName};
+use glam::{UVec2, Vec4};
+use opengl::{BlendFactor, BufferMask, Capability, Context, StringName};
 pub use program::*;
 use std::{
     cell::RefCell,
@@ -11,7 +12,10 @@ use std::{
     rc::Rc,
 };
 
-use self::texture_buffer::{TextureBuffer, TextureBufferBuilder, TextureBufferBuilderError};
+use self::{
+    texture::Texture2d,
+    texture_buffer::{TextureBuffer, TextureBufferBuilder, TextureBufferBuilderError},
+};
 
 #[derive(Debug)]
 pub enum OpenGlError {
@@ -61,7 +65,9 @@ impl Debug for OpenGlInfo {
 
 pub struct OpenGl {
     gl: Rc<RefCell<Context>>,
-    programs: Vec<Rc<RefCell<Program>>>,
+
+    /// Programs in the order that they're drawn, with programs in the same group taking turns
+    programs: Vec<Vec<Rc<RefCell<Program>>>>,
 }
 
 impl OpenGl {
@@ -107,23 +113,64 @@ impl OpenGl {
         let program = Rc::new(RefCell::new(program));
 
         // Save the program
-        self.programs.push(program.clone());
+        self.programs.push(vec![program.clone()]);
 
         Ok(program)
     }
 
+    /// Adds programs that take turns drawing, so that what they draw can be layered between each
+    /// other. The first range of vertices (given by [`DrawArrays`]) is drawn by each program, then
+    /// the second range by each program, and so on.
+    pub fn add_interleaved_programs(
+        &mut self,
+        builders: Vec<ProgramBuilder>,
+    ) -> Result<Vec<Rc<RefCell<Program>>>, ProgramBuilderError> {
+        let programs = builders
+            .into_iter()
+            .map(|builder| {
+                let program = builder.with_gl(self.gl.clone()).build()?;
+
+                Ok(Rc::new(RefCell::new(program)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        self.programs.push(programs.clone());
+
+        Ok(programs)
+    }
+
     pub fn render(&self) {
         let gl = self.gl.borrow();
 
         gl.clear_color(Vec4::new(1.0, 1.0, 1.0, 1.0));
         gl.clear(BufferMask::Color);
+        gl.clear(BufferMask::Depth);
+
+        // Allow programs to draw partially transparent fragments (eg anti-aliased text)
+        gl.enable(Capability::Blend);
+        gl.blend_func(BlendFactor::SrcAlpha, BlendFactor::OneMinusSrcAlpha);
 
         // gl.polygon_mode(
         //     glow::FRONT_AND_BACK,
         //     if wireframe { glow::LINE } else { glow::FILL },
         // );
-        for program in &self.programs {
-            program.borrow().render();
+        for programs in &self.programs {
+            if let [program] = programs.as_slice() {
+                program.borrow().render();
+                continue;
+            }
+
+            let range_count = programs
+                .iter()
+                .map(|program| program.borrow().range_count())
+                .max()
+                .unwrap_or_default();
+
+            for range in 0..range_count {
+                for program in programs {
+                    program.borrow().render_range(range);
+                }
+            }
         }
     }
 
@@ -134,6 +181,10 @@ impl OpenGl {
         builder.build(self.gl.clone())
     }
 
+    pub fn create_texture_2d(&self, size: UVec2) -> Result<Texture2d, opengl::OpenGlError> {
+        Texture2d::new(self.gl.clone(), size)
+    }
+
     pub fn clear_program(&self) {
         self.gl.borrow().clear_program();
     }
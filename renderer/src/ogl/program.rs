@@ -1,5 +1,5 @@
-use super::{texture_buffer::TextureBuffer, OpenGlError};
-use glam::{Mat4, Vec3};
+use super::{texture::Texture2d, texture_buffer::TextureBuffer, OpenGlError};
+use glam::{Mat4, Vec2, Vec3};
 use opengl::{Buffer, BufferType, Capability, Context, Location, ShaderType, VertexArrayObject};
 use std::{
     cell::RefCell, collections::HashMap, error::Error, fmt::Display, fs, path::Path, rc::Rc,
@@ -15,6 +15,12 @@ impl UniformValue for f32 {
     }
 }
 
+impl UniformValue for Vec2 {
+    fn set_uniform(&self, gl: &Context, location: Location) {
+        gl.uniform_vec2(location, self)
+    }
+}
+
 impl UniformValue for Mat4 {
     fn set_uniform(&self, gl: &Context, location: Location) {
         gl.uniform_mat4(location, self)
@@ -27,6 +33,7 @@ pub enum DrawType {
     Points,
     Lines,
     LineStrip,
+    LinesAdjacency,
 }
 impl From<DrawType> for opengl::DrawType {
     fn from(value: DrawType) -> Self {
@@ -35,6 +42,7 @@ impl From<DrawType> for opengl::DrawType {
             DrawType::Points => opengl::DrawType::Points,
             DrawType::Lines => opengl::DrawType::Lines,
             DrawType::LineStrip => opengl::DrawType::LineStrip,
+            DrawType::LinesAdjacency => opengl::DrawType::LinesAdjacency,
         }
     }
 }
@@ -64,6 +72,7 @@ pub struct ProgramBuilder {
     shaders: Vec<(ShaderType, String)>,
     vertex_format: Vec<VertexFormat>,
     draw_type: Option<DrawType>,
+    depth_test: bool,
 }
 impl ProgramBuilder {
     pub fn new() -> Self {
@@ -90,6 +99,13 @@ impl ProgramBuilder {
         self
     }
 
+    /// Only draw fragments that are in front of what has already been drawn, rather than in the
+    /// order that they're rendered.
+    pub fn with_depth_test(mut self, depth_test: bool) -> Self {
+        self.depth_test = depth_test;
+        self
+    }
+
     pub fn build(self) -> Result<Program, ProgramBuilderError> {
         let gl = self.gl.ok_or(ProgramBuilderError::MissingGl)?;
 
@@ -170,14 +186,24 @@ impl ProgramBuilder {
             // Configure all of the attributes
             for (i, (format, offset)) in self.vertex_format.iter().zip(offsets).enumerate() {
                 gl.enable_vertex_attribute_array(i as u32);
-                gl.vertex_attribute_pointer_f32(
-                    i as u32,
-                    format.count,
-                    (&format.vertex_type).into(),
-                    false,
-                    vertex_step,
-                    offset,
-                );
+                match format.vertex_type {
+                    VertexType::Float => gl.vertex_attribute_pointer_f32(
+                        i as u32,
+                        format.count,
+                        (&format.vertex_type).into(),
+                        false,
+                        vertex_step,
+                        offset,
+                    ),
+                    // Integer attributes must not be converted to floats
+                    VertexType::UInt => gl.vertex_attribute_pointer_i32(
+                        i as u32,
+                        format.count,
+                        (&format.vertex_type).into(),
+                        vertex_step,
+                        offset,
+                    ),
+                }
             }
 
             (program, vertex_buffer, vertex_array_object)
@@ -193,8 +219,10 @@ impl ProgramBuilder {
             vertex_format: self.vertex_format,
             uniform_locations: HashMap::new(),
             draw_type: self.draw_type.unwrap_or(DrawType::Triangles),
+            depth_test: self.depth_test,
             draw_arrays: None,
             texture_buffer: None,
+            texture: None,
         })
     }
 }
@@ -225,6 +253,15 @@ pub trait VertexData {
     fn get_bytes(&self) -> Vec<u8>;
 }
 
+impl VertexData for Vec2 {
+    fn get_bytes(&self) -> Vec<u8> {
+        self.to_array()
+            .iter()
+            .flat_map(|n| n.to_ne_bytes())
+            .collect()
+    }
+}
+
 impl VertexData for Vec3 {
     fn get_bytes(&self) -> Vec<u8> {
         self.to_array()
@@ -258,8 +295,10 @@ pub struct Program {
     vertex_format: Vec<VertexFormat>,
     uniform_locations: HashMap<String, Location>,
     draw_type: DrawType,
+    depth_test: bool,
     draw_arrays: Option<DrawArrays>,
     texture_buffer: Option<Rc<TextureBuffer>>,
+    texture: Option<Rc<Texture2d>>,
 }
 
 impl Program {
@@ -307,38 +346,91 @@ impl Program {
         gl.use_program(self.program);
     }
 
-    pub fn render(&self) {
+    /// Binds the program along with its vertices and textures, ready to draw.
+    fn bind(&self) {
         self.use_program();
 
         let gl = self.gl.borrow();
         gl.enable(Capability::ProgramPointSize);
-        if let Some(vertex_count) = self.vertex_count {
-            // Rebind vertex array
-            gl.bind_vertex_array(self.vertex_array_object);
-
-            // Bind buffer texture
-            if let (Some(texture_buffer), Some(sampler_location)) = (
-                &self.texture_buffer,
-                gl.get_uniform_location(self.program, "path_data"),
-            ) {
-                // TODO: Work out better way to deal with this
-                let texture_number = 0;
-
-                gl.active_texture(texture_number);
-                texture_buffer.bind(texture_number);
-                gl.uniform_i32(sampler_location, texture_number as i32);
-            }
+        if self.depth_test {
+            gl.enable(Capability::DepthTest);
+        } else {
+            gl.disable(Capability::DepthTest);
+        }
 
-            if let Some(DrawArrays { first, count }) = self.draw_arrays.as_ref() {
-                // glow doesn't support glMultiDrawArrays, but *alegedly* this has the same
-                // performance impact
-                for (&first, &count) in first.iter().zip(count.iter()) {
-                    gl.draw_arrays(self.draw_type.into(), first, count);
-                }
-            } else {
-                gl.draw_arrays(self.draw_type.into(), 0, vertex_count);
+        // Rebind vertex array
+        gl.bind_vertex_array(self.vertex_array_object);
+
+        // Bind buffer texture
+        if let (Some(texture_buffer), Some(sampler_location)) = (
+            &self.texture_buffer,
+            gl.get_uniform_location(self.program, "path_data"),
+        ) {
+            // TODO: Work out better way to deal with this
+            let texture_number = 0;
+
+            gl.active_texture(texture_number);
+            texture_buffer.bind(texture_number);
+            gl.uniform_i32(sampler_location, texture_number as i32);
+        }
+
+        // Bind 2D texture, after the buffer texture
+        if let (Some(texture), Some(sampler_location)) = (
+            &self.texture,
+            gl.get_uniform_location(self.program, "image"),
+        ) {
+            let texture_number = 1;
+
+            texture.bind(texture_number);
+            gl.uniform_i32(sampler_location, texture_number as i32);
+        }
+    }
+
+    pub fn render(&self) {
+        let Some(vertex_count) = self.vertex_count else {
+            return;
+        };
+
+        self.bind();
+
+        let gl = self.gl.borrow();
+        if let Some(DrawArrays { first, count }) = self.draw_arrays.as_ref() {
+            // glow doesn't support glMultiDrawArrays, but *alegedly* this has the same
+            // performance impact
+            for (&first, &count) in first.iter().zip(count.iter()) {
+                gl.draw_arrays(self.draw_type.into(), first, count);
             }
+        } else {
+            gl.draw_arrays(self.draw_type.into(), 0, vertex_count);
+        }
+    }
+
+    /// Number of ranges of vertices that the program draws, as given by [`DrawArrays`].
+    pub fn range_count(&self) -> usize {
+        self.draw_arrays
+            .as_ref()
+            .map_or(0, |draw_arrays| draw_arrays.count.len())
+    }
+
+    /// Draws a single range of vertices, as given by [`DrawArrays`].
+    pub fn render_range(&self, index: usize) {
+        let Some(DrawArrays { first, count }) = self.draw_arrays.as_ref() else {
+            return;
+        };
+
+        let (Some(&first), Some(&count)) = (first.get(index), count.get(index)) else {
+            return;
+        };
+
+        // Skip binding the program when there's nothing to draw
+        if count == 0 {
+            return;
         }
+
+        self.bind();
+
+        let gl = self.gl.borrow();
+        gl.draw_arrays(self.draw_type.into(), first, count);
     }
 
     pub fn attach_vertices(
@@ -380,6 +472,11 @@ impl Program {
         self.texture_buffer = Some(texture_buffer);
     }
 
+    /// Attaches a 2D texture, which shaders can sample as the `image` uniform.
+    pub fn attach_texture(&mut self, texture: Rc<Texture2d>) {
+        self.texture = Some(texture);
+    }
+
     pub fn set_uniform(
         &mut self,
         name: &str,
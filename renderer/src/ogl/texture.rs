@@ -0,0 +1,68 @@
+use glam::UVec2;
+use opengl::{
+    Context, ImageFormat, OpenGlError, PixelFormat, Texture, TextureParameter,
+    TextureParameterValue, TextureTarget,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// RGBA 2D texture, which can be updated a region at a time.
+pub struct Texture2d {
+    gl: Rc<RefCell<Context>>,
+    texture: Texture,
+    size: UVec2,
+}
+
+impl Texture2d {
+    pub(super) fn new(gl: Rc<RefCell<Context>>, size: UVec2) -> Result<Self, OpenGlError> {
+        let texture = {
+            let gl = gl.borrow();
+            let texture = gl.create_texture()?;
+
+            gl.bind_texture(TextureTarget::_2d, texture);
+            gl.tex_image_2d(TextureTarget::_2d, ImageFormat::RGBA8, size.x, size.y);
+
+            // Smoothly scale the texture, without wrapping around at the edges
+            for (parameter, value) in [
+                (TextureParameter::MinFilter, TextureParameterValue::Linear),
+                (TextureParameter::MagFilter, TextureParameterValue::Linear),
+                (TextureParameter::WrapS, TextureParameterValue::ClampToEdge),
+                (TextureParameter::WrapT, TextureParameterValue::ClampToEdge),
+            ] {
+                gl.tex_parameter(TextureTarget::_2d, parameter, value);
+            }
+
+            texture
+        };
+
+        Ok(Self { gl, texture, size })
+    }
+
+    pub fn size(&self) -> UVec2 {
+        self.size
+    }
+
+    pub fn bind(&self, texture_number: u32) {
+        let gl = self.gl.borrow();
+
+        gl.active_texture(texture_number);
+        gl.bind_texture(TextureTarget::_2d, self.texture);
+    }
+
+    /// Copies RGBA pixels (one byte per channel, in row major order) into a region of the texture.
+    pub fn set_region(&self, position: UVec2, size: UVec2, pixels: &[u8]) {
+        debug_assert_eq!(pixels.len() as u32, size.x * size.y * 4);
+
+        let gl = self.gl.borrow();
+
+        gl.bind_texture(TextureTarget::_2d, self.texture);
+        gl.tex_sub_image_2d_u8_slice(
+            TextureTarget::_2d,
+            position.x,
+            position.y,
+            size.x,
+            size.y,
+            PixelFormat::Rgba,
+            pixels,
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use glam::UVec2;
+
+/// Padding (in texels) left around every image packed into the atlas, so that sampling around the
+/// edge of an image (eg for label halos) never bleeds into its neighbour.
+const PADDING: u32 = 6;
+
+/// Image atlas, packed row by row ('shelves'), with the height growing as images are added. The
+/// data is stored as `f32`s so that it can be uploaded directly as a texture buffer, with
+/// `channels` values per texel.
+pub(crate) struct Atlas {
+    width: u32,
+    height: u32,
+    channels: u32,
+    data: Vec<f32>,
+
+    /// Position and height of the shelf currently being filled
+    cursor: UVec2,
+    shelf_height: u32,
+}
+
+impl Atlas {
+    pub fn new(width: u32, channels: u32) -> Self {
+        Self {
+            width,
+            height: 0,
+            channels,
+            data: Vec::new(),
+            cursor: UVec2::new(PADDING, PADDING),
+            shelf_height: 0,
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Copies an image into the atlas, returning the position of its top left corner. `pixels`
+    /// must contain `size.x * size.y * channels` values, in row major order.
+    pub fn insert(&mut self, size: UVec2, pixels: &[f32]) -> UVec2 {
+        debug_assert_eq!(pixels.len() as u32, size.x * size.y * self.channels);
+
+        // Start a new shelf if the image won't fit on the current one
+        if self.cursor.x + size.x + PADDING > self.width {
+            self.cursor = UVec2::new(PADDING, self.cursor.y + self.shelf_height + PADDING);
+            self.shelf_height = 0;
+        }
+
+        let position = self.cursor;
+
+        self.cursor.x += size.x + PADDING;
+        self.shelf_height = self.shelf_height.max(size.y);
+
+        // Grow the atlas to fit the shelf
+        let required_height = position.y + self.shelf_height + PADDING;
+        if required_height > self.height {
+            self.height = required_height;
+            self.data
+                .resize((self.width * self.height * self.channels) as usize, 0.0);
+        }
+
+        for y in 0..size.y {
+            let source = (y * size.x * self.channels) as usize;
+            let destination =
+                (((position.y + y) * self.width + position.x) * self.channels) as usize;
+            let length = (size.x * self.channels) as usize;
+
+            self.data[destination..destination + length]
+                .copy_from_slice(&pixels[source..source + length]);
+        }
+
+        position
+    }
+
+    pub fn get_bytes(&self) -> Vec<u8> {
+        self.data
+            .iter()
+            .flat_map(|value| value.to_ne_bytes())
+            .collect()
+    }
+}
@@ -1,15 +1,24 @@
 use crate::{
-    ogl::{DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    ogl::{DrawArrays, DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
     RenderStep,
 };
 use glam::Vec3;
-use std::{cell::RefCell, rc::Rc};
+use std::{cell::RefCell, collections::BTreeMap, rc::Rc};
+
+/// Maximum number of lengths in a dash pattern
+const MAX_DASH_LENGTH: usize = 4;
+
+/// Distance (in pixels) between symbols along a decorated path, unless otherwise specified
+pub const DEFAULT_DECORATION_SPACING: f32 = 100.0;
 
 #[derive(Default)]
 pub struct Path {
     points: Vec<Vec3>,
     stroke: Option<Stroke>,
     fill: Option<Fill>,
+    decoration: Option<Decoration>,
+    layer: i32,
+    z_index: i32,
 }
 
 impl Path {
@@ -31,12 +40,45 @@ impl Path {
 
         self
     }
+
+    /// Opacity (between 0 and 1) of the fill added with [`Path::with_fill`], so that whatever is
+    /// underneath shows through.
+    pub fn with_fill_opacity(mut self, opacity: f32) -> Self {
+        debug_assert!(self.fill.is_some(), "fill must be added first");
+
+        if let Some(fill) = &mut self.fill {
+            fill.opacity = opacity;
+        }
+
+        self
+    }
+
+    /// Symbols repeated along the path, such as oneway arrows. Paths can be decorated without a
+    /// stroke, so that the symbols can go in a different direction to the path.
+    pub fn with_decoration(mut self, decoration: Decoration) -> Self {
+        self.decoration = Some(decoration);
+
+        self
+    }
+
+    /// Vertical layer that the path is on (eg bridges above, tunnels below). Paths on higher
+    /// layers are always drawn over paths on lower layers.
+    pub fn with_layer(mut self, layer: i32) -> Self {
+        self.layer = layer;
+
+        self
+    }
+
+    /// Order that paths within the same layer are drawn in, with higher values drawn on top.
+    pub fn with_z_index(mut self, z_index: i32) -> Self {
+        self.z_index = z_index;
+
+        self
+    }
 }
 
 impl CanvasObject for Path {
     fn get_vertices(&self) -> Vec<Vec3> {
-        // TODO: Generate dashed segments here?
-
         self.points.clone()
     }
 
@@ -47,136 +89,282 @@ impl CanvasObject for Path {
     fn get_fill(&self) -> Option<Fill> {
         self.fill.clone()
     }
+
+    fn get_decoration(&self) -> Option<Decoration> {
+        self.decoration.clone()
+    }
+
+    fn get_draw_order(&self) -> (i32, i32) {
+        (self.layer, self.z_index)
+    }
+}
+
+/// Units that a stroke's width is measured in.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum StrokeUnits {
+    /// Constant width on screen, regardless of zoom
+    #[default]
+    Pixels,
+    /// Scales with the map, like any other geometry
+    World,
+}
+
+/// Shape drawn where two segments of a path meet.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum LineJoin {
+    /// Extends the outer edges until they meet, falling back to a bevel for sharp corners
+    #[default]
+    Miter,
+    Bevel,
+    Round,
+}
+
+/// Shape drawn at the ends of an open path.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum LineCap {
+    #[default]
+    Butt,
+    Round,
+    /// Extends past the end by half of the stroke width
+    Square,
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct Stroke {
     width: f32,
-    dash: Option<f32>,
+    units: StrokeUnits,
+    join: LineJoin,
+    cap: LineCap,
+    dash: Vec<f32>,
     color: Vec3,
 }
 impl Stroke {
+    /// Stroke with a width in pixels. Use [`Stroke::with_units`] for a width in world units.
     pub fn new(width: f32, color: Vec3) -> Self {
         Self {
             width,
             color,
-            dash: None,
+            ..Self::default()
         }
     }
 
-    pub fn with_dash(mut self, dash: f32) -> Self {
-        self.dash = Some(dash);
+    pub fn width(&self) -> f32 {
+        self.width
+    }
+
+    pub fn color(&self) -> Vec3 {
+        self.color
+    }
+
+    pub fn with_color(mut self, color: Vec3) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    pub fn is_dashed(&self) -> bool {
+        !self.dash.is_empty()
+    }
+
+    pub fn with_units(mut self, units: StrokeUnits) -> Self {
+        self.units = units;
+
+        self
+    }
+
+    pub fn with_join(mut self, join: LineJoin) -> Self {
+        self.join = join;
+
+        self
+    }
+
+    pub fn with_cap(mut self, cap: LineCap) -> Self {
+        self.cap = cap;
+
+        self
+    }
+
+    /// Alternating lengths of dashes and gaps (in the same units as the width), which continue
+    /// along the entire path. Patterns with an odd number of lengths are repeated to make them
+    /// even, as in SVG.
+    pub fn with_dash(mut self, pattern: &[f32]) -> Self {
+        self.dash = if pattern.len() % 2 == 1 {
+            pattern.repeat(2)
+        } else {
+            pattern.to_vec()
+        };
+
+        assert!(
+            self.dash.len() <= MAX_DASH_LENGTH,
+            "dash pattern can have at most {MAX_DASH_LENGTH} lengths"
+        );
 
         self
     }
 }
 
-pub fn point_in_triangle(p: Vec3, a: Vec3, b: Vec3, c: Vec3) -> bool {
-    let triangle_area = (b - a).cross(c - a).length() / 2.0;
-    let alpha = (b - p).cross(c - p).length() / (2.0 * triangle_area);
-    let beta = (c - p).cross(a - p).length() / (2.0 * triangle_area);
-    let gamma = (b - p).cross(a - p).length() / (2.0 * triangle_area);
+/// Shape repeated along a path by a [`Decoration`], pointing along the path.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Symbol {
+    /// Line with an arrow head
+    #[default]
+    Arrow,
+    /// Open arrow head (`>`)
+    Chevron,
+    /// Solid arrow head
+    Triangle,
+}
 
-    (0.0..=1.0).contains(&alpha)
-        && (0.0..=1.0).contains(&beta)
-        && (0.0..=1.0).contains(&gamma)
-        && alpha + beta + gamma == 1.0
+#[derive(Clone, Debug, Default)]
+pub struct Decoration {
+    symbol: Symbol,
+    size: f32,
+    spacing: f32,
+    color: Vec3,
+    reversed: bool,
+}
+impl Decoration {
+    /// Symbols with a length in pixels, which are repeated every [`DEFAULT_DECORATION_SPACING`]
+    /// pixels along the path.
+    pub fn new(symbol: Symbol, size: f32, color: Vec3) -> Self {
+        Self {
+            symbol,
+            size,
+            spacing: DEFAULT_DECORATION_SPACING,
+            color,
+            reversed: false,
+        }
+    }
+
+    /// Distance (in pixels) between the middle of each symbol. The spacing stays the same on
+    /// screen at any zoom, with symbols left out where a path is too short to fit one.
+    pub fn with_spacing(mut self, spacing: f32) -> Self {
+        self.spacing = spacing;
+
+        self
+    }
+
+    /// Points the symbols towards the start of the path instead of the end.
+    pub fn with_reversed(mut self, reversed: bool) -> Self {
+        self.reversed = reversed;
+
+        self
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct Fill {
     indexes: Vec<usize>,
     fill: Vec3,
+    opacity: f32,
 }
 impl Fill {
+    /// Fill of the area enclosed by an outline, which is split into triangles so that it can be
+    /// drawn. Outlines may be concave, and may or may not repeat the first point at the end.
     pub fn new(fill: Vec3, outline: &[Vec3]) -> Self {
-        // TODO: temp, rmeove duplicated end point
-        let outline = &outline[0..outline.len() - 1];
-
-        // Determine path direction (clockwise/anti-clockwise)
-        let smallest = outline
+        let closed = outline.len() > 1 && outline.first() == outline.last();
+        let outline = if closed {
+            &outline[..outline.len() - 1]
+        } else {
+            outline
+        };
+
+        // Polygons are on the y=0 plane
+        let coordinates = outline
             .iter()
-            .enumerate()
-            .reduce(|(smallest_i, smallest), (i, node)| {
-                if node.z < smallest.z || (node.z == smallest.z && node.x < smallest.x) {
-                    (i, node)
-                } else {
-                    (smallest_i, smallest)
-                }
-            })
-            .unwrap();
-        let left_side = outline[(smallest.0 + outline.len() - 1) % outline.len()] - *smallest.1;
-        let right_side = outline[(smallest.0 + outline.len() + 1) % outline.len()] - *smallest.1;
-        let direction = left_side.cross(right_side).length().signum();
-
-        let mut indexes = Vec::new();
-        let mut remaining_indexes = (0..outline.len()).collect::<Vec<_>>();
-
-        let mut i = 0;
-        'point_loop: while remaining_indexes.len() > 3 {
-            let left_i = remaining_indexes[i % remaining_indexes.len()];
-            let center_i = remaining_indexes[(i + 1) % remaining_indexes.len()];
-            let right_i = remaining_indexes[(i + 2) % remaining_indexes.len()];
-
-            let left = outline[left_i];
-            let center = outline[center_i];
-            let right = outline[right_i];
-
-            // Check angle between center point
-            let left_side = left - center;
-            let right_side = right - center;
-
-            // Assumes that polygon is on y=0 plane
-            let cross = left_side.cross(right_side);
-
-            if cross.length().signum() == direction {
-                // Internal angle
-                for &index in remaining_indexes.iter() {
-                    let p = outline[index];
-                    if p == left || p == center || p == right {
-                        continue;
-                    }
-
-                    if point_in_triangle(p, left, center, right) {
-                        i += 1;
-                        continue 'point_loop;
-                    }
-                }
-
-                // If reached here, everything is valid
-                indexes.extend_from_slice({
-                    let c = remaining_indexes.remove((i + 1) % remaining_indexes.len());
-
-                    &[left_i, c, right_i]
-                });
-
-                continue;
-            }
+            .flat_map(|point| [point.x, point.z])
+            .collect::<Vec<_>>();
 
-            i += 1;
-        }
+        // Invalid outlines (eg with too few points) are left unfilled
+        let indexes = earcutr::earcut(&coordinates, &[], 2).unwrap_or_default();
 
-        indexes.extend(remaining_indexes.into_iter());
-
-        Fill { indexes, fill }
+        Fill {
+            indexes,
+            fill,
+            opacity: 1.0,
+        }
     }
 }
 
+/// Splits a line into segments, each with the points either side of it so that joins can be
+/// drawn (`prev, start, end, next`). The ends of an open line are marked by repeating the end
+/// point, whilst closed lines wrap around. Each point is paired with its distance along the line,
+/// so that dash patterns continue between segments.
+fn segments_with_adjacency(points: &[Vec3]) -> Vec<(Vec3, f32)> {
+    let closed = points.len() > 2 && points.first() == points.last();
+
+    let distances = points
+        .iter()
+        .scan(
+            (points.first().cloned().unwrap_or_default(), 0.0),
+            |(last, distance), &p| {
+                *distance += last.distance(p);
+                *last = p;
+
+                Some(*distance)
+            },
+        )
+        .collect::<Vec<_>>();
+
+    points
+        .windows(2)
+        .enumerate()
+        .flat_map(|(i, segment)| {
+            let (start, end) = (segment[0], segment[1]);
+
+            let prev = match i {
+                0 if closed => points[points.len() - 2],
+                0 => start,
+                _ => points[i - 1],
+            };
+            let next = match points.get(i + 2) {
+                Some(&next) => next,
+                None if closed => points[1],
+                None => end,
+            };
+
+            // Only the distance at either end of the segment is used
+            [
+                (prev, distances[i]),
+                (start, distances[i]),
+                (end, distances[i + 1]),
+                (next, distances[i + 1]),
+            ]
+        })
+        .collect()
+}
+
 pub trait CanvasObject {
     fn get_vertices(&self) -> Vec<Vec3>;
 
     fn get_stroke(&self) -> Option<Stroke>;
     fn get_fill(&self) -> Option<Fill>;
+    fn get_decoration(&self) -> Option<Decoration>;
+
+    /// Layer and z-index of the object, which are compared in that order to determine which
+    /// objects are drawn on top.
+    fn get_draw_order(&self) -> (i32, i32);
 }
 
 #[derive(Default)]
 pub struct CanvasProgram {
-    objects: Vec<Box<dyn CanvasObject>>,
+    /// Objects grouped by their draw order, with objects in the same group drawn in the order that
+    /// they were added. Each group is drawn in full (fills, then outlines, then decorations)
+    /// before the next group.
+    objects: BTreeMap<(i32, i32), Vec<Box<dyn CanvasObject>>>,
 }
 
 impl CanvasProgram {
     pub fn add_object(&mut self, object: Box<dyn CanvasObject>) {
-        self.objects.push(object);
+        self.objects
+            .entry(object.get_draw_order())
+            .or_default()
+            .push(object);
+    }
+
+    /// All objects, in the order that they should be drawn
+    fn objects(&self) -> impl Iterator<Item = &Box<dyn CanvasObject>> {
+        self.objects.values().flatten()
     }
 
     pub fn clear(&mut self) {
@@ -186,85 +374,159 @@ impl CanvasProgram {
 
 impl RenderStep for CanvasProgram {
     fn get_vertices(&self) -> Vec<Vec<u8>> {
-        let (fill, outline): (Vec<Vec<u8>>, Vec<Vec<u8>>) = self
+        let mut fill = Vec::new();
+        let mut outline = Vec::new();
+        let mut decoration = Vec::new();
+
+        for (id, object) in self.objects().enumerate() {
+            let id = id as u32;
+
+            let outline_vertices = object.get_vertices();
+
+            let fill_vertices = object
+                .get_fill()
+                .as_ref()
+                .map(|fill| {
+                    fill.indexes
+                        .iter()
+                        .map(|&i| outline_vertices[i])
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+
+            // Get the vertices for the object and give them an ID
+            fill.extend(
+                fill_vertices
+                    .into_iter()
+                    .flat_map(|vertex| [&id.to_ne_bytes(), vertex.get_bytes().as_slice()].concat()),
+            );
+
+            let segments = segments_with_adjacency(&outline_vertices)
+                .into_iter()
+                .flat_map(|(vertex, distance)| {
+                    [
+                        &id.to_ne_bytes(),
+                        vertex.get_bytes().as_slice(),
+                        &distance.to_ne_bytes(),
+                    ]
+                    .concat()
+                })
+                .collect::<Vec<_>>();
+
+            // Decorations are placed along the same segments as the outline
+            if object.get_decoration().is_some() {
+                decoration.extend_from_slice(&segments);
+            }
+
+            outline.extend(segments);
+        }
+
+        vec![fill, outline, decoration]
+    }
+
+    fn get_draw_arrays(&self) -> Option<Vec<DrawArrays>> {
+        // Number of fill, outline and decoration vertices in each group, matching the vertices
+        // above (with 4 vertices for each segment of a path)
+        let counts = self
             .objects
-            .iter()
-            .enumerate()
-            .map(|(id, object)| {
-                let id = id as u32;
-
-                let outline_vertices = object.get_vertices();
-
-                let fill_vertices = object
-                    .get_fill()
-                    .as_ref()
-                    .map(|fill| {
-                        fill.indexes
-                            .iter()
-                            .map(|&i| outline_vertices[i])
-                            .collect::<Vec<_>>()
+            .values()
+            .map(|objects| {
+                objects
+                    .iter()
+                    .fold([0; 3], |[fill, outline, decoration], object| {
+                        let segments = 4 * object.get_vertices().len().saturating_sub(1) as u32;
+
+                        [
+                            fill + object
+                                .get_fill()
+                                .map_or(0, |fill| fill.indexes.len() as u32),
+                            outline + segments,
+                            decoration + object.get_decoration().map_or(0, |_| segments),
+                        ]
                     })
-                    .unwrap_or_default();
-
-                // Get the vertices for the object and give them an ID
-                (
-                    fill_vertices
-                        .into_iter()
-                        .flat_map(move |vertex| {
-                            [&id.to_ne_bytes(), vertex.get_bytes().as_slice()].concat()
-                        })
-                        .collect::<Vec<_>>(),
-                    outline_vertices
-                        .into_iter()
-                        .flat_map(move |vertex| {
-                            [&id.to_ne_bytes(), vertex.get_bytes().as_slice()].concat()
-                        })
-                        .collect::<Vec<_>>(),
-                )
             })
-            .unzip();
+            .collect::<Vec<_>>();
 
-        vec![fill.concat(), outline.concat()]
+        Some(
+            (0..3)
+                .map(|program| {
+                    DrawArrays::new_continuous(counts.iter().map(|count| count[program]).collect())
+                })
+                .collect(),
+        )
     }
 
-    fn build_programs(&self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
-        let vertex_format = &[
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        let fill_format = vec![
             // ID
             VertexFormat::new(1, VertexType::UInt),
             // Vertex
             VertexFormat::new(3, VertexType::Float),
         ];
 
-        [
-            ("canvas_fill", DrawType::Triangles),
-            ("canvas_outline", DrawType::LineStrip),
+        let outline_format = [
+            fill_format.as_slice(),
+            &[
+                // Distance along the path
+                VertexFormat::new(1, VertexType::Float),
+            ],
+        ]
+        .concat();
+
+        let builders = [
+            ("canvas_fill", fill_format, DrawType::Triangles),
+            (
+                "canvas_outline",
+                outline_format.clone(),
+                DrawType::LinesAdjacency,
+            ),
+            (
+                "canvas_decoration",
+                outline_format,
+                DrawType::LinesAdjacency,
+            ),
         ]
         .into_iter()
-        .map(|(directory, draw_type)| {
-            gl.add_program(
-                Program::from_directory(&format!("renderer/src/shaders/{}", directory))
-                    .unwrap()
-                    .with_format(vertex_format)
-                    .with_draw_type(draw_type),
-            )
-            .unwrap()
+        .map(|(directory, vertex_format, draw_type)| {
+            Program::from_directory(&format!("renderer/src/shaders/{}", directory))
+                .unwrap()
+                .with_format(&vertex_format)
+                .with_draw_type(draw_type)
         })
-        .collect()
+        .collect();
+
+        // Programs take turns drawing each group of objects, so that (for example) the fill of a
+        // polygon covers the outline of a path on a lower layer
+        gl.add_interleaved_programs(builders).unwrap()
     }
 
     fn get_texture_buffer(&self) -> Option<Vec<u8>> {
         Some(
-            self.objects
-                .iter()
+            self.objects()
                 .flat_map(|object| {
                     let stroke = object.get_stroke();
                     let fill = object.get_fill();
+                    let decoration = object.get_decoration();
 
                     [
                         {
-                            [stroke.is_some(), fill.is_some()]
-                                .into_iter()
-                                .fold(0u32, |packed, value| (packed << 1) | (value as u32))
+                            let style = stroke.as_ref().map(|stroke| {
+                                (stroke.join as u32)
+                                    | ((stroke.cap as u32) << 2)
+                                    | ((stroke.units as u32) << 4)
+                            });
+
+                            let decoration_style = decoration.as_ref().map(|decoration| {
+                                1 | ((decoration.symbol as u32) << 1)
+                                    | ((decoration.reversed as u32) << 3)
+                            });
+
+                            // Bits 0-1 are flags, with the stroke style (bits 2-6) and decoration
+                            // (bits 7-10) packed in above them
+                            ((decoration_style.unwrap_or_default() << 7)
+                                | (style.unwrap_or_default() << 2)
+                                | ((stroke.is_some() as u32) << 1)
+                                | (fill.is_some() as u32))
                                 .to_ne_bytes()
                                 .as_slice()
                         },
@@ -274,7 +536,13 @@ impl RenderStep for CanvasProgram {
                             [
                                 stroke.color.get_bytes().as_slice(),
                                 stroke.width.to_ne_bytes().as_slice(),
-                                stroke.dash.unwrap_or_default().to_ne_bytes().as_slice(),
+                                {
+                                    // Unused lengths are left as 0
+                                    let mut dash = [0.0; MAX_DASH_LENGTH];
+                                    dash[..stroke.dash.len()].copy_from_slice(&stroke.dash);
+
+                                    dash.map(f32::to_ne_bytes).concat().as_slice()
+                                },
                             ]
                             .concat()
                             .as_slice()
@@ -282,7 +550,23 @@ impl RenderStep for CanvasProgram {
                         {
                             let fill = fill.unwrap_or_default();
 
-                            fill.fill.get_bytes().as_slice()
+                            [
+                                fill.fill.get_bytes().as_slice(),
+                                fill.opacity.to_ne_bytes().as_slice(),
+                            ]
+                            .concat()
+                            .as_slice()
+                        },
+                        {
+                            let decoration = decoration.unwrap_or_default();
+
+                            [
+                                decoration.color.get_bytes().as_slice(),
+                                decoration.size.to_ne_bytes().as_slice(),
+                                decoration.spacing.to_ne_bytes().as_slice(),
+                            ]
+                            .concat()
+                            .as_slice()
                         },
                     ]
                     .concat()
@@ -0,0 +1,93 @@
+use glam::Vec2;
+use std::collections::HashMap;
+
+/// Size (in pixels) of each cell in the collision grid
+const CELL_SIZE: f32 = 64.0;
+
+/// Axis aligned box in screen space
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ScreenBox {
+    pub min: Vec2,
+    pub max: Vec2,
+}
+
+impl ScreenBox {
+    pub fn new(min: Vec2, max: Vec2) -> Self {
+        Self { min, max }
+    }
+
+    /// Creates a box centered on a point
+    pub fn around(center: Vec2, size: Vec2) -> Self {
+        Self::new(center - size / 2.0, center + size / 2.0)
+    }
+
+    pub fn intersects(&self, other: &ScreenBox) -> bool {
+        self.min.x < other.max.x
+            && other.min.x < self.max.x
+            && self.min.y < other.max.y
+            && other.min.y < self.max.y
+    }
+
+    fn cells(&self) -> impl Iterator<Item = (i32, i32)> {
+        let min = (self.min / CELL_SIZE).floor();
+        let max = (self.max / CELL_SIZE).floor();
+
+        (min.x as i32..=max.x as i32)
+            .flat_map(move |x| (min.y as i32..=max.y as i32).map(move |y| (x, y)))
+    }
+}
+
+/// Keeps track of the screen space occupied by symbols that have already been placed, so that
+/// later symbols can be rejected if they would overlap.
+#[derive(Default)]
+pub(crate) struct CollisionIndex {
+    boxes: Vec<ScreenBox>,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl CollisionIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn collides(&self, screen_box: &ScreenBox) -> bool {
+        screen_box.cells().any(|cell| {
+            self.cells
+                .get(&cell)
+                .map(|indexes| {
+                    indexes
+                        .iter()
+                        .any(|&i| self.boxes[i].intersects(screen_box))
+                })
+                .unwrap_or_default()
+        })
+    }
+
+    /// Attempts to place a symbol made up of multiple boxes. Nothing is inserted (and `false` is
+    /// returned) if any of the boxes collide with a symbol that has already been placed.
+    pub fn try_insert(&mut self, screen_boxes: &[ScreenBox]) -> bool {
+        if screen_boxes
+            .iter()
+            .any(|screen_box| self.collides(screen_box))
+        {
+            return false;
+        }
+
+        self.insert(screen_boxes);
+
+        true
+    }
+
+    /// Places a symbol regardless of whether it collides with anything, so that symbols placed
+    /// later avoid it.
+    pub fn insert(&mut self, screen_boxes: &[ScreenBox]) {
+        for screen_box in screen_boxes {
+            let index = self.boxes.len();
+            self.boxes.push(*screen_box);
+
+            for cell in screen_box.cells() {
+                self.cells.entry(cell).or_default().push(index);
+            }
+        }
+    }
+}
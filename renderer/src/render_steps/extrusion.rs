@@ -0,0 +1,147 @@
+use crate::{
+    ogl::{DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    RenderStep,
+};
+use glam::Vec3;
+use std::{cell::RefCell, rc::Rc};
+
+/// Area extruded upwards from the map into a solid shape (such as a building), with walls around
+/// the outline and a flat roof on top.
+pub struct Extrusion {
+    outline: Vec<Vec3>,
+    min_height: f32,
+    height: f32,
+    color: Vec3,
+}
+
+impl Extrusion {
+    /// Extrusion of an outline (on the map) up to a height, in world units.
+    pub fn new(outline: Vec<Vec3>, height: f32, color: Vec3) -> Self {
+        Self {
+            outline,
+            min_height: 0.0,
+            height,
+            color,
+        }
+    }
+
+    /// Height (in world units) that the extrusion starts at, for parts of buildings that overhang
+    /// the ground.
+    pub fn with_min_height(mut self, min_height: f32) -> Self {
+        self.min_height = min_height;
+
+        self
+    }
+
+    /// Triangles of the walls and roof, with the normal of each vertex.
+    fn triangles(&self) -> Vec<(Vec3, Vec3)> {
+        let closed = self.outline.len() > 1 && self.outline.first() == self.outline.last();
+        let outline = if closed {
+            &self.outline[..self.outline.len() - 1]
+        } else {
+            &self.outline[..]
+        };
+
+        if outline.len() < 3 || self.height <= self.min_height {
+            return Vec::new();
+        }
+
+        // The map is on the y=0 plane, with the camera looking down from negative y
+        let up = Vec3::NEG_Y;
+        let (bottom, top) = (up * self.min_height, up * self.height);
+
+        // Outlines going anticlockwise (looking down on the map) have the outside on their right
+        let area = outline
+            .iter()
+            .zip(outline.iter().cycle().skip(1))
+            .map(|(a, b)| a.x * b.z - b.x * a.z)
+            .sum::<f32>();
+        let outwards = if area > 0.0 { 1.0 } else { -1.0 };
+
+        let mut triangles = Vec::new();
+
+        for (&a, &b) in outline.iter().zip(outline.iter().cycle().skip(1)) {
+            let normal = (b - a).cross(up).normalize_or_zero() * outwards;
+
+            triangles.extend(
+                [
+                    a + bottom,
+                    b + bottom,
+                    b + top,
+                    a + bottom,
+                    b + top,
+                    a + top,
+                ]
+                .map(|vertex| (vertex, normal)),
+            );
+        }
+
+        let coordinates = outline
+            .iter()
+            .flat_map(|point| [point.x, point.z])
+            .collect::<Vec<_>>();
+        let roof = earcutr::earcut(&coordinates, &[], 2).unwrap_or_default();
+
+        triangles.extend(roof.into_iter().map(|i| (outline[i] + top, up)));
+
+        triangles
+    }
+}
+
+#[derive(Default)]
+pub struct ExtrusionProgram {
+    extrusions: Vec<Extrusion>,
+}
+
+impl ExtrusionProgram {
+    pub fn add_extrusion(&mut self, extrusion: Extrusion) {
+        self.extrusions.push(extrusion);
+    }
+
+    pub fn clear(&mut self) {
+        self.extrusions.clear();
+    }
+}
+
+impl RenderStep for ExtrusionProgram {
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        vec![gl
+            .add_program(
+                Program::from_directory("renderer/src/shaders/extrusion")
+                    .unwrap()
+                    .with_format(&[
+                        // Vertex
+                        VertexFormat::new(3, VertexType::Float),
+                        // Normal
+                        VertexFormat::new(3, VertexType::Float),
+                        // Color
+                        VertexFormat::new(3, VertexType::Float),
+                    ])
+                    .with_draw_type(DrawType::Triangles)
+                    // Extrusions in front hide those behind them, regardless of the order that
+                    // they're drawn in
+                    .with_depth_test(true),
+            )
+            .unwrap()]
+    }
+
+    fn get_vertices(&self) -> Vec<Vec<u8>> {
+        vec![self
+            .extrusions
+            .iter()
+            .flat_map(|extrusion| {
+                extrusion
+                    .triangles()
+                    .into_iter()
+                    .flat_map(move |(vertex, normal)| {
+                        [
+                            vertex.get_bytes(),
+                            normal.get_bytes(),
+                            extrusion.color.get_bytes(),
+                        ]
+                        .concat()
+                    })
+            })
+            .collect()]
+    }
+}
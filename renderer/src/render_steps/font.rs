@@ -0,0 +1,8 @@
+use ab_glyph::FontRef;
+
+/// Font used for all text, including labels and icon symbols
+const FONT_DATA: &[u8] = include_bytes!("../fonts/DejaVuSans.ttf");
+
+pub(crate) fn load_font() -> FontRef<'static> {
+    FontRef::try_from_slice(FONT_DATA).expect("bundled font should be valid")
+}
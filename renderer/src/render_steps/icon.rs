@@ -0,0 +1,288 @@
+use super::{atlas::Atlas, collision::ScreenBox, font::load_font};
+use crate::{
+    ogl::{DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    CameraInfo, RenderStep,
+};
+use ab_glyph::{point, Font, PxScale, ScaleFont};
+use glam::{UVec2, Vec2, Vec3, Vec4};
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+/// Width (in texels) of the sprite atlas
+const ATLAS_WIDTH: u32 = 512;
+
+/// Diameter (in pixels) of badge sprites
+const BADGE_SIZE: u32 = 20;
+
+/// Size (in pixels) of the symbol drawn on badge sprites
+const BADGE_SYMBOL_SIZE: f32 = 12.0;
+
+/// Minimum spacing (in pixels) kept between icons
+const ICON_MARGIN: f32 = 2.0;
+
+/// RGBA image that icons can be drawn with
+pub struct Sprite {
+    size: UVec2,
+    pixels: Vec<Vec4>,
+}
+
+impl Sprite {
+    /// Creates a sprite from RGBA pixels (with each channel between 0 and 1), in row major order
+    /// starting from the top left.
+    pub fn from_rgba(size: UVec2, pixels: Vec<Vec4>) -> Self {
+        assert_eq!(pixels.len() as u32, size.x * size.y);
+
+        Self { size, pixels }
+    }
+
+    /// Round badge with a white symbol (one or two characters) in the middle.
+    pub fn badge(symbol: &str, color: Vec3) -> Self {
+        let size = UVec2::splat(BADGE_SIZE);
+        let center = size.as_vec2() / 2.0;
+        let radius = center.x - 1.0;
+        let border = 1.5;
+
+        // Draw the background circle, with a white border
+        let mut pixels = (0..size.y)
+            .flat_map(|y| (0..size.x).map(move |x| Vec2::new(x as f32, y as f32) + 0.5))
+            .map(|p| {
+                let distance = p.distance(center);
+
+                let alpha = (radius + 0.5 - distance).clamp(0.0, 1.0);
+                let fill = (radius - border + 0.5 - distance).clamp(0.0, 1.0);
+
+                Vec3::ONE.lerp(color, fill).extend(alpha)
+            })
+            .collect::<Vec<_>>();
+
+        // Draw the symbol over the top
+        let font = load_font();
+
+        // Shrink wide symbols so that they fit inside the border
+        let width = symbol
+            .chars()
+            .map(|c| {
+                font.as_scaled(BADGE_SYMBOL_SIZE)
+                    .h_advance(font.glyph_id(c))
+            })
+            .sum::<f32>();
+        let available = (radius - border) * 2.0 - 2.0;
+        let scale = PxScale::from(BADGE_SYMBOL_SIZE * (available / width).min(1.0));
+        let scaled = font.as_scaled(scale);
+
+        let mut pen = 0.0;
+        let glyphs = symbol
+            .chars()
+            .map(|c| {
+                let glyph = font
+                    .glyph_id(c)
+                    .with_scale_and_position(scale, point(pen, 0.0));
+                pen += scaled.h_advance(glyph.id);
+
+                glyph
+            })
+            .collect::<Vec<_>>();
+
+        // Center the symbol, both horizontally and vertically
+        let origin = center - Vec2::new(pen, scaled.ascent() + scaled.descent()) / 2.0;
+
+        for outline in glyphs
+            .into_iter()
+            .filter_map(|glyph| font.outline_glyph(glyph))
+        {
+            let bounds = outline.px_bounds();
+
+            outline.draw(|x, y, coverage| {
+                let x = origin.x.round() as i32 + bounds.min.x as i32 + x as i32;
+                let y =
+                    origin.y.round() as i32 + (scaled.ascent() + bounds.min.y) as i32 + y as i32;
+
+                if (0..size.x as i32).contains(&x) && (0..size.y as i32).contains(&y) {
+                    let pixel = &mut pixels[(y as u32 * size.x + x as u32) as usize];
+                    *pixel = pixel.truncate().lerp(Vec3::ONE, coverage).extend(pixel.w);
+                }
+            });
+        }
+
+        Self::from_rgba(size, pixels)
+    }
+}
+
+/// Location of a sprite within the atlas
+#[derive(Clone, Copy)]
+struct SpriteLocation {
+    atlas_position: UVec2,
+    size: UVec2,
+}
+
+pub struct Icon {
+    position: Vec3,
+    sprite: String,
+    priority: i32,
+}
+
+impl Icon {
+    /// Icon drawn with a sprite that has previously been added to the icon program.
+    pub fn new(position: Vec3, sprite: &str) -> Self {
+        Self {
+            position,
+            sprite: sprite.to_string(),
+            priority: 0,
+        }
+    }
+
+    /// Icons with a higher priority are placed first, and so win any collisions.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+
+        self
+    }
+}
+
+/// Render step that draws sprites as billboards with a constant size in screen space, thinning
+/// them out so that they never overlap.
+pub struct IconProgram {
+    atlas: Atlas,
+    sprites: HashMap<String, SpriteLocation>,
+
+    /// Icons, ordered from the highest to lowest priority
+    icons: Vec<Icon>,
+
+    /// Vertices for the icons placed with the current camera
+    vertices: Vec<u8>,
+}
+
+impl Default for IconProgram {
+    fn default() -> Self {
+        Self {
+            atlas: Atlas::new(ATLAS_WIDTH, 4),
+            sprites: HashMap::new(),
+            icons: Vec::new(),
+            vertices: Vec::new(),
+        }
+    }
+}
+
+impl IconProgram {
+    pub fn add_sprite(&mut self, name: &str, sprite: Sprite) {
+        let pixels = sprite
+            .pixels
+            .iter()
+            .flat_map(|pixel| pixel.to_array())
+            .collect::<Vec<_>>();
+
+        let atlas_position = self.atlas.insert(sprite.size, &pixels);
+
+        self.sprites.insert(
+            name.to_string(),
+            SpriteLocation {
+                atlas_position,
+                size: sprite.size,
+            },
+        );
+    }
+
+    pub fn has_sprite(&self, name: &str) -> bool {
+        self.sprites.contains_key(name)
+    }
+
+    pub fn add_icon(&mut self, icon: Icon) {
+        debug_assert!(self.has_sprite(&icon.sprite), "sprite must be added first");
+
+        let index = self
+            .icons
+            .partition_point(|other| other.priority >= icon.priority);
+        self.icons.insert(index, icon);
+    }
+
+    pub fn clear(&mut self) {
+        self.icons.clear();
+        self.vertices.clear();
+    }
+}
+
+impl RenderStep for IconProgram {
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        let program = gl
+            .add_program(
+                Program::from_directory("renderer/src/shaders/icon")
+                    .unwrap()
+                    .with_format(&[
+                        // Anchor
+                        VertexFormat::new(3, VertexType::Float),
+                        // Screen offset
+                        VertexFormat::new(2, VertexType::Float),
+                        // Atlas position
+                        VertexFormat::new(2, VertexType::Float),
+                    ])
+                    .with_draw_type(DrawType::Triangles),
+            )
+            .unwrap();
+
+        program
+            .borrow_mut()
+            .set_uniform("atlas_width", &(self.atlas.width() as f32))
+            .unwrap();
+
+        vec![program]
+    }
+
+    fn get_vertices(&self) -> Vec<Vec<u8>> {
+        vec![self.vertices.clone()]
+    }
+
+    fn get_texture_buffer(&self) -> Option<Vec<u8>> {
+        Some(self.atlas.get_bytes())
+    }
+
+    fn update_camera(&mut self, camera: &CameraInfo) -> bool {
+        let mut collisions = camera.collisions.borrow_mut();
+
+        self.vertices = self
+            .icons
+            .iter()
+            .filter_map(|icon| {
+                let sprite = self.sprites.get(&icon.sprite)?;
+
+                let screen = camera.to_screen(icon.position)?;
+                if !camera.in_viewport(screen, sprite.size.max_element() as f32) {
+                    return None;
+                }
+
+                collisions
+                    .try_insert(&[ScreenBox::around(
+                        screen,
+                        sprite.size.as_vec2() + Vec2::splat(ICON_MARGIN * 2.0),
+                    )])
+                    .then_some((icon, sprite))
+            })
+            .flat_map(|(icon, sprite)| {
+                let size = sprite.size.as_vec2();
+
+                [
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(0.0, 1.0),
+                    Vec2::new(1.0, 0.0),
+                    Vec2::new(1.0, 0.0),
+                    Vec2::new(0.0, 1.0),
+                    Vec2::new(1.0, 1.0),
+                ]
+                .into_iter()
+                .flat_map(|corner| {
+                    // Center the sprite on its position
+                    let offset = (Vec2::new(corner.x, 1.0 - corner.y) - 0.5) * size;
+                    let uv = sprite.atlas_position.as_vec2() + corner * size;
+
+                    [
+                        icon.position.get_bytes(),
+                        offset.get_bytes(),
+                        uv.get_bytes(),
+                    ]
+                    .concat()
+                })
+                .collect::<Vec<_>>()
+            })
+            .collect();
+
+        true
+    }
+}
@@ -0,0 +1,548 @@
+use super::{atlas::Atlas, collision::ScreenBox, font::load_font};
+use crate::{
+    ogl::{DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    CameraInfo, RenderStep,
+};
+use ab_glyph::{Font as _, FontRef, PxScale, ScaleFont};
+use glam::{UVec2, Vec2, Vec3};
+use std::{cell::RefCell, collections::HashMap, f32::consts::PI, rc::Rc};
+
+/// Width (in texels) of the glyph atlas
+const ATLAS_WIDTH: u32 = 1024;
+
+/// Distance (in pixels) that the halo extends around each glyph. Must match the label shader.
+const HALO: f32 = 2.0;
+
+/// Minimum spacing (in pixels) kept between labels
+const LABEL_MARGIN: f32 = 4.0;
+
+/// Largest change in direction allowed between consecutive glyphs of a line label
+const MAX_GLYPH_ANGLE: f32 = PI / 4.0;
+
+/// A glyph that has been rasterized into the atlas, with all measurements in pixels
+#[derive(Clone, Copy)]
+struct Glyph {
+    /// Top left corner of the bitmap within the atlas
+    atlas_position: UVec2,
+    size: UVec2,
+
+    /// Offset of the top left corner of the bitmap from the pen position (y down)
+    offset: Vec2,
+    advance: f32,
+}
+
+/// Rasterizes glyphs on demand, caching them in the atlas
+struct GlyphCache {
+    font: FontRef<'static>,
+    glyphs: HashMap<(char, u32), Glyph>,
+    atlas: Atlas,
+}
+
+impl GlyphCache {
+    fn new() -> Self {
+        Self {
+            font: load_font(),
+            glyphs: HashMap::new(),
+            atlas: Atlas::new(ATLAS_WIDTH, 1),
+        }
+    }
+
+    fn get(&mut self, c: char, size: f32) -> Glyph {
+        let font = &self.font;
+        let atlas = &mut self.atlas;
+
+        *self.glyphs.entry((c, size.to_bits())).or_insert_with(|| {
+            let scale = PxScale::from(size);
+            let glyph_id = font.glyph_id(c);
+            let advance = font.as_scaled(scale).h_advance(glyph_id);
+
+            match font.outline_glyph(glyph_id.with_scale(scale)) {
+                Some(outline) => {
+                    let bounds = outline.px_bounds();
+                    let size = UVec2::new(bounds.width() as u32, bounds.height() as u32);
+
+                    let mut pixels = vec![0.0; (size.x * size.y) as usize];
+                    outline.draw(|x, y, coverage| {
+                        pixels[(y * size.x + x) as usize] = coverage;
+                    });
+
+                    Glyph {
+                        atlas_position: atlas.insert(size, &pixels),
+                        size,
+                        offset: Vec2::new(bounds.min.x, bounds.min.y),
+                        advance,
+                    }
+                }
+                // Glyph without an outline (eg whitespace)
+                None => Glyph {
+                    atlas_position: UVec2::ZERO,
+                    size: UVec2::ZERO,
+                    offset: Vec2::ZERO,
+                    advance,
+                },
+            }
+        })
+    }
+
+    /// Lays out a single line of text, with the origin at the center of the text.
+    fn layout(&mut self, text: &str, size: f32) -> TextLayout {
+        let (ascent, descent, kerning) = {
+            let scaled = self.font.as_scaled(PxScale::from(size));
+
+            let kerning = text
+                .chars()
+                .zip(text.chars().skip(1))
+                .map(|(a, b)| scaled.kern(self.font.glyph_id(a), self.font.glyph_id(b)))
+                .collect::<Vec<_>>();
+
+            (scaled.ascent(), scaled.descent(), kerning)
+        };
+
+        let mut pen = 0.0;
+        let mut glyphs = Vec::with_capacity(text.len());
+        for (i, c) in text.chars().enumerate() {
+            let glyph = self.get(c, size);
+            glyphs.push((glyph, pen));
+
+            pen += glyph.advance + kerning.get(i).cloned().unwrap_or_default();
+        }
+
+        // Center the text around the origin
+        let width = pen;
+        let baseline = -(ascent + descent) / 2.0;
+        for (_, x) in glyphs.iter_mut() {
+            *x -= width / 2.0;
+        }
+
+        TextLayout {
+            glyphs,
+            size: Vec2::new(width, ascent - descent),
+            baseline,
+        }
+    }
+}
+
+/// Glyphs making up a line of text, positioned relative to the center of the text
+struct TextLayout {
+    /// Glyphs, along with the pen position that they start at
+    glyphs: Vec<(Glyph, f32)>,
+    size: Vec2,
+
+    /// Vertical position of the baseline (y up)
+    baseline: f32,
+}
+
+enum Placement {
+    /// Centered on a single point
+    Point(Vec3),
+
+    /// Following the geometry of a line
+    Line(Vec<Vec3>),
+
+    /// Fixed to a position on the screen (in normalised device coordinates), regardless of the
+    /// camera
+    Screen(Vec2),
+}
+
+pub struct Label {
+    text: String,
+    placement: Placement,
+    size: f32,
+    color: Vec3,
+    priority: i32,
+    offset: Vec2,
+    allow_overlap: bool,
+}
+
+impl Label {
+    fn new(text: &str, placement: Placement) -> Self {
+        Self {
+            text: text.to_string(),
+            placement,
+            size: 12.0,
+            color: Vec3::new(0.2, 0.2, 0.2),
+            priority: 0,
+            offset: Vec2::ZERO,
+            allow_overlap: false,
+        }
+    }
+
+    /// Label centered on a point, such as a place name
+    pub fn point(position: Vec3, text: &str) -> Self {
+        Self::new(text, Placement::Point(position))
+    }
+
+    /// Label placed along a line, such as a street name
+    pub fn line(points: Vec<Vec3>, text: &str) -> Self {
+        Self::new(text, Placement::Line(points))
+    }
+
+    /// Label fixed to a position on the screen, such as part of a prompt. The position is given
+    /// in normalised device coordinates (from -1 to 1, with the origin in the middle), and can be
+    /// moved from there by a number of pixels with an offset.
+    pub fn screen(position: Vec2, text: &str) -> Self {
+        Self::new(text, Placement::Screen(position))
+    }
+
+    /// Text size in pixels
+    pub fn with_size(mut self, size: f32) -> Self {
+        self.size = size;
+
+        self
+    }
+
+    pub fn with_color(mut self, color: Vec3) -> Self {
+        self.color = color;
+
+        self
+    }
+
+    /// Labels with a higher priority are placed first, and so win any collisions.
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    /// Moves a point label away from its position (in pixels), such as to make room for an icon.
+    pub fn with_offset(mut self, offset: Vec2) -> Self {
+        self.offset = offset;
+
+        self
+    }
+
+    /// Places the label even if it overlaps other labels or icons, such as for parts of a prompt
+    /// that always need to be seen. Symbols placed afterwards still avoid it.
+    pub fn with_allow_overlap(mut self, allow_overlap: bool) -> Self {
+        self.allow_overlap = allow_overlap;
+
+        self
+    }
+}
+
+struct LaidOutLabel {
+    placement: Placement,
+    color: Vec3,
+    priority: i32,
+    offset: Vec2,
+    allow_overlap: bool,
+    layout: TextLayout,
+}
+
+impl LaidOutLabel {
+    /// Attempts to place a point label, returning the screen boxes it would occupy and a
+    /// position and rotation for every glyph.
+    fn place_point(
+        &self,
+        position: Vec3,
+        camera: &CameraInfo,
+    ) -> Option<(Vec<ScreenBox>, Vec<PlacedGlyph>)> {
+        let screen = camera.to_screen(position)? + self.offset;
+        if !camera.in_viewport(screen, 0.0) {
+            return None;
+        }
+
+        let glyphs = self
+            .layout
+            .glyphs
+            .iter()
+            .map(|&(glyph, x)| PlacedGlyph {
+                glyph,
+                anchor: position,
+                origin: Vec2::new(x, self.layout.baseline) + self.offset,
+                rotation: Vec2::X,
+            })
+            .collect();
+
+        Some((
+            vec![ScreenBox::around(
+                screen,
+                self.layout.size + Vec2::splat(LABEL_MARGIN * 2.0),
+            )],
+            glyphs,
+        ))
+    }
+
+    fn place_screen(
+        &self,
+        position: Vec2,
+        camera: &CameraInfo,
+    ) -> Option<(Vec<ScreenBox>, Vec<PlacedGlyph>)> {
+        // Anchor the glyphs to a point in the world that's at the position on the screen
+        let anchor = camera
+            .projection_view
+            .inverse()
+            .project_point3(position.extend(0.0));
+
+        self.place_point(anchor, camera)
+    }
+
+    fn place_line(
+        &self,
+        points: &[Vec3],
+        camera: &CameraInfo,
+    ) -> Option<(Vec<ScreenBox>, Vec<PlacedGlyph>)> {
+        let mut screen_points = points
+            .iter()
+            .map(|&point| camera.to_screen(point))
+            .collect::<Option<Vec<_>>>()?;
+        let mut points = points.to_vec();
+
+        // Keep the text upright by always running it from left to right
+        if screen_points.first()?.x > screen_points.last()?.x {
+            screen_points.reverse();
+            points.reverse();
+        }
+
+        let distances = screen_points
+            .windows(2)
+            .scan(0.0, |distance, segment| {
+                *distance += segment[0].distance(segment[1]);
+                Some(*distance)
+            })
+            .collect::<Vec<_>>();
+        let length = distances.last().cloned().unwrap_or_default();
+
+        let width = self.layout.size.x;
+        if length < width + LABEL_MARGIN * 2.0 {
+            return None;
+        }
+
+        // Center the text along the line
+        let middle = length / 2.0;
+
+        let mut boxes = Vec::with_capacity(self.layout.glyphs.len());
+        let mut glyphs = Vec::with_capacity(self.layout.glyphs.len());
+        let mut previous_angle = None;
+
+        for &(glyph, x) in self.layout.glyphs.iter() {
+            let distance = middle + x + (glyph.advance / 2.0);
+
+            // Find the segment that the center of the glyph falls on
+            let segment = distances
+                .iter()
+                .position(|&end| distance <= end)
+                .unwrap_or(distances.len() - 1);
+            let segment_start = if segment == 0 {
+                0.0
+            } else {
+                distances[segment - 1]
+            };
+            let segment_length = distances[segment] - segment_start;
+            let t = if segment_length > 0.0 {
+                (distance - segment_start) / segment_length
+            } else {
+                0.0
+            };
+
+            let start = screen_points[segment];
+            let end = screen_points[segment + 1];
+            let screen = start.lerp(end, t);
+
+            // Reject labels that bend too sharply to be legible
+            let direction = (end - start).normalize_or_zero();
+            let angle = direction.y.atan2(direction.x);
+            if let Some(previous_angle) = previous_angle {
+                let difference: f32 = angle - previous_angle;
+                if difference.sin().atan2(difference.cos()).abs() > MAX_GLYPH_ANGLE {
+                    return None;
+                }
+            }
+            previous_angle = Some(angle);
+
+            if !camera.in_viewport(screen, 0.0) {
+                return None;
+            }
+
+            boxes.push(ScreenBox::around(
+                screen,
+                Vec2::splat(glyph.advance.max(self.layout.size.y) + LABEL_MARGIN * 2.0),
+            ));
+            glyphs.push(PlacedGlyph {
+                glyph,
+                anchor: points[segment].lerp(points[segment + 1], t),
+                origin: Vec2::new(-glyph.advance / 2.0, self.layout.baseline),
+                rotation: direction,
+            });
+        }
+
+        Some((boxes, glyphs))
+    }
+}
+
+/// Glyph that has been positioned in the world
+struct PlacedGlyph {
+    glyph: Glyph,
+
+    /// World position that the glyph is attached to
+    anchor: Vec3,
+
+    /// Pen position of the glyph relative to the anchor (in pixels, before rotation)
+    origin: Vec2,
+
+    /// Direction of the baseline in screen space
+    rotation: Vec2,
+}
+
+impl PlacedGlyph {
+    fn get_bytes(&self, color: Vec3) -> Vec<u8> {
+        let Glyph {
+            atlas_position,
+            size,
+            offset,
+            ..
+        } = self.glyph;
+
+        if size == UVec2::ZERO {
+            return Vec::new();
+        }
+
+        // Expand the quad to make room for the halo
+        let top_left = self.origin + Vec2::new(offset.x, -offset.y) + Vec2::new(-HALO, HALO);
+        let quad_size = size.as_vec2() + Vec2::splat(HALO * 2.0);
+        let uv_top_left = atlas_position.as_vec2() - Vec2::splat(HALO);
+
+        [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(1.0, 0.0),
+            Vec2::new(0.0, 1.0),
+            Vec2::new(1.0, 1.0),
+        ]
+        .into_iter()
+        .flat_map(|corner| {
+            let offset = self
+                .rotation
+                .rotate(top_left + Vec2::new(corner.x, -corner.y) * quad_size);
+            let uv = uv_top_left + corner * quad_size;
+
+            [
+                self.anchor.get_bytes(),
+                offset.get_bytes(),
+                uv.get_bytes(),
+                color.get_bytes(),
+            ]
+            .concat()
+        })
+        .collect()
+    }
+}
+
+/// Render step that draws text labels in screen space, skipping any that would overlap.
+pub struct LabelProgram {
+    glyph_cache: GlyphCache,
+
+    /// Labels, ordered from the highest to lowest priority
+    labels: Vec<LaidOutLabel>,
+
+    /// Vertices for the labels placed with the current camera
+    vertices: Vec<u8>,
+}
+
+impl Default for LabelProgram {
+    fn default() -> Self {
+        Self {
+            glyph_cache: GlyphCache::new(),
+            labels: Vec::new(),
+            vertices: Vec::new(),
+        }
+    }
+}
+
+impl LabelProgram {
+    pub fn add_label(&mut self, label: Label) {
+        let layout = self.glyph_cache.layout(&label.text, label.size);
+
+        let index = self
+            .labels
+            .partition_point(|other| other.priority >= label.priority);
+        self.labels.insert(
+            index,
+            LaidOutLabel {
+                placement: label.placement,
+                color: label.color,
+                priority: label.priority,
+                offset: label.offset,
+                allow_overlap: label.allow_overlap,
+                layout,
+            },
+        );
+    }
+
+    pub fn clear(&mut self) {
+        self.labels.clear();
+        self.vertices.clear();
+    }
+}
+
+impl RenderStep for LabelProgram {
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        let program = gl
+            .add_program(
+                Program::from_directory("renderer/src/shaders/label")
+                    .unwrap()
+                    .with_format(&[
+                        // Anchor
+                        VertexFormat::new(3, VertexType::Float),
+                        // Screen offset
+                        VertexFormat::new(2, VertexType::Float),
+                        // Atlas position
+                        VertexFormat::new(2, VertexType::Float),
+                        // Color
+                        VertexFormat::new(3, VertexType::Float),
+                    ])
+                    .with_draw_type(DrawType::Triangles),
+            )
+            .unwrap();
+
+        program
+            .borrow_mut()
+            .set_uniform("atlas_width", &(self.glyph_cache.atlas.width() as f32))
+            .unwrap();
+
+        vec![program]
+    }
+
+    fn get_vertices(&self) -> Vec<Vec<u8>> {
+        vec![self.vertices.clone()]
+    }
+
+    fn get_texture_buffer(&self) -> Option<Vec<u8>> {
+        Some(self.glyph_cache.atlas.get_bytes())
+    }
+
+    fn update_camera(&mut self, camera: &CameraInfo) -> bool {
+        let mut collisions = camera.collisions.borrow_mut();
+
+        self.vertices = self
+            .labels
+            .iter()
+            .filter_map(|label| {
+                match &label.placement {
+                    Placement::Point(position) => label.place_point(*position, camera),
+                    Placement::Line(points) => label.place_line(points, camera),
+                    Placement::Screen(position) => label.place_screen(*position, camera),
+                }
+                .filter(|(boxes, _)| {
+                    if label.allow_overlap {
+                        collisions.insert(boxes);
+
+                        true
+                    } else {
+                        collisions.try_insert(boxes)
+                    }
+                })
+                .map(|(_, glyphs)| (label, glyphs))
+            })
+            .flat_map(|(label, glyphs)| {
+                glyphs
+                    .into_iter()
+                    .flat_map(|glyph| glyph.get_bytes(label.color))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+
+        true
+    }
+}
@@ -1 +1,9 @@
+mod atlas;
 pub mod canvas;
+pub(crate) mod collision;
+pub mod extrusion;
+mod font;
+pub mod icon;
+pub mod label;
+pub mod raster;
+pub mod tiles;
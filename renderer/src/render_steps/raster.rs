@@ -0,0 +1,112 @@
+use crate::{
+    ogl::{DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    RenderStep,
+};
+use glam::{UVec2, Vec2, Vec3, Vec4};
+use std::{cell::RefCell, rc::Rc};
+
+/// RGBA image stretched over a rectangle of the map, such as a hillshade.
+pub struct Raster {
+    top_left: Vec3,
+    bottom_right: Vec3,
+    size: UVec2,
+    pixels: Vec<Vec4>,
+}
+
+impl Raster {
+    /// Creates a raster from RGBA pixels (with each channel between 0 and 1), in row major order
+    /// starting from the top left. The corners are the top left and bottom right corners of the
+    /// image, as seen when looking down on the map.
+    pub fn from_rgba(top_left: Vec3, bottom_right: Vec3, size: UVec2, pixels: Vec<Vec4>) -> Self {
+        assert_eq!(pixels.len() as u32, size.x * size.y);
+
+        Self {
+            top_left,
+            bottom_right,
+            size,
+            pixels,
+        }
+    }
+}
+
+/// Render step that draws rasters as textured quads on the map, smoothly interpolating between
+/// their pixels.
+#[derive(Default)]
+pub struct RasterProgram {
+    rasters: Vec<Raster>,
+}
+
+impl RasterProgram {
+    pub fn add_raster(&mut self, raster: Raster) {
+        self.rasters.push(raster);
+    }
+
+    pub fn clear(&mut self) {
+        self.rasters.clear();
+    }
+}
+
+impl RenderStep for RasterProgram {
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        vec![gl
+            .add_program(
+                Program::from_directory("renderer/src/shaders/raster")
+                    .unwrap()
+                    .with_format(&[
+                        // Vertex
+                        VertexFormat::new(3, VertexType::Float),
+                        // Position within the raster (in pixels)
+                        VertexFormat::new(2, VertexType::Float),
+                        // Raster offset (in pixels), width and height
+                        VertexFormat::new(3, VertexType::UInt),
+                    ])
+                    .with_draw_type(DrawType::Triangles),
+            )
+            .unwrap()]
+    }
+
+    fn get_vertices(&self) -> Vec<Vec<u8>> {
+        let mut offset = 0;
+
+        vec![self
+            .rasters
+            .iter()
+            .flat_map(|raster| {
+                let (top_left, bottom_right) = (raster.top_left, raster.bottom_right);
+                let top_right = Vec3::new(bottom_right.x, top_left.y, top_left.z);
+                let bottom_left = Vec3::new(top_left.x, bottom_right.y, bottom_right.z);
+                let size = raster.size.as_vec2();
+
+                let info = [offset, raster.size.x, raster.size.y]
+                    .iter()
+                    .flat_map(|value| value.to_ne_bytes())
+                    .collect::<Vec<_>>();
+                offset += raster.size.x * raster.size.y;
+
+                [
+                    (top_left, Vec2::ZERO),
+                    (bottom_left, Vec2::new(0.0, size.y)),
+                    (top_right, Vec2::new(size.x, 0.0)),
+                    (top_right, Vec2::new(size.x, 0.0)),
+                    (bottom_left, Vec2::new(0.0, size.y)),
+                    (bottom_right, size),
+                ]
+                .into_iter()
+                .flat_map(move |(vertex, uv)| {
+                    [vertex.get_bytes(), uv.get_bytes(), info.clone()].concat()
+                })
+            })
+            .collect()]
+    }
+
+    fn get_texture_buffer(&self) -> Option<Vec<u8>> {
+        Some(
+            self.rasters
+                .iter()
+                .flat_map(|raster| raster.pixels.iter())
+                .flat_map(|pixel| pixel.to_array())
+                .flat_map(|value| value.to_ne_bytes())
+                .collect(),
+        )
+    }
+}
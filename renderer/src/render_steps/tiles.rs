@@ -0,0 +1,332 @@
+use crate::{
+    ogl::{texture::Texture2d, DrawType, OpenGl, Program, VertexData, VertexFormat, VertexType},
+    CameraInfo, RenderStep,
+};
+use glam::{UVec2, Vec2, Vec3};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
+
+/// Size (in pixels) that tiles are stored at in the cache, with tiles of other sizes resampled.
+const TILE_SIZE: u32 = 256;
+
+/// Number of tiles along each side of the cache texture.
+const CACHE_TILES: u32 = 16;
+
+/// Furthest distance (as a multiple of the camera's height) that tiles are drawn at when the
+/// camera is tilted up towards the horizon.
+const MAX_DISTANCE: f32 = 8.0;
+
+/// Position of a tile in the 'slippy map' scheme, with rows going from north to south.
+// https://wiki.openstreetmap.org/wiki/Slippy_map_tilenames
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TileId {
+    pub zoom: u32,
+    pub x: u32,
+    pub y: u32,
+}
+
+/// Decoded tile, with four bytes (RGBA) per pixel in row major order starting from the top left.
+pub struct TileImage {
+    size: UVec2,
+    pixels: Vec<u8>,
+}
+
+impl TileImage {
+    pub fn from_rgba8(size: UVec2, pixels: Vec<u8>) -> Self {
+        assert_eq!(pixels.len() as u32, size.x * size.y * 4);
+
+        Self { size, pixels }
+    }
+
+    /// Pixels scaled to the size of the tiles in the cache.
+    fn resampled(&self) -> Vec<u8> {
+        if self.size == UVec2::splat(TILE_SIZE) {
+            return self.pixels.clone();
+        }
+
+        (0..TILE_SIZE)
+            .flat_map(|y| (0..TILE_SIZE).map(move |x| UVec2::new(x, y)))
+            .flat_map(|position| {
+                let source = (position * self.size) / TILE_SIZE;
+                let i = ((source.y * self.size.x + source.x) * 4) as usize;
+
+                self.pixels[i..i + 4].to_vec()
+            })
+            .collect()
+    }
+}
+
+/// Store of tiles, such as an MBTiles file.
+pub trait TileSource {
+    /// Lowest and highest zoom levels that there are tiles for.
+    fn zoom_range(&self) -> (u32, u32);
+
+    /// Reads a tile, returning `None` if there isn't one at that position.
+    fn get_tile(&mut self, tile: TileId) -> Option<TileImage>;
+}
+
+/// Tiles kept in a texture, with the least recently used tile replaced once it's full.
+struct TileCache {
+    texture: Rc<Texture2d>,
+
+    /// Tile in each slot of the texture, and when it was last used
+    slots: Vec<Option<(TileId, u64)>>,
+    positions: HashMap<TileId, usize>,
+
+    /// Tiles that the source doesn't have, so that they're only looked for once
+    missing: HashSet<TileId>,
+}
+
+impl TileCache {
+    fn new(texture: Rc<Texture2d>) -> Self {
+        Self {
+            texture,
+            slots: vec![None; (CACHE_TILES * CACHE_TILES) as usize],
+            positions: HashMap::new(),
+            missing: HashSet::new(),
+        }
+    }
+
+    /// Slot that a tile is in, reading it from the source if it isn't already cached. Tiles used
+    /// at the time given are never replaced.
+    fn get(&mut self, source: &mut dyn TileSource, tile: TileId, time: u64) -> Option<usize> {
+        if let Some(&slot) = self.positions.get(&tile) {
+            self.slots[slot] = Some((tile, time));
+            return Some(slot);
+        }
+
+        if self.missing.contains(&tile) {
+            return None;
+        }
+
+        let Some(image) = source.get_tile(tile) else {
+            self.missing.insert(tile);
+            return None;
+        };
+
+        // Empty slots are used first, as they were never used at all
+        let (slot, _) = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, slot)| slot.is_none_or(|(_, used)| used < time))
+            .min_by_key(|(_, slot)| slot.map(|(_, used)| used))?;
+
+        if let Some((evicted, _)) = self.slots[slot] {
+            self.positions.remove(&evicted);
+        }
+
+        self.texture.set_region(
+            self.slot_position(slot),
+            UVec2::splat(TILE_SIZE),
+            &image.resampled(),
+        );
+        self.slots[slot] = Some((tile, time));
+        self.positions.insert(tile, slot);
+
+        Some(slot)
+    }
+
+    /// Position (in pixels) of the top left corner of a slot in the texture.
+    fn slot_position(&self, slot: usize) -> UVec2 {
+        UVec2::new(slot as u32 % CACHE_TILES, slot as u32 / CACHE_TILES) * TILE_SIZE
+    }
+}
+
+/// Render step that draws raster tiles (such as satellite imagery) on the map, using the zoom
+/// level that best matches the camera.
+#[derive(Default)]
+pub struct TileProgram {
+    source: Option<Box<dyn TileSource>>,
+
+    /// World position of the north west corner of the tile at zoom 0, and its width
+    origin: Vec3,
+    size: f32,
+
+    cache: Option<TileCache>,
+
+    /// Number of times that the camera has moved, marking when tiles were last used
+    time: u64,
+
+    /// Vertices for the tiles visible with the current camera
+    vertices: Vec<u8>,
+}
+
+impl TileProgram {
+    /// Sets the source of the tiles, along with the position (in world space) of the north west
+    /// corner of the tile at zoom 0 and its width.
+    pub fn set_source(&mut self, source: Box<dyn TileSource>, origin: Vec3, size: f32) {
+        self.source = Some(source);
+        self.origin = origin;
+        self.size = size;
+        self.vertices.clear();
+
+        if let Some(cache) = self.cache.as_mut() {
+            *cache = TileCache::new(Rc::clone(&cache.texture));
+        }
+    }
+
+    /// Tiles covering the area on the map, at the given zoom level.
+    fn tiles_between(&self, zoom: u32, min: Vec2, max: Vec2) -> Vec<TileId> {
+        let count = 1 << zoom;
+        let tile_size = self.size / count as f32;
+
+        // Rows go south, away from the origin
+        let column = |x: f32| ((x - self.origin.x) / tile_size).floor();
+        let row = |z: f32| ((self.origin.z - z) / tile_size).floor();
+        let clamp = |i: f32| i.clamp(0.0, (count - 1) as f32) as u32;
+
+        let (min_column, max_column) = (column(min.x), column(max.x));
+        let (min_row, max_row) = (row(max.y), row(min.y));
+
+        if max_column < 0.0
+            || max_row < 0.0
+            || min_column >= count as f32
+            || min_row >= count as f32
+        {
+            return Vec::new();
+        }
+
+        (clamp(min_row)..=clamp(max_row))
+            .flat_map(|y| {
+                (clamp(min_column)..=clamp(max_column)).map(move |x| TileId { zoom, x, y })
+            })
+            .collect()
+    }
+}
+
+impl RenderStep for TileProgram {
+    fn build_programs(&mut self, gl: &mut OpenGl) -> Vec<Rc<RefCell<Program>>> {
+        let program = gl
+            .add_program(
+                Program::from_directory("renderer/src/shaders/tiles")
+                    .unwrap()
+                    .with_format(&[
+                        // Vertex
+                        VertexFormat::new(3, VertexType::Float),
+                        // Position within the cache texture
+                        VertexFormat::new(2, VertexType::Float),
+                    ])
+                    .with_draw_type(DrawType::Triangles),
+            )
+            .unwrap();
+
+        let texture = Rc::new(
+            gl.create_texture_2d(UVec2::splat(CACHE_TILES * TILE_SIZE))
+                .unwrap(),
+        );
+        program.borrow_mut().attach_texture(Rc::clone(&texture));
+        self.cache = Some(TileCache::new(texture));
+
+        vec![program]
+    }
+
+    fn get_vertices(&self) -> Vec<Vec<u8>> {
+        vec![self.vertices.clone()]
+    }
+
+    fn update_camera(&mut self, camera: &CameraInfo) -> bool {
+        let Some(source) = self.source.as_ref() else {
+            return false;
+        };
+
+        let inverse = camera.projection_view.inverse();
+        let unproject = |x: f32, y: f32, z: f32| inverse.project_point3(Vec3::new(x, y, z));
+
+        // Where the corners (and centre) of the screen land on the map, limiting how far away
+        // they can be when the camera is looking towards the horizon
+        let camera_position = unproject(0.0, 0.0, -1.0);
+        let max_distance = camera_position.y.abs() * MAX_DISTANCE;
+        let ground = |x: f32, y: f32| {
+            let near = unproject(x, y, -1.0);
+            let direction = (unproject(x, y, 1.0) - near).normalize();
+
+            let distance = if direction.y > 0.0 {
+                (-near.y / direction.y).min(max_distance)
+            } else {
+                max_distance
+            };
+
+            let point = near + direction * distance;
+            Vec2::new(point.x, point.z)
+        };
+
+        let corners =
+            [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)].map(|(x, y)| ground(x, y));
+        let min = corners.into_iter().reduce(Vec2::min).unwrap();
+        let max = corners.into_iter().reduce(Vec2::max).unwrap();
+
+        // Pick the zoom level where tiles are closest to their actual size in the middle of the
+        // screen
+        let centre = ground(0.0, 0.0);
+        let pixels_per_unit = camera
+            .to_screen(Vec3::new(centre.x, 0.0, centre.y))
+            .zip(camera.to_screen(Vec3::new(centre.x + 1.0, 0.0, centre.y)))
+            .map(|(a, b)| a.distance(b))
+            .unwrap_or_default();
+
+        let (min_zoom, max_zoom) = source.zoom_range();
+        let ideal_zoom = (self.size * pixels_per_unit / TILE_SIZE as f32)
+            .log2()
+            .round();
+        let mut zoom = (ideal_zoom.max(0.0) as u32).clamp(min_zoom, max_zoom);
+
+        // Zoom out until all the tiles fit in the cache at once
+        let mut tiles = self.tiles_between(zoom, min, max);
+        while tiles.len() > (CACHE_TILES * CACHE_TILES) as usize && zoom > min_zoom {
+            zoom -= 1;
+            tiles = self.tiles_between(zoom, min, max);
+        }
+
+        let (Some(source), Some(cache)) = (self.source.as_mut(), self.cache.as_mut()) else {
+            return false;
+        };
+
+        self.time += 1;
+
+        let texture_size = cache.texture.size().as_vec2();
+        let tile_size = self.size / (1 << zoom) as f32;
+        let origin = self.origin;
+        let time = self.time;
+
+        let vertices = tiles
+            .into_iter()
+            .take((CACHE_TILES * CACHE_TILES) as usize)
+            .filter_map(|tile| {
+                let slot = cache.get(source.as_mut(), tile, time)?;
+
+                Some((tile, cache.slot_position(slot).as_vec2()))
+            })
+            .flat_map(|(tile, slot_position)| {
+                let top_left = origin + Vec3::new(tile.x as f32, 0.0, -(tile.y as f32)) * tile_size;
+
+                [
+                    Vec2::new(0.0, 0.0),
+                    Vec2::new(0.0, 1.0),
+                    Vec2::new(1.0, 0.0),
+                    Vec2::new(1.0, 0.0),
+                    Vec2::new(0.0, 1.0),
+                    Vec2::new(1.0, 1.0),
+                ]
+                .into_iter()
+                .flat_map(move |corner| {
+                    let vertex = top_left + Vec3::new(corner.x, 0.0, -corner.y) * tile_size;
+
+                    // Inset by half a pixel, so that neighbouring tiles in the cache never bleed in
+                    let uv = (slot_position + Vec2::splat(0.5) + corner * (TILE_SIZE as f32 - 1.0))
+                        / texture_size;
+
+                    [vertex.get_bytes(), uv.get_bytes()].concat()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let changed = vertices != self.vertices;
+        self.vertices = vertices;
+
+        changed
+    }
+}
@@ -1,7 +1,9 @@
 #version 410 core
 
+in vec3 color;
+
 out vec4 frag_color;
 
 void main() {
-    frag_color = vec4(1.0, 0.4, 0.2, 1.0);
+    frag_color = vec4(color, 1.0);
 }
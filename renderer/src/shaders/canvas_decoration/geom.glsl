@@ -0,0 +1,221 @@
+#version 410 core
+
+#define SYMBOL_ARROW 0
+#define SYMBOL_CHEVRON 1
+#define SYMBOL_TRIANGLE 2
+
+// Most symbols placed along a single segment, which is only reached by long segments when zoomed
+// in a long way
+#define MAX_SYMBOLS 16
+
+// Previous point, segment start, segment end, next point
+layout(lines_adjacency) in;
+
+in VertexData {
+    uint line_id;
+    vec3 position;
+    float distance;
+} in_data[];
+
+// Each symbol has at most 7 vertices (the shaft and head of an arrow)
+layout(triangle_strip, max_vertices = 112) out;
+
+out vec3 color;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// Size of the viewport in pixels
+uniform vec2 viewport;
+
+// Size of a world unit on the map at the center of the screen (in pixels), which the spacing is
+// measured with so that it carries on from one segment to the next when the camera is pitched
+uniform float map_scale;
+
+uniform samplerBuffer path_data;
+
+// Number of 32 bit floats per each path data struct
+const uint PATH_DATA_SIZE = 18;
+
+float get(uint i) {
+    return texelFetch(path_data, int(i)).r;
+}
+
+vec3 get_vec3(uint start) {
+    return vec3(
+        get(start),
+        get(start + 1),
+        get(start + 2)
+    );
+}
+
+bool get_bit(uint n, uint bit_number) {
+    uint one = 1;
+    return ((n >> bit_number) & one) == one;
+}
+
+uint get_bits(uint n, uint start, uint count) {
+    return (n >> start) & ((uint(1) << count) - uint(1));
+}
+
+struct Decoration {
+    bool has_decoration;
+    uint symbol;
+    bool reversed;
+
+    vec3 color;
+    float size;
+    float spacing;
+};
+
+Decoration get_decoration(uint path) {
+    uint base = path * PATH_DATA_SIZE;
+
+    uint metadata = floatBitsToUint(get(base));
+
+    bool has_decoration = get_bit(metadata, 7);
+    uint symbol = get_bits(metadata, 8, 2);
+    bool reversed = get_bit(metadata, 10);
+
+    vec3 color = get_vec3(base + 13);
+    float size = get(base + 16);
+    float spacing = get(base + 17);
+
+    return Decoration (
+        has_decoration,
+        symbol,
+        reversed,
+        color,
+        size,
+        spacing
+    );
+}
+
+// Symbols are built in screen space (in pixels, relative to the center of the viewport) so that
+// they stay the same size at any zoom
+struct ScreenPoint {
+    vec2 position;
+    float depth;
+};
+
+ScreenPoint to_screen(vec3 world) {
+    vec4 clip = projection * view * vec4(world, 1.0);
+    vec3 ndc = clip.xyz / clip.w;
+
+    return ScreenPoint(ndc.xy * viewport * 0.5, ndc.z);
+}
+
+void emit(vec2 position, float depth) {
+    gl_Position = vec4(position / (viewport * 0.5), depth, 1.0);
+    EmitVertex();
+}
+
+// Symbol centered on a point, pointing forwards
+void emit_symbol(uint symbol, vec2 center, vec2 forward, float size, float depth) {
+    vec2 side = vec2(-forward.y, forward.x);
+
+    vec2 tip = center + forward * size * 0.5;
+    vec2 tail = center - forward * size * 0.5;
+    float half_width = size * 0.3;
+    float thickness = max(size * 0.15, 1.0);
+
+    if (symbol == SYMBOL_ARROW) {
+        vec2 head = tip - forward * size * 0.4;
+
+        // Shaft
+        emit(tail + side * thickness * 0.5, depth);
+        emit(tail - side * thickness * 0.5, depth);
+        emit(head + side * thickness * 0.5, depth);
+        emit(head - side * thickness * 0.5, depth);
+        EndPrimitive();
+
+        // Head
+        emit(head + side * half_width, depth);
+        emit(head - side * half_width, depth);
+        emit(tip, depth);
+        EndPrimitive();
+    } else if (symbol == SYMBOL_CHEVRON) {
+        // Two arms at 45 degrees, meeting at a point
+        vec2 offset = -forward * size * 0.3;
+        vec2 point = tip + offset * 0.5;
+        vec2 left = point - forward * size * 0.5 + side * size * 0.5;
+        vec2 right = point - forward * size * 0.5 - side * size * 0.5;
+
+        emit(left + offset, depth);
+        emit(left, depth);
+        emit(point + offset, depth);
+        emit(point, depth);
+        emit(right + offset, depth);
+        emit(right, depth);
+        EndPrimitive();
+    } else {
+        emit(tail + side * half_width, depth);
+        emit(tail - side * half_width, depth);
+        emit(tip, depth);
+        EndPrimitive();
+    }
+}
+
+void main() {
+    // Don't generate geometry between non-connected lines
+    if (in_data[1].line_id != in_data[2].line_id) return;
+
+    Decoration decoration = get_decoration(in_data[1].line_id);
+    if (!decoration.has_decoration || decoration.spacing <= 0.0) return;
+
+    ScreenPoint start = to_screen(in_data[1].position);
+    ScreenPoint end = to_screen(in_data[2].position);
+
+    vec2 l = end.position - start.position;
+    float screen_length = length(l);
+    if (screen_length == 0.0) return;
+
+    vec2 direction = l / screen_length;
+    vec2 forward = decoration.reversed ? -direction : direction;
+
+    // Distance along the path, in pixels
+    float start_distance = in_data[1].distance * map_scale;
+    float end_distance = in_data[2].distance * map_scale;
+    float segment_length = end_distance - start_distance;
+    if (segment_length <= 0.0) return;
+
+    // Only the part of the segment that's on screen needs symbols, found by clipping the segment
+    // to the viewport (with enough margin for symbols just off the edge)
+    vec2 bounds = viewport * 0.5 + decoration.size;
+    float t_start = 0.0;
+    float t_end = 1.0;
+    for (int axis = 0; axis < 2; axis++) {
+        if (abs(l[axis]) < 0.0001) {
+            if (abs(start.position[axis]) > bounds[axis]) return;
+        } else {
+            float a = (-bounds[axis] - start.position[axis]) / l[axis];
+            float b = (bounds[axis] - start.position[axis]) / l[axis];
+
+            t_start = max(t_start, min(a, b));
+            t_end = min(t_end, max(a, b));
+        }
+    }
+    if (t_start > t_end) return;
+
+    float visible_start = start_distance + t_start * segment_length;
+    float visible_end = start_distance + t_end * segment_length;
+
+    // Symbols are centered half of the spacing from the start of the path, and then every spacing
+    float first = ceil((visible_start - decoration.spacing * 0.5) / decoration.spacing);
+
+    color = decoration.color;
+
+    for (int i = 0; i < MAX_SYMBOLS; i++) {
+        float distance = (first + float(i) + 0.5) * decoration.spacing;
+        if (distance >= visible_end || distance >= end_distance) break;
+
+        float t = (distance - start_distance) / segment_length;
+        emit_symbol(
+            decoration.symbol,
+            start.position + l * t,
+            forward,
+            decoration.size,
+            mix(start.depth, end.depth, t)
+        );
+    }
+}
@@ -0,0 +1,17 @@
+#version 410 core
+
+layout(location = 0) in uint line_id;
+layout(location = 1) in vec3 position;
+layout(location = 2) in float distance;
+
+out VertexData {
+    uint line_id;
+    vec3 position;
+    float distance;
+} out_data;
+
+void main() {
+    out_data.line_id = line_id;
+    out_data.position = position;
+    out_data.distance = distance;
+}
@@ -1,11 +1,11 @@
 #version 410 core
 
 in VertexData {
-    vec3 color;
+    vec4 color;
 } in_data;
 
 out vec4 frag_color;
 
 void main() {
-    frag_color = vec4(in_data.color, 1.0);
+    frag_color = in_data.color;
 }
@@ -9,10 +9,10 @@ uniform mat4 view;
 uniform samplerBuffer path_data;
 
 // Number of 32 bit floats per each path data struct
-const uint PATH_DATA_SIZE = 9;
+const uint PATH_DATA_SIZE = 18;
 
 out VertexData {
-    vec3 color;
+    vec4 color;
 } out_data;
 
 float get(uint i) {
@@ -40,10 +40,11 @@ struct PathData {
     // Stroke
     vec3 stroke_color;
     float stroke_width;
-    float stroke_dash;
+    vec4 stroke_dash;
 
     // Fill
     vec3 fill_color;
+    float fill_opacity;
 };
 
 PathData get_path(uint path) {
@@ -56,9 +57,10 @@ PathData get_path(uint path) {
 
     vec3 stroke_color = get_vec3(base + 1);
     float stroke_width = get(base + 4);
-    float stroke_dash = get(base + 5);
+    vec4 stroke_dash = vec4(get_vec3(base + 5), get(base + 8));
 
-    vec3 fill_color = get_vec3(base + 6);
+    vec3 fill_color = get_vec3(base + 9);
+    float fill_opacity = get(base + 12);
 
     return PathData (
         has_fill,
@@ -66,7 +68,8 @@ PathData get_path(uint path) {
         stroke_color,
         stroke_width,
         stroke_dash,
-        fill_color
+        fill_color,
+        fill_opacity
     );
 }
 
@@ -77,11 +80,5 @@ void main() {
 
     PathData path = get_path(line_id);
 
-    // out_data.color = vec3(1.0, 0.0, 0.0);
-
-    // out_data.color = vec4(path.has_stroke ? 1.0 : 0.0, path.has_fill ? 1.0 : 0.0, 0.0);
-    if (path.has_stroke) {
-        out_data.color = path.fill_color;
-        // out_data.color = vec3(1.0, 0.0, 0.0);
-    }
+    out_data.color = vec4(path.fill_color, path.fill_opacity);
 }
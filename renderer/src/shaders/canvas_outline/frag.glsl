@@ -1,9 +1,22 @@
 #version 410 core
 
 in vec3 color;
+noperspective in float distance;
+flat in vec4 dash;
 
 out vec4 frag_color;
 
 void main() {
+    // Skip fragments that fall within the gaps of the dash pattern
+    float pattern_length = dash.x + dash.y + dash.z + dash.w;
+    if (pattern_length > 0.0) {
+        float position = mod(distance, pattern_length);
+
+        bool in_gap = (position >= dash.x && position < dash.x + dash.y)
+            || position >= dash.x + dash.y + dash.z;
+
+        if (in_gap) discard;
+    }
+
     frag_color = vec4(color, 1.0);
 }
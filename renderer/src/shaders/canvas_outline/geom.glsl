@@ -1,31 +1,58 @@
 #version 410 core
 
 #define PI 3.1415926538
-#define ARC_RESOLUTION 5
+#define ARC_RESOLUTION 8
 
-layout(lines) in;
+// Miter joins sharper than this (as a ratio of miter length to stroke width) become bevels
+#define MITER_LIMIT 4.0
+
+#define JOIN_MITER 0
+#define JOIN_BEVEL 1
+#define JOIN_ROUND 2
+
+#define CAP_BUTT 0
+#define CAP_ROUND 1
+#define CAP_SQUARE 2
+
+#define UNITS_PIXELS 0
+#define UNITS_WORLD 1
+
+// Previous point, segment start, segment end, next point
+layout(lines_adjacency) in;
 
 in VertexData {
     uint line_id;
     vec3 position;
+    float distance;
 } in_data[];
 
-layout(triangle_strip, max_vertices = 256) out;
-// layout(points, max_vertices = 256) out;
+// Body (4), a round join (3 * ARC_RESOLUTION) and two round caps (2 * 3 * ARC_RESOLUTION), kept
+// small enough for the output component limit (1024)
+layout(triangle_strip, max_vertices = 76) out;
 
 out vec3 color;
 
+// Distance along the path, in the units of the stroke
+noperspective out float distance;
+
+// Lengths of the dashes and gaps, in the units of the stroke
+flat out vec4 dash;
+
 uniform mat4 projection;
 uniform mat4 view;
 
+// Size of the viewport in pixels
+uniform vec2 viewport;
+
+// Size of a world unit on the map at the center of the screen (in pixels). Dashes in pixels are
+// measured with this rather than the size of each segment on screen, which varies when the camera
+// is pitched, so that the pattern carries on from one segment to the next.
+uniform float map_scale;
+
 uniform samplerBuffer path_data;
 
 // Number of 32 bit floats per each path data struct
-const uint PATH_DATA_SIZE = 9;
-
-out VertexData {
-    vec3 color;
-} out_data;
+const uint PATH_DATA_SIZE = 18;
 
 float get(uint i) {
     return texelFetch(path_data, int(i)).r;
@@ -44,15 +71,22 @@ bool get_bit(uint n, uint bit_number) {
     return ((n >> bit_number) & one) == one;
 }
 
+uint get_bits(uint n, uint start, uint count) {
+    return (n >> start) & ((uint(1) << count) - uint(1));
+}
+
 struct PathData {
     // Metadata
     bool has_stroke;
     bool has_fill;
+    uint stroke_join;
+    uint stroke_cap;
+    uint stroke_units;
 
     // Stroke
     vec3 stroke_color;
     float stroke_width;
-    float stroke_dash;
+    vec4 stroke_dash;
 
     // Fill
     vec3 fill_color;
@@ -65,16 +99,22 @@ PathData get_path(uint path) {
 
     bool has_stroke = get_bit(metadata, 1);
     bool has_fill = get_bit(metadata, 0);
+    uint stroke_join = get_bits(metadata, 2, 2);
+    uint stroke_cap = get_bits(metadata, 4, 2);
+    uint stroke_units = get_bits(metadata, 6, 1);
 
     vec3 stroke_color = get_vec3(base + 1);
     float stroke_width = get(base + 4);
-    float stroke_dash = get(base + 5);
+    vec4 stroke_dash = vec4(get_vec3(base + 5), get(base + 8));
 
-    vec3 fill_color = get_vec3(base + 6);
+    vec3 fill_color = get_vec3(base + 9);
 
     return PathData (
         has_stroke,
         has_fill,
+        stroke_join,
+        stroke_cap,
+        stroke_units,
         stroke_color,
         stroke_width,
         stroke_dash,
@@ -82,92 +122,177 @@ PathData get_path(uint path) {
     );
 }
 
+// Geometry is built in screen space (in pixels, relative to the center of the viewport) so that
+// widths can be given in pixels
+struct ScreenPoint {
+    vec2 position;
+    float depth;
+};
+
+ScreenPoint to_screen(vec3 world) {
+    vec4 clip = projection * view * vec4(world, 1.0);
+    vec3 ndc = clip.xyz / clip.w;
+
+    return ScreenPoint(ndc.xy * viewport * 0.5, ndc.z);
+}
+
+void emit(vec2 position, float depth, float path_distance) {
+    gl_Position = vec4(position / (viewport * 0.5), depth, 1.0);
+    distance = path_distance;
+    EmitVertex();
+}
+
+void emit_triangle(vec2 a, vec2 b, vec2 c, float depth, float path_distance) {
+    emit(a, depth, path_distance);
+    emit(b, depth, path_distance);
+    emit(c, depth, path_distance);
+    EndPrimitive();
+}
+
+vec2 perpendicular(vec2 v) {
+    return vec2(-v.y, v.x);
+}
+
+// Fan of triangles around `center`, sweeping from `from` to `to` (both offsets from the center)
+// the short way around
+void emit_arc(vec2 center, vec2 from, vec2 to, int segments, float depth, float path_distance) {
+    float radius = length(from);
+    float start_angle = atan(from.y, from.x);
+    float sweep = atan(to.y, to.x) - start_angle;
+
+    // Wrap the sweep to (-PI, PI]
+    if (sweep > PI) sweep -= 2.0 * PI;
+    if (sweep <= -PI) sweep += 2.0 * PI;
+
+    vec2 previous = from;
+    for (int i = 1; i <= segments; i++) {
+        float angle = start_angle + sweep * (float(i) / segments);
+        vec2 current = i == segments ? to : vec2(cos(angle), sin(angle)) * radius;
+
+        emit_triangle(center, center + previous, center + current, depth, path_distance);
+
+        previous = current;
+    }
+}
+
 void main() {
     // Don't generate geometry between non-connected lines
-    if (in_data[0].line_id != in_data[1].line_id) return;
+    if (in_data[1].line_id != in_data[2].line_id) return;
 
     // Load path data
-    PathData path = get_path(in_data[0].line_id);
-    vec3 start = in_data[0].position;
-    vec3 end = in_data[1].position;
+    PathData path = get_path(in_data[1].line_id);
+    if (!path.has_stroke) return;
 
-    gl_PointSize = 10.0;
+    ScreenPoint prev = to_screen(in_data[0].position);
+    ScreenPoint start = to_screen(in_data[1].position);
+    ScreenPoint end = to_screen(in_data[2].position);
+    ScreenPoint next = to_screen(in_data[3].position);
 
-    vec3 l = end - start;
-    vec3 l_perp = normalize(vec3(l.z, l.y, -l.x));
-    vec3 normalized_l = normalize(l);
+    // Ends of the path are marked by repeating the end points
+    bool has_prev = in_data[0].position != in_data[1].position;
+    bool has_next = in_data[3].position != in_data[2].position;
 
-    mat4 pv = projection * view;
+    vec2 l = end.position - start.position;
+    if (length(l) == 0.0) return;
 
-    float line_length = length(l);
+    vec2 direction = normalize(l);
+    vec2 normal = perpendicular(direction);
 
-    // Default stroke dash if none is provided
-    if (path.stroke_dash == 0.0) path.stroke_dash = line_length;
+    // Size of a world unit on screen (in pixels)
+    vec3 world_l = in_data[2].position - in_data[1].position;
+    float world_scale = length(l) / length(world_l);
 
-    int stroke_count = int(ceil(line_length / path.stroke_dash));
+    // Half of the stroke width, in pixels
+    float half_width = path.stroke_width * 0.5;
+    if (path.stroke_units == UNITS_WORLD) {
+        // Measure how large a world unit (perpendicular to the segment) is on screen
+        vec3 world_normal = normalize(vec3(world_l.z, 0.0, -world_l.x));
 
-    // Use same color for line
-    color = path.stroke_color;
-
-    // Generate segments
-    for (float stroke_i = 0.5; stroke_i < float(stroke_count); stroke_i += 2.0) {
-        vec3 line_middle = start + (float(stroke_i) * path.stroke_dash * normalized_l);
-
-        // Generate corners
-        vec3 corners[4];
-        uint i = 0;
-        for (int dx = -1; dx <= 1; dx += 2) {
-            for (int dy = -1; dy <= 1; dy += 2) {
-                vec3 p = line_middle
-                    + (l_perp * path.stroke_width * 0.5 * dx) // Add width
-                    + (normalized_l * dy * path.stroke_dash * 0.5); // Add length
-
-                corners[i] = p;
-                i++;
-            }
-        }
-
-        // Emit corners
-        int indices[] = int[]( 0, 1, 2, 1, 2, 3 );
-        for (uint i = 0; i < indices.length(); i++) {
-            gl_Position = pv * vec4(corners[i], 1.0);
-            EmitVertex();
-        }
+        half_width *= length(to_screen(in_data[1].position + world_normal).position - start.position);
+    }
 
-        EndPrimitive();
+    // Distances are measured in the same units as the stroke width
+    float distance_scale = path.stroke_units == UNITS_WORLD ? 1.0 : map_scale;
+    float units_per_pixel = distance_scale / world_scale;
+    float start_distance = in_data[1].distance * distance_scale;
+    float end_distance = in_data[2].distance * distance_scale;
 
-        // Generate rounded caps
-        for (int dir = -1; dir <= 1; dir += 2) {
-            vec3 middle = line_middle + (normalized_l * path.stroke_dash * 0.5 * dir);
+    color = path.stroke_color;
+    dash = path.stroke_dash;
 
-            color = vec3(1.0, 0.0, 0.0);
-            gl_Position = pv * vec4(middle, 1.0);
+    // Offsets for each corner of the segment, which are extended to the miter point when mitering
+    vec2 start_offset = normal * half_width;
+    vec2 end_offset = normal * half_width;
 
-            vec3 arc_points[ARC_RESOLUTION + 1];
+    if (path.stroke_join == JOIN_MITER) {
+        if (has_prev && length(start.position - prev.position) > 0.0) {
+            vec2 prev_normal = perpendicular(normalize(start.position - prev.position));
+            vec2 miter = normalize(prev_normal + normal);
+            float miter_length = 1.0 / dot(miter, normal);
 
-            for (uint i = 0; i <= ARC_RESOLUTION + 1; i++) {
-                float angle = PI / ARC_RESOLUTION * i;
+            if (miter_length <= MITER_LIMIT) start_offset = miter * half_width * miter_length;
+        }
 
-                vec3 p = middle
-                    + (l_perp * path.stroke_width * 0.5 * cos(angle))
-                    + (normalized_l * path.stroke_width * 0.5 * dir * sin(angle));
+        if (has_next && length(next.position - end.position) > 0.0) {
+            vec2 next_normal = perpendicular(normalize(next.position - end.position));
+            vec2 miter = normalize(normal + next_normal);
+            float miter_length = 1.0 / dot(miter, normal);
 
-                arc_points[i] = p;
-            }
+            if (miter_length <= MITER_LIMIT) end_offset = miter * half_width * miter_length;
+        }
+    }
 
-            color = path.stroke_color;
+    // Extend open ends for square caps
+    vec2 start_position = start.position;
+    vec2 end_position = end.position;
+    float start_position_distance = start_distance;
+    float end_position_distance = end_distance;
+    if (path.stroke_cap == CAP_SQUARE) {
+        if (!has_prev) {
+            start_position -= direction * half_width;
+            start_position_distance -= half_width * units_per_pixel;
+        }
+        if (!has_next) {
+            end_position += direction * half_width;
+            end_position_distance += half_width * units_per_pixel;
+        }
+    }
 
-            // Emit vertices for caps
-            for (uint i = 0; i < arc_points.length() - 1; i++) {
-                vec3 points[] = vec3[]( middle, arc_points[i], arc_points[i + 1] );
+    // Body of the segment
+    emit(start_position + start_offset, start.depth, start_position_distance);
+    emit(start_position - start_offset, start.depth, start_position_distance);
+    emit(end_position + end_offset, end.depth, end_position_distance);
+    emit(end_position - end_offset, end.depth, end_position_distance);
+    EndPrimitive();
+
+    // Join with the next segment (the previous segment draws the join at the start), filling in
+    // the gap on the outside of the corner
+    if (has_next && length(next.position - end.position) > 0.0) {
+        vec2 next_normal = perpendicular(normalize(next.position - end.position));
+
+        // Outer side of the corner is opposite to the direction that the line turns
+        float side = dot(next.position - end.position, normal) > 0.0 ? -1.0 : 1.0;
+        vec2 from = normal * half_width * side;
+        vec2 to = next_normal * half_width * side;
+
+        if (path.stroke_join == JOIN_ROUND) {
+            emit_arc(end.position, from, to, ARC_RESOLUTION, end.depth, end_distance);
+        } else if (path.stroke_join == JOIN_BEVEL || end_offset == normal * half_width) {
+            // Bevels, along with miters that exceeded the limit
+            emit_triangle(end.position, end.position + from, end.position + to, end.depth, end_distance);
+        }
+    }
 
-                for (int point_i = 0; point_i < points.length(); point_i++) {
-                    gl_Position = pv * vec4(points[point_i], 1.0);
-                    EmitVertex();
-                }
-            }
+    // Round caps at the ends of the path, as two quarter circles
+    if (path.stroke_cap == CAP_ROUND) {
+        if (!has_prev) {
+            emit_arc(start.position, normal * half_width, -direction * half_width, ARC_RESOLUTION / 2, start.depth, start_distance);
+            emit_arc(start.position, -direction * half_width, -normal * half_width, ARC_RESOLUTION / 2, start.depth, start_distance);
+        }
 
-            EndPrimitive();
+        if (!has_next) {
+            emit_arc(end.position, normal * half_width, direction * half_width, ARC_RESOLUTION / 2, end.depth, end_distance);
+            emit_arc(end.position, direction * half_width, -normal * half_width, ARC_RESOLUTION / 2, end.depth, end_distance);
         }
     }
 }
@@ -0,0 +1,26 @@
+#version 410 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec3 normal;
+layout(location = 2) in vec3 color;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// Direction towards the light, from the north west and above the map (up is -y)
+const vec3 LIGHT_DIRECTION = normalize(vec3(-0.5, -1.0, 0.6));
+
+// Proportion of the colour that's lit regardless of which way a face points, so that walls facing
+// away from the light aren't black
+const float AMBIENT = 0.6;
+
+out VertexData {
+    vec3 color;
+} out_data;
+
+void main() {
+    gl_Position = projection * view * vec4(position, 1.0);
+
+    float diffuse = max(dot(normal, LIGHT_DIRECTION), 0.0);
+    out_data.color = color * (AMBIENT + (1.0 - AMBIENT) * diffuse);
+}
@@ -0,0 +1,30 @@
+#version 410 core
+
+in VertexData {
+    vec2 uv;
+} in_data;
+
+out vec4 frag_color;
+
+// Sprite atlas, with four values (RGBA) per texel
+uniform samplerBuffer path_data;
+uniform float atlas_width;
+
+vec4 texel(ivec2 position) {
+    int i = (position.y * int(atlas_width) + position.x) * 4;
+
+    return vec4(
+        texelFetch(path_data, i).r,
+        texelFetch(path_data, i + 1).r,
+        texelFetch(path_data, i + 2).r,
+        texelFetch(path_data, i + 3).r
+    );
+}
+
+void main() {
+    vec4 color = texel(ivec2(floor(in_data.uv)));
+
+    if (color.a <= 0.0) discard;
+
+    frag_color = color;
+}
@@ -0,0 +1,31 @@
+#version 410 core
+
+layout(location = 0) in vec3 anchor;
+layout(location = 1) in vec2 offset;
+layout(location = 2) in vec2 uv;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// Size of the viewport in pixels
+uniform vec2 viewport;
+
+out VertexData {
+    vec2 uv;
+} out_data;
+
+void main() {
+    vec4 anchor_clip = projection * view * vec4(anchor, 1.0);
+
+    // Snap the icon to the pixel grid so that the sprite stays sharp
+    vec2 screen = ((anchor_clip.xy / anchor_clip.w) * 0.5 + 0.5) * viewport;
+    screen = floor(screen) + offset;
+
+    gl_Position = vec4(
+        (screen / viewport) * 2.0 - 1.0,
+        anchor_clip.z / anchor_clip.w,
+        1.0
+    );
+
+    out_data.uv = uv;
+}
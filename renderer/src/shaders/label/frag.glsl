@@ -0,0 +1,56 @@
+#version 410 core
+
+// Distance (in pixels) that the halo extends around each glyph
+#define HALO 2
+
+in VertexData {
+    vec2 uv;
+    vec3 color;
+} in_data;
+
+out vec4 frag_color;
+
+// Glyph atlas, with a single coverage value per texel
+uniform samplerBuffer path_data;
+uniform float atlas_width;
+
+const vec3 HALO_COLOR = vec3(1.0, 1.0, 1.0);
+
+float texel(ivec2 position) {
+    return texelFetch(path_data, position.y * int(atlas_width) + position.x).r;
+}
+
+// Bilinear sample of the atlas, as glyphs won't always line up with the pixel grid
+float sample_atlas(vec2 position) {
+    vec2 p = position - 0.5;
+    ivec2 i = ivec2(floor(p));
+    vec2 f = fract(p);
+
+    return mix(
+        mix(texel(i), texel(i + ivec2(1, 0)), f.x),
+        mix(texel(i + ivec2(0, 1)), texel(i + ivec2(1, 1)), f.x),
+        f.y
+    );
+}
+
+void main() {
+    float coverage = sample_atlas(in_data.uv);
+
+    // Halo is the largest coverage of any nearby texel
+    float halo = 0.0;
+    ivec2 center = ivec2(floor(in_data.uv));
+    for (int dx = -HALO; dx <= HALO; dx++) {
+        for (int dy = -HALO; dy <= HALO; dy++) {
+            halo = max(halo, texel(center + ivec2(dx, dy)));
+        }
+    }
+    halo *= 0.8;
+
+    // Composite the glyph over the halo
+    float alpha = coverage + (1.0 - coverage) * halo;
+    if (alpha <= 0.0) discard;
+
+    vec3 color = (in_data.color * coverage + HALO_COLOR * (1.0 - coverage) * halo) / alpha;
+
+    frag_color = vec4(color, alpha);
+}
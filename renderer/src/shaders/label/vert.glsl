@@ -0,0 +1,27 @@
+#version 410 core
+
+layout(location = 0) in vec3 anchor;
+layout(location = 1) in vec2 offset;
+layout(location = 2) in vec2 uv;
+layout(location = 3) in vec3 color;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+// Size of the viewport in pixels
+uniform vec2 viewport;
+
+out VertexData {
+    vec2 uv;
+    vec3 color;
+} out_data;
+
+void main() {
+    gl_Position = projection * view * vec4(anchor, 1.0);
+
+    // Offset is in pixels, so convert it to clip space
+    gl_Position.xy += (offset / viewport) * 2.0 * gl_Position.w;
+
+    out_data.uv = uv;
+    out_data.color = color;
+}
@@ -0,0 +1,42 @@
+#version 410 core
+
+in VertexData {
+    vec2 uv;
+    flat uvec3 raster;
+} in_data;
+
+out vec4 frag_color;
+
+// Pixels of every raster, with four values (RGBA) per texel
+uniform samplerBuffer path_data;
+
+vec4 texel(ivec2 position) {
+    ivec2 size = ivec2(in_data.raster.yz);
+    position = clamp(position, ivec2(0), size - 1);
+
+    int i = (int(in_data.raster.x) + position.y * size.x + position.x) * 4;
+
+    return vec4(
+        texelFetch(path_data, i).r,
+        texelFetch(path_data, i + 1).r,
+        texelFetch(path_data, i + 2).r,
+        texelFetch(path_data, i + 3).r
+    );
+}
+
+void main() {
+    // Blend between the four nearest pixel centres
+    vec2 position = in_data.uv - 0.5;
+    ivec2 corner = ivec2(floor(position));
+    vec2 t = fract(position);
+
+    vec4 color = mix(
+        mix(texel(corner), texel(corner + ivec2(1, 0)), t.x),
+        mix(texel(corner + ivec2(0, 1)), texel(corner + ivec2(1, 1)), t.x),
+        t.y
+    );
+
+    if (color.a <= 0.0) discard;
+
+    frag_color = color;
+}
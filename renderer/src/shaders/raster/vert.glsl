@@ -0,0 +1,20 @@
+#version 410 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 uv;
+layout(location = 2) in uvec3 raster;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+out VertexData {
+    vec2 uv;
+    flat uvec3 raster;
+} out_data;
+
+void main() {
+    gl_Position = projection * view * vec4(position, 1.0);
+
+    out_data.uv = uv;
+    out_data.raster = raster;
+}
@@ -0,0 +1,14 @@
+#version 410 core
+
+in VertexData {
+    vec2 uv;
+} in_data;
+
+out vec4 frag_color;
+
+// Cache of tiles
+uniform sampler2D image;
+
+void main() {
+    frag_color = texture(image, in_data.uv);
+}
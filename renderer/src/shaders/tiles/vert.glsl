@@ -0,0 +1,17 @@
+#version 410 core
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 uv;
+
+uniform mat4 projection;
+uniform mat4 view;
+
+out VertexData {
+    vec2 uv;
+} out_data;
+
+void main() {
+    gl_Position = projection * view * vec4(position, 1.0);
+
+    out_data.uv = uv;
+}
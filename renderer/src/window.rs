@@ -33,6 +33,8 @@ pub enum WindowEvent {
         keycode: VirtualKeyCode,
         state: ElementState,
     },
+    /// Text typed on the keyboard, after the key press that produced it
+    Character(char),
     MouseMove {
         physical_x: f32,
         physical_y: f32,
@@ -43,6 +45,8 @@ pub enum WindowEvent {
         x: f32,
         y: f32,
     },
+    /// About to draw a frame, allowing anything animated to move on
+    Frame,
 }
 
 pub struct WindowSize {
@@ -85,6 +89,7 @@ impl TryFrom<winit::event::WindowEvent<'_>> for WindowEvent {
                     },
                 ..
             } => Ok(Self::Keyboard { keycode, state }),
+            winit::event::WindowEvent::ReceivedCharacter(c) => Ok(Self::Character(c)),
             winit::event::WindowEvent::CursorMoved { position, .. } => Ok(Self::MouseMove {
                 physical_x: position.x as f32,
                 physical_y: position.y as f32,
@@ -129,19 +134,23 @@ impl Window {
                     .with_inner_size(size)
                     .with_min_inner_size(size)
             }))
-            .build(&event_loop, ConfigTemplateBuilder::new(), |configs| {
-                // Select the best config out of the available ones
-                configs
-                    .reduce(|ideal, config| {
-                        // Select the config with the greatest sampling
-                        if config.num_samples() > ideal.num_samples() {
-                            config
-                        } else {
-                            ideal
-                        }
-                    })
-                    .unwrap()
-            })
+            .build(
+                &event_loop,
+                ConfigTemplateBuilder::new().with_depth_size(24),
+                |configs| {
+                    // Select the best config out of the available ones
+                    configs
+                        .reduce(|ideal, config| {
+                            // Select the config with the greatest sampling
+                            if config.num_samples() > ideal.num_samples() {
+                                config
+                            } else {
+                                ideal
+                            }
+                        })
+                        .unwrap()
+                },
+            )
             .unwrap();
 
         // Save the window and display for future reference
@@ -196,6 +205,19 @@ impl Window {
         (size.height, size.width)
     }
 
+    fn info(&self) -> WindowInfo {
+        let window_scale = self.window.scale_factor();
+
+        WindowInfo {
+            size: self
+                .window
+                .inner_size()
+                .to_logical::<u32>(window_scale)
+                .into(),
+            scale: window_scale as f32,
+        }
+    }
+
     pub fn render(&self) -> glutin::error::Result<()> {
         self.gl.render();
 
@@ -216,7 +238,7 @@ impl Window {
             control_flow.set_wait();
 
             // Handle events
-            let forward_event = match event {
+            let action = match event {
                 Event::RedrawRequested(window_id) if self.window.id() == window_id => {
                     // Attempt to re-render
                     let now = Instant::now();
@@ -233,16 +255,22 @@ impl Window {
                         //     1.0 / frame_time.unwrap_or_default().as_secs_f32()
                         // );
 
+                        let action = event_handler(WindowEvent::Frame, self.info());
+
                         // TODO: bad
                         self.render().unwrap();
 
                         redraw_pending = false;
-                    } else if let Some(frame_time) = frame_time {
-                        redraw_pending = true;
-                        control_flow.set_wait_timeout(min_frame_time - frame_time)
-                    }
 
-                    None
+                        action
+                    } else {
+                        if let Some(frame_time) = frame_time {
+                            redraw_pending = true;
+                            control_flow.set_wait_timeout(min_frame_time - frame_time)
+                        }
+
+                        None
+                    }
                 }
                 Event::WindowEvent { window_id, event } if self.window.id() == window_id => {
                     match event {
@@ -250,30 +278,21 @@ impl Window {
                             control_flow.set_exit();
                             None
                         }
-                        window_event => window_event.try_into().ok(),
+                        window_event => window_event
+                            .try_into()
+                            .ok()
+                            .and_then(|event| event_handler(event, self.info())),
                     }
                 }
                 // Event::DeviceEvent { event, .. } => event.try_into().ok(),
                 _ => None,
             };
 
-            if let Some(event) = forward_event {
-                let window_scale = self.window.scale_factor();
-                let window_info = WindowInfo {
-                    size: self
-                        .window
-                        .inner_size()
-                        .to_logical::<u32>(window_scale)
-                        .into(),
-                    scale: window_scale as f32,
-                };
-
-                if let Some(action) = event_handler(event, window_info) {
-                    match action {
-                        WindowAction::Close => control_flow.set_exit(),
-                        WindowAction::RequestRedraw => {
-                            self.window.request_redraw();
-                        }
+            if let Some(action) = action {
+                match action {
+                    WindowAction::Close => control_flow.set_exit(),
+                    WindowAction::RequestRedraw => {
+                        self.window.request_redraw();
                     }
                 }
             }
@@ -0,0 +1,416 @@
+use glam::Vec3;
+use renderer::render_steps::canvas::Path;
+
+use crate::{map_data::MapData, objects::WATER_COLOR, Point};
+
+/// The sea and land are the base of the map, underneath everything else.
+const COASTLINE_LAYER: i32 = i32::MIN;
+
+/// Matches the background, so that land looks the same as on maps without a coastline.
+const LAND_COLOR: Vec3 = Vec3::ONE;
+
+/// Positions (projected, before translating into world space) around an area or along a line.
+type Ring = Vec<(f64, f64)>;
+
+/// Sea and land areas, assembled from the `natural=coastline` ways within the bounds of the map.
+///
+/// Coastlines are drawn with the land on the left and the sea on the right. Ways are joined end to
+/// end, with closed rings becoming islands (or sea, if they go clockwise), and lines that leave the
+/// map continued around its edge until they meet the next line that comes back in.
+// https://wiki.openstreetmap.org/wiki/Tag:natural%3Dcoastline
+pub(crate) fn coastline_paths(map_data: &MapData) -> Vec<Path> {
+    let Some((land, mut sea)) = areas(map_data) else {
+        return Vec::new();
+    };
+
+    let path = |ring: Ring, color: Vec3| {
+        let points = ring
+            .into_iter()
+            .map(|(x, y)| (&map_data.translate(Point::new(x as f32, y as f32))).into())
+            .collect();
+
+        Path::new(points)
+            .with_fill(color)
+            .with_layer(COASTLINE_LAYER)
+    };
+
+    // The whole map is sea to start with, with the land drawn over it and any enclosed sea over
+    // that
+    let mut paths = vec![path(sea.remove(0), WATER_COLOR).with_z_index(0)];
+    paths.extend(
+        land.into_iter()
+            .map(|ring| path(ring, LAND_COLOR).with_z_index(1)),
+    );
+    paths.extend(
+        sea.into_iter()
+            .map(|ring| path(ring, WATER_COLOR).with_z_index(2)),
+    );
+
+    paths
+}
+
+/// Land and sea areas, with the first sea area covering the whole map. There are none if the map
+/// doesn't have a coastline.
+fn areas(map_data: &MapData) -> Option<(Vec<Ring>, Vec<Ring>)> {
+    let (rings, lines) = join_coastlines(map_data);
+    if rings.is_empty() && lines.is_empty() {
+        return None;
+    }
+
+    let bounds = Bounds::new(map_data);
+    let mut land = Vec::new();
+    let mut sea = vec![bounds.ring()];
+
+    for ring in rings {
+        if signed_area(&ring) >= 0.0 {
+            land.push(ring);
+        } else {
+            sea.push(ring);
+        }
+    }
+
+    land.extend(bounds.close(lines));
+
+    Some((land, sea))
+}
+
+/// Joins coastline ways that share end nodes, returning the closed rings and the lines that are
+/// left open (as positions).
+fn join_coastlines(map_data: &MapData) -> (Vec<Ring>, Vec<Ring>) {
+    let mut remaining = map_data
+        .osm_data
+        .ways
+        .values()
+        .filter(|way| {
+            way.tags
+                .get("natural")
+                .filter(|&natural| natural == "coastline")
+                .is_some()
+        })
+        .filter(|way| way.nodes.len() >= 2)
+        .map(|way| way.nodes.clone())
+        .collect::<Vec<_>>();
+
+    let mut rings = Vec::new();
+    let mut lines = Vec::new();
+
+    while let Some(mut chain) = remaining.pop() {
+        loop {
+            if chain.len() > 2 && chain.first() == chain.last() {
+                rings.push(chain);
+                break;
+            }
+
+            if let Some(i) = remaining
+                .iter()
+                .position(|next| next.first() == chain.last())
+            {
+                let next = remaining.swap_remove(i);
+                chain.extend_from_slice(&next[1..]);
+            } else if let Some(i) = remaining
+                .iter()
+                .position(|previous| previous.last() == chain.first())
+            {
+                let mut previous = remaining.swap_remove(i);
+                previous.extend_from_slice(&chain[1..]);
+                chain = previous;
+            } else {
+                lines.push(chain);
+                break;
+            }
+        }
+    }
+
+    let positions = |chain: Vec<i64>| {
+        chain
+            .iter()
+            .filter_map(|node| map_data.osm_data.nodes.get(node))
+            .map(|node| (node.x, node.y))
+            .collect::<Vec<_>>()
+    };
+
+    (
+        rings.into_iter().map(positions).collect(),
+        lines
+            .into_iter()
+            .map(positions)
+            .filter(|line| line.len() >= 2)
+            .collect(),
+    )
+}
+
+/// Twice the area enclosed by a ring, which is positive if it goes anticlockwise.
+fn signed_area(ring: &[(f64, f64)]) -> f64 {
+    ring.windows(2)
+        .map(|pair| pair[0].0 * pair[1].1 - pair[1].0 * pair[0].1)
+        .sum()
+}
+
+/// Edge of the map, with positions around it measured anticlockwise from the bottom left corner.
+struct Bounds {
+    min_x: f64,
+    min_y: f64,
+    width: f64,
+    height: f64,
+}
+
+impl Bounds {
+    fn new(map_data: &MapData) -> Self {
+        let bounding = &map_data.bounding;
+
+        Self {
+            min_x: bounding.min_x,
+            min_y: bounding.min_y,
+            width: bounding.dx(),
+            height: bounding.dy(),
+        }
+    }
+
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.width + self.height)
+    }
+
+    /// Distances around the edge of each corner, starting with the bottom right.
+    fn corners(&self) -> [f64; 4] {
+        [
+            self.width,
+            self.width + self.height,
+            2.0 * self.width + self.height,
+            0.0,
+        ]
+    }
+
+    fn ring(&self) -> Ring {
+        let mut ring = self
+            .corners()
+            .iter()
+            .map(|&distance| self.position(distance))
+            .collect::<Vec<_>>();
+        ring.push(ring[0]);
+
+        ring
+    }
+
+    /// Distance around the edge to the closest point on it.
+    fn distance(&self, (x, y): (f64, f64)) -> f64 {
+        let (x, y) = (
+            (x - self.min_x).clamp(0.0, self.width),
+            (y - self.min_y).clamp(0.0, self.height),
+        );
+
+        // Distance to the bottom, right, top and left edges, and the distance around to the
+        // closest point on each
+        [
+            (y, x),
+            (self.width - x, self.width + y),
+            (self.height - y, 2.0 * self.width + self.height - x),
+            (x, self.perimeter() - y),
+        ]
+        .into_iter()
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, distance)| distance % self.perimeter())
+        .unwrap_or_default()
+    }
+
+    fn position(&self, distance: f64) -> (f64, f64) {
+        let distance = distance.rem_euclid(self.perimeter());
+        let (w, h) = (self.width, self.height);
+
+        let (x, y) = if distance <= w {
+            (distance, 0.0)
+        } else if distance <= w + h {
+            (w, distance - w)
+        } else if distance <= 2.0 * w + h {
+            (2.0 * w + h - distance, h)
+        } else {
+            (0.0, self.perimeter() - distance)
+        };
+
+        (self.min_x + x, self.min_y + y)
+    }
+
+    /// Closes lines that leave the map into land areas. Land is on the left of each line, so from
+    /// where a line leaves the map, the edge is followed anticlockwise to the next line that comes
+    /// back in.
+    fn close(&self, lines: Vec<Ring>) -> Vec<Ring> {
+        let perimeter = self.perimeter();
+        let ends = lines
+            .iter()
+            .map(|line| (self.distance(line[0]), self.distance(line[line.len() - 1])))
+            .collect::<Vec<_>>();
+
+        let mut visited = vec![false; lines.len()];
+        let mut areas = Vec::new();
+
+        for first in 0..lines.len() {
+            if visited[first] {
+                continue;
+            }
+
+            let mut area = Vec::new();
+            let mut current = first;
+
+            loop {
+                visited[current] = true;
+                area.extend_from_slice(&lines[current]);
+
+                let exit = ends[current].1;
+                let around = |distance: f64| (distance - exit).rem_euclid(perimeter);
+
+                // Every line is an option, including the current one looping back around
+                let next = (0..lines.len())
+                    .filter(|&i| !visited[i] || i == first)
+                    .min_by(|&a, &b| around(ends[a].0).total_cmp(&around(ends[b].0)))
+                    .expect("first line should still be an option");
+
+                area.push(self.position(exit));
+
+                let mut corners = self
+                    .corners()
+                    .into_iter()
+                    .filter(|&corner| around(corner) > 0.0 && around(corner) < around(ends[next].0))
+                    .collect::<Vec<_>>();
+                corners.sort_by(|&a, &b| around(a).total_cmp(&around(b)));
+                area.extend(corners.into_iter().map(|corner| self.position(corner)));
+
+                area.push(self.position(ends[next].0));
+
+                if next == first {
+                    area.push(area[0]);
+                    break;
+                }
+
+                current = next;
+            }
+
+            areas.push(area);
+        }
+
+        areas
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{areas, join_coastlines, signed_area};
+    use crate::{
+        map_data::MapData,
+        osm::{Node, Osm, Tags, Way},
+    };
+
+    /// Map from (0, 0) to (100, 100), with nodes at projected positions and coastline ways along
+    /// them.
+    fn map(nodes: &[(i64, f64, f64)], coastlines: &[&[i64]]) -> MapData {
+        let corners = [(-1, 0.0, 0.0), (-2, 100.0, 100.0)];
+
+        MapData::new(Osm {
+            nodes: corners
+                .iter()
+                .chain(nodes)
+                .map(|&(id, x, y)| (id, Node::new(x, y)))
+                .collect(),
+            ways: coastlines
+                .iter()
+                .enumerate()
+                .map(|(i, nodes)| {
+                    let way = Way {
+                        tags: Tags::from_iter([("natural", "coastline")]),
+                        nodes: nodes.to_vec(),
+                    };
+
+                    (i as i64, way)
+                })
+                .collect(),
+            relations: HashMap::new(),
+        })
+    }
+
+    /// Square in the middle of the map, with its corners numbered anticlockwise.
+    const SQUARE: [(i64, f64, f64); 4] = [
+        (1, 40.0, 40.0),
+        (2, 60.0, 40.0),
+        (3, 60.0, 60.0),
+        (4, 40.0, 60.0),
+    ];
+
+    #[test]
+    fn joins_ways_into_rings() {
+        let map = map(&SQUARE, &[&[3, 4], &[1, 2, 3], &[4, 1]]);
+        let (rings, lines) = join_coastlines(&map);
+
+        assert_eq!(rings.len(), 1);
+        assert!(lines.is_empty());
+
+        // Each node once, with the ring closed
+        let ring = &rings[0];
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring.first(), ring.last());
+        assert_eq!(signed_area(ring), 2.0 * 400.0);
+    }
+
+    #[test]
+    fn islands_and_lakes() {
+        // Anticlockwise, so land is on the inside
+        let (land, sea) = areas(&map(&SQUARE, &[&[1, 2, 3, 4, 1]])).unwrap();
+
+        assert_eq!(land.len(), 1);
+        assert_eq!(sea.len(), 1);
+        assert_eq!(signed_area(&sea[0]), 2.0 * 10000.0);
+
+        // Clockwise, so sea is on the inside (eg the map is within a large island)
+        let (land, sea) = areas(&map(&SQUARE, &[&[1, 4, 3, 2, 1]])).unwrap();
+
+        assert!(land.is_empty());
+        assert_eq!(sea.len(), 2);
+        assert_eq!(signed_area(&sea[1]), -2.0 * 400.0);
+    }
+
+    #[test]
+    fn closes_lines_around_the_edge() {
+        let nodes = [(1, 0.0, 50.0), (2, 50.0, 50.0), (3, 100.0, 50.0)];
+
+        // Heading east, with the land to the north
+        let (land, _) = areas(&map(&nodes, &[&[1, 2], &[2, 3]])).unwrap();
+
+        assert_eq!(land.len(), 1);
+        assert_eq!(
+            land[0],
+            vec![
+                (0.0, 50.0),
+                (50.0, 50.0),
+                (100.0, 50.0),
+                (100.0, 50.0),
+                (100.0, 100.0),
+                (0.0, 100.0),
+                (0.0, 50.0),
+                (0.0, 50.0),
+            ]
+        );
+
+        // Heading west, with the land to the south
+        let (land, _) = areas(&map(&nodes, &[&[3, 2, 1]])).unwrap();
+
+        assert_eq!(land.len(), 1);
+        assert_eq!(signed_area(&land[0]), 2.0 * 5000.0);
+        assert!(land[0].iter().all(|&(_, y)| y <= 50.0));
+    }
+
+    #[test]
+    fn joins_lines_along_the_edge() {
+        // Two headlands cut off the bottom corners, leaving a bay between them
+        let nodes = [
+            (1, 0.0, 50.0),
+            (2, 50.0, 0.0),
+            (3, 70.0, 0.0),
+            (4, 85.0, 25.0),
+            (5, 100.0, 50.0),
+        ];
+        let (land, _) = areas(&map(&nodes, &[&[1, 2], &[3, 4], &[4, 5]])).unwrap();
+
+        // One area, without the triangles in the bottom left and right
+        assert_eq!(land.len(), 1);
+        assert_eq!(signed_area(&land[0]), 2.0 * (10000.0 - 1250.0 - 750.0));
+    }
+}
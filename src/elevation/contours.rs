@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+use renderer::render_steps::canvas::{LineJoin, Path, Stroke};
+
+use super::Heightmap;
+use crate::{objects::CONTOUR_Z_INDEX, Point};
+
+/// Every nth contour is an index contour, drawn more prominently to make heights easier to follow.
+const INDEX_CONTOUR_EVERY: i64 = 5;
+
+const CONTOUR_COLOR: Vec3 = Vec3::new(209.0 / 255.0, 176.0 / 255.0, 143.0 / 255.0);
+const INDEX_CONTOUR_COLOR: Vec3 = Vec3::new(176.0 / 255.0, 136.0 / 255.0, 98.0 / 255.0);
+
+/// Edge between two neighbouring samples, as the sample it starts from and whether it goes north
+/// (rather than east).
+type Edge = (usize, usize, bool);
+
+/// Lines of constant elevation, every `interval` metres.
+pub(crate) fn contour_paths(heightmap: &Heightmap, interval: f32) -> Vec<Path> {
+    if interval <= 0.0 {
+        return Vec::new();
+    }
+
+    // Segments crossing each cell, keyed by the contour's level (in multiples of the interval)
+    let mut segments = HashMap::<i64, Vec<(Edge, Edge)>>::new();
+
+    for row in 0..heightmap.height - 1 {
+        for column in 0..heightmap.width - 1 {
+            let (c, r) = (column as isize, row as isize);
+
+            // Corners of the cell, anticlockwise from the south west
+            let corners = [
+                heightmap.sample(c, r),
+                heightmap.sample(c + 1, r),
+                heightmap.sample(c + 1, r + 1),
+                heightmap.sample(c, r + 1),
+            ];
+            if corners.iter().any(|corner| corner.is_nan()) {
+                continue;
+            }
+
+            // Edges of the cell, anticlockwise from the south edge (each following its corner)
+            let edges = [
+                (column, row, false),
+                (column + 1, row, true),
+                (column, row + 1, false),
+                (column, row, true),
+            ];
+
+            let lowest = corners.iter().copied().fold(f32::INFINITY, f32::min);
+            let highest = corners.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+
+            for level in (lowest / interval).ceil() as i64..=(highest / interval).floor() as i64 {
+                let elevation = level as f32 * interval;
+                let above = corners.map(|corner| corner >= elevation);
+
+                let crossed = (0..4)
+                    .filter(|&i| above[i] != above[(i + 1) % 4])
+                    .map(|i| edges[i])
+                    .collect::<Vec<_>>();
+
+                let cell_segments = segments.entry(level).or_default();
+                match crossed[..] {
+                    [a, b] => cell_segments.push((a, b)),
+                    [south, east, north, west] => {
+                        // Saddle, where the middle of the cell decides which corners are cut off
+                        let middle = corners.iter().sum::<f32>() / 4.0 >= elevation;
+
+                        if middle == above[0] {
+                            cell_segments.extend([(south, east), (north, west)]);
+                        } else {
+                            cell_segments.extend([(west, south), (east, north)]);
+                        }
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+
+    segments
+        .into_iter()
+        .flat_map(|(level, segments)| {
+            let elevation = level as f32 * interval;
+            let (width, color) = if level % INDEX_CONTOUR_EVERY == 0 {
+                (1.6, INDEX_CONTOUR_COLOR)
+            } else {
+                (1.0, CONTOUR_COLOR)
+            };
+
+            join_segments(segments).into_iter().filter_map(move |line| {
+                let points = line
+                    .into_iter()
+                    .map(|edge| (&crossing(heightmap, edge, elevation)).into())
+                    .collect::<Vec<Vec3>>();
+
+                // Contours at exactly the height of a peak (or pit) shrink to a single point
+                if points.iter().all(|&point| point == points[0]) {
+                    return None;
+                }
+
+                Some(
+                    Path::new(points)
+                        .with_stroke(Stroke::new(width, color).with_join(LineJoin::Round))
+                        .with_z_index(CONTOUR_Z_INDEX),
+                )
+            })
+        })
+        .collect()
+}
+
+/// Joins segments that share an edge into lines, which are closed if they loop back round.
+fn join_segments(segments: Vec<(Edge, Edge)>) -> Vec<Vec<Edge>> {
+    // Each edge is crossed by the segments of the (at most two) cells either side of it
+    let mut by_edge = HashMap::<Edge, Vec<usize>>::new();
+    for (i, &(a, b)) in segments.iter().enumerate() {
+        by_edge.entry(a).or_default().push(i);
+        by_edge.entry(b).or_default().push(i);
+    }
+
+    let mut used = vec![false; segments.len()];
+    let mut lines = Vec::new();
+
+    for start in 0..segments.len() {
+        if used[start] {
+            continue;
+        }
+        used[start] = true;
+
+        let (a, b) = segments[start];
+        let mut line = vec![a, b];
+
+        // Follow the line forwards from the end, then backwards from the start
+        for forwards in [true, false] {
+            loop {
+                let end = if forwards {
+                    line[line.len() - 1]
+                } else {
+                    line[0]
+                };
+
+                let Some(next) = by_edge[&end].iter().copied().find(|&i| !used[i]) else {
+                    break;
+                };
+                used[next] = true;
+
+                let (a, b) = segments[next];
+                let other = if a == end { b } else { a };
+
+                if forwards {
+                    line.push(other);
+                } else {
+                    line.insert(0, other);
+                }
+            }
+        }
+
+        lines.push(line);
+    }
+
+    lines
+}
+
+/// Position along an edge where the terrain crosses an elevation.
+fn crossing(heightmap: &Heightmap, (column, row, north): Edge, elevation: f32) -> Point {
+    let (c, r) = (column as isize, row as isize);
+    let (from, to) = if north {
+        (heightmap.sample(c, r), heightmap.sample(c, r + 1))
+    } else {
+        (heightmap.sample(c, r), heightmap.sample(c + 1, r))
+    };
+
+    let t = (elevation - from) / (to - from);
+    let (column, row) = (column as f32, row as f32);
+
+    if north {
+        heightmap.position(column, row + t)
+    } else {
+        heightmap.position(column + t, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use renderer::render_steps::canvas::CanvasObject;
+
+    use super::{super::Heightmap, contour_paths};
+    use crate::Point;
+
+    /// Heightmap with samples 1 unit apart from the origin, given as rows from south to north.
+    fn heightmap(rows: &[&[f32]]) -> Heightmap {
+        Heightmap {
+            origin: Point::new(0.0, 0.0),
+            spacing: 1.0,
+            spacing_metres: 1.0,
+            width: rows[0].len(),
+            height: rows.len(),
+            samples: rows.concat(),
+        }
+    }
+
+    /// Points along each contour line, rounded and in a consistent order so that they can be
+    /// compared.
+    fn lines(heightmap: &Heightmap, interval: f32) -> Vec<Vec<(f32, f32)>> {
+        let mut lines = contour_paths(heightmap, interval)
+            .iter()
+            .map(|path| {
+                let mut line = path
+                    .get_vertices()
+                    .iter()
+                    .map(|point| {
+                        let round = |value: f32| (value * 1000.0).round() / 1000.0;
+                        (round(point.x), round(point.z))
+                    })
+                    .collect::<Vec<_>>();
+
+                if line.last() < line.first() {
+                    line.reverse();
+                }
+
+                line
+            })
+            .collect::<Vec<_>>();
+        lines.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+        lines
+    }
+
+    /// Slope rising 10 m per sample towards the east.
+    const SLOPE: [f32; 5] = [1.0, 11.0, 21.0, 31.0, 41.0];
+
+    #[test]
+    fn intervals() {
+        let heightmap = heightmap(&[&SLOPE, &SLOPE, &SLOPE]);
+
+        // A straight line running north for each multiple of the interval
+        assert_eq!(
+            lines(&heightmap, 10.0),
+            [0.9, 1.9, 2.9, 3.9]
+                .map(|x| vec![(x, 0.0), (x, 1.0), (x, 2.0)])
+                .to_vec()
+        );
+        assert_eq!(
+            lines(&heightmap, 20.0),
+            [1.9, 3.9]
+                .map(|x| vec![(x, 0.0), (x, 1.0), (x, 2.0)])
+                .to_vec()
+        );
+
+        assert!(lines(&heightmap, 0.0).is_empty());
+        assert!(lines(&heightmap, 50.0).is_empty());
+    }
+
+    #[test]
+    fn index_contours() {
+        let heightmap = heightmap(&[&SLOPE, &SLOPE]);
+        let paths = contour_paths(&heightmap, 2.0);
+
+        // Every 5th contour (every 10 m) is wider
+        let widths = paths
+            .iter()
+            .map(|path| path.get_stroke().unwrap().width())
+            .collect::<Vec<_>>();
+
+        assert_eq!(widths.len(), 20);
+        assert_eq!(widths.iter().filter(|&&width| width > 1.0).count(), 4);
+    }
+
+    #[test]
+    fn crosses_between_higher_and_lower_samples() {
+        // Rising to the west rather than the east, so each contour is on the other side of the
+        // sample it's closest in height to
+        let west = SLOPE.iter().rev().copied().collect::<Vec<_>>();
+        let heightmap = heightmap(&[&west, &west]);
+
+        assert_eq!(
+            lines(&heightmap, 10.0),
+            [0.1, 1.1, 2.1, 3.1]
+                .map(|x| vec![(x, 0.0), (x, 1.0)])
+                .to_vec()
+        );
+    }
+
+    #[test]
+    fn surrounds_hills() {
+        let heightmap = heightmap(&[&[0.0, 0.0, 0.0], &[0.0, 10.0, 0.0], &[0.0, 0.0, 0.0]]);
+        let lines = lines(&heightmap, 5.0);
+
+        // Closed ring, halfway between the top of the hill and the samples around it (and no
+        // contour at the very top)
+        assert_eq!(lines.len(), 1);
+        let ring = &lines[0];
+
+        assert_eq!(ring.len(), 5);
+        assert_eq!(ring.first(), ring.last());
+        for point in [(0.5, 1.0), (1.0, 0.5), (1.5, 1.0), (1.0, 1.5)] {
+            assert!(ring.contains(&point), "{point:?} should be on the ring");
+        }
+    }
+
+    #[test]
+    fn saddles() {
+        // High south west and north east corners, with the middle of the cell (the average of the
+        // corners) above the contour, so the high corners are joined and the low ones cut off
+        let joined = heightmap(&[&[10.0, 0.0], &[0.0, 10.0]]);
+
+        assert_eq!(
+            lines(&joined, 5.0),
+            vec![vec![(0.0, 0.5), (0.5, 1.0)], vec![(0.5, 0.0), (1.0, 0.5)],]
+        );
+
+        // Middle below the contour, so the high corners are cut off instead
+        let separate = heightmap(&[&[10.0, 0.0], &[0.0, 8.0]]);
+
+        assert_eq!(
+            lines(&separate, 5.0),
+            vec![
+                vec![(0.0, 0.5), (0.5, 0.0)],
+                vec![(0.625, 1.0), (1.0, 0.625)],
+            ]
+        );
+    }
+}
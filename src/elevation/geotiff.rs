@@ -0,0 +1,109 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use tiff::{
+    decoder::{Decoder, DecodingResult},
+    tags::Tag,
+};
+
+use super::{ElevationError, Grid};
+
+/// GeoTIFF keys describing how the image is positioned.
+// http://docs.opengeospatial.org/is/19-008r4/19-008r4.html#_summary_of_geokey_ids_and_names
+const MODEL_TYPE_KEY: u16 = 1024;
+const RASTER_TYPE_KEY: u16 = 1025;
+
+/// Model type of images in longitude and latitude.
+const MODEL_TYPE_GEOGRAPHIC: u16 = 2;
+
+/// Raster type of images where each pixel is a point, rather than an area.
+const RASTER_TYPE_POINT: u16 = 2;
+
+/// Reads the first band of a GeoTIFF in longitude and latitude (such as the SRTM and Copernicus
+/// elevation models).
+pub(super) fn read_geotiff(path: &Path) -> Result<Grid, ElevationError> {
+    let file = File::open(path).map_err(ElevationError::IoError)?;
+    let mut decoder = Decoder::new(BufReader::new(file)).map_err(ElevationError::TiffError)?;
+
+    // Keys are stored as a header of four values followed by four values for each key, with the
+    // value itself last when it's stored inline
+    let keys = decoder
+        .find_tag_unsigned_vec::<u16>(Tag::GeoKeyDirectoryTag)
+        .map_err(ElevationError::TiffError)?
+        .unwrap_or_default();
+    let key = |id| {
+        keys.chunks_exact(4)
+            .skip(1)
+            .find(|key| key[0] == id && key[1] == 0)
+            .map(|key| key[3])
+    };
+
+    if key(MODEL_TYPE_KEY).is_some_and(|model_type| model_type != MODEL_TYPE_GEOGRAPHIC) {
+        return Err(ElevationError::UnsupportedProjection(path.to_path_buf()));
+    }
+
+    let missing_georeference = || ElevationError::MissingGeoreference(path.to_path_buf());
+    let scale = decoder
+        .get_tag_f64_vec(Tag::ModelPixelScaleTag)
+        .map_err(|_| missing_georeference())?;
+    let tie_point = decoder
+        .get_tag_f64_vec(Tag::ModelTiepointTag)
+        .map_err(|_| missing_georeference())?;
+    if scale.len() < 2 || tie_point.len() < 6 {
+        return Err(missing_georeference());
+    }
+
+    let no_data = decoder
+        .get_tag_ascii_string(Tag::GdalNodata)
+        .ok()
+        .and_then(|value| value.trim_matches(char::from(0)).trim().parse::<f32>().ok());
+
+    let (width, height) = decoder.dimensions().map_err(ElevationError::TiffError)?;
+    let (width, height) = (width as usize, height as usize);
+    let pixels = width * height;
+
+    let image = decoder.read_image().map_err(ElevationError::TiffError)?;
+    let values: Vec<f32> = match image {
+        DecodingResult::U8(values) => values.into_iter().map(f32::from).collect(),
+        DecodingResult::U16(values) => values.into_iter().map(f32::from).collect(),
+        DecodingResult::U32(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::U64(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I8(values) => values.into_iter().map(f32::from).collect(),
+        DecodingResult::I16(values) => values.into_iter().map(f32::from).collect(),
+        DecodingResult::I32(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::I64(values) => values.into_iter().map(|v| v as f32).collect(),
+        DecodingResult::F32(values) => values,
+        DecodingResult::F64(values) => values.into_iter().map(|v| v as f32).collect(),
+    };
+
+    // Only the first band is used if there are several, with the bands interleaved
+    let bands = values.len() / pixels.max(1);
+    if bands == 0 || width < 2 || height < 2 {
+        return Err(ElevationError::InvalidData(path.to_path_buf()));
+    }
+
+    let samples = values
+        .into_iter()
+        .step_by(bands)
+        .map(|value| match no_data {
+            Some(no_data) if value == no_data => f32::NAN,
+            _ => value,
+        })
+        .collect();
+
+    // The tie point is the corner of the first pixel, unless each pixel is a point
+    let half_pixel = if key(RASTER_TYPE_KEY) == Some(RASTER_TYPE_POINT) {
+        0.0
+    } else {
+        0.5
+    };
+    let (column, row, lon, lat) = (tie_point[0], tie_point[1], tie_point[3], tie_point[4]);
+
+    Ok(Grid {
+        west: lon + (half_pixel - column) * scale[0],
+        north: lat - (half_pixel - row) * scale[1],
+        step: (scale[0], scale[1]),
+        width,
+        height,
+        samples,
+    })
+}
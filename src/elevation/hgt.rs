@@ -0,0 +1,59 @@
+use std::{fs, path::Path};
+
+use super::{ElevationError, Grid};
+use crate::export::LonLat;
+
+/// Value of samples without any data, such as over water or in deep valleys.
+const VOID: i16 = -32768;
+
+/// Longitude and latitude of the south west corner of a tile, from its name (eg `N37W122.hgt`).
+// https://www.usgs.gov/centers/eros/science/usgs-eros-archive-digital-elevation-shuttle-radar-topography-mission-srtm-1
+pub(super) fn tile_origin(path: &Path) -> Option<LonLat> {
+    let name = path.file_stem()?.to_str()?.to_uppercase();
+    let (lat, lon) = name.split_at(name.find(['E', 'W'])?);
+
+    let degrees = |value: &str, positive, negative| {
+        let hemisphere = value.get(..1)?;
+        let degrees = value.get(1..)?.parse::<f64>().ok()?;
+
+        if hemisphere == positive {
+            Some(degrees)
+        } else if hemisphere == negative {
+            Some(-degrees)
+        } else {
+            None
+        }
+    };
+
+    Some((degrees(lon, "E", "W")?, degrees(lat, "N", "S")?))
+}
+
+/// Reads a tile of big endian 16 bit samples, which are either 1 or 3 arc seconds apart.
+pub(super) fn read_hgt(path: &Path, (lon, lat): LonLat) -> Result<Grid, ElevationError> {
+    let data = fs::read(path).map_err(ElevationError::IoError)?;
+
+    // Tiles are square, with the samples along each edge shared with the neighbouring tile
+    let size = ((data.len() / 2) as f64).sqrt() as usize;
+    if size < 2 || size * size * 2 != data.len() {
+        return Err(ElevationError::InvalidData(path.to_path_buf()));
+    }
+
+    let samples = data
+        .chunks_exact(2)
+        .map(|bytes| match i16::from_be_bytes([bytes[0], bytes[1]]) {
+            VOID => f32::NAN,
+            elevation => elevation as f32,
+        })
+        .collect();
+
+    let step = 1.0 / (size - 1) as f64;
+
+    Ok(Grid {
+        west: lon,
+        north: lat + 1.0,
+        step: (step, step),
+        width: size,
+        height: size,
+        samples,
+    })
+}
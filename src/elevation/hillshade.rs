@@ -0,0 +1,79 @@
+use glam::{UVec2, Vec3, Vec4};
+use renderer::render_steps::raster::Raster;
+
+use super::Heightmap;
+
+/// Direction (in degrees, clockwise from north) that the light comes from. Lighting from the north
+/// west is the convention, as otherwise valleys can look like ridges.
+const LIGHT_AZIMUTH: f32 = 315.0;
+
+/// Angle (in degrees) of the light above the horizon.
+const LIGHT_ALTITUDE: f32 = 45.0;
+
+/// How much steeper slopes are made to look, so that gentle terrain is still visible.
+const VERTICAL_EXAGGERATION: f32 = 2.0;
+
+/// Brightness of slopes facing directly away from the light, keeping the map over them readable.
+const SHADOW_BRIGHTNESS: f32 = 0.55;
+
+/// Shades the terrain as if lit from the north west, darkening slopes facing away from the light
+/// with translucent black so that whatever is underneath (such as raster tiles) still shows
+/// through. Flat ground, slopes facing the light, and areas without elevation data are left clear.
+pub(crate) fn hillshade(heightmap: &Heightmap) -> Raster {
+    let (azimuth, altitude) = (LIGHT_AZIMUTH.to_radians(), LIGHT_ALTITUDE.to_radians());
+    let light = Vec3::new(
+        altitude.cos() * azimuth.sin(),
+        altitude.cos() * azimuth.cos(),
+        altitude.sin(),
+    );
+
+    // Rasters start from the top left, whilst the heightmap starts from the south
+    let pixels = (0..heightmap.height as isize)
+        .rev()
+        .flat_map(|row| (0..heightmap.width as isize).map(move |column| (column, row)))
+        .map(|(column, row)| {
+            let sample = |dx, dy| heightmap.sample(column + dx, row + dy);
+
+            if sample(0, 0).is_nan() {
+                return Vec4::ZERO;
+            }
+
+            // Slope towards the east and north, from the neighbouring samples
+            let slope = |a: f32, b: f32| {
+                let slope = (b - a) * VERTICAL_EXAGGERATION / (2.0 * heightmap.spacing_metres);
+
+                if slope.is_nan() {
+                    0.0
+                } else {
+                    slope
+                }
+            };
+            let normal = Vec3::new(
+                -slope(sample(-1, 0), sample(1, 0)),
+                -slope(sample(0, -1), sample(0, 1)),
+                1.0,
+            )
+            .normalize();
+
+            // Relative to the brightness of flat ground
+            let brightness = (normal.dot(light).max(0.0) / light.z).min(1.0);
+
+            Vec4::new(
+                0.0,
+                0.0,
+                0.0,
+                (1.0 - SHADOW_BRIGHTNESS) * (1.0 - brightness),
+            )
+        })
+        .collect();
+
+    let top_left = heightmap.position(-0.5, heightmap.height as f32 - 0.5);
+    let bottom_right = heightmap.position(heightmap.width as f32 - 0.5, -0.5);
+
+    Raster::from_rgba(
+        (&top_left).into(),
+        (&bottom_right).into(),
+        UVec2::new(heightmap.width as u32, heightmap.height as u32),
+        pixels,
+    )
+}
@@ -0,0 +1,255 @@
+mod contours;
+mod geotiff;
+mod hgt;
+mod hillshade;
+
+use std::{error::Error, fmt::Display, fs, path::PathBuf};
+
+use crate::{export::LonLat, map_data::MapData, osm::Node, Point};
+pub(crate) use contours::contour_paths;
+pub(crate) use hillshade::hillshade;
+
+/// Number of samples along each side of a heightmap covering the map.
+const HEIGHTMAP_RESOLUTION: usize = 512;
+
+#[derive(Debug)]
+pub(crate) enum ElevationError {
+    IoError(std::io::Error),
+    TiffError(tiff::TiffError),
+    InvalidData(PathBuf),
+    MissingGeoreference(PathBuf),
+    UnsupportedProjection(PathBuf),
+}
+impl Display for ElevationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "Problem reading elevation data: {e}"),
+            Self::TiffError(e) => write!(f, "Problem reading GeoTIFF: {e}"),
+            Self::InvalidData(path) => write!(
+                f,
+                "Problem reading elevation data: {} isn't a valid elevation model",
+                path.display()
+            ),
+            Self::MissingGeoreference(path) => write!(
+                f,
+                "Problem reading GeoTIFF: {} doesn't say where it is on the map",
+                path.display()
+            ),
+            Self::UnsupportedProjection(path) => write!(
+                f,
+                "Problem reading GeoTIFF: {} must use longitude and latitude coordinates",
+                path.display()
+            ),
+        }
+    }
+}
+impl Error for ElevationError {}
+
+/// Regular grid of elevation samples (in metres), in longitude and latitude. Rows go from north to
+/// south, and voids in the data are `NaN`.
+struct Grid {
+    /// Position of the centre of the north west sample
+    west: f64,
+    north: f64,
+
+    /// Degrees of longitude and latitude between neighbouring samples
+    step: (f64, f64),
+
+    width: usize,
+    height: usize,
+    samples: Vec<f32>,
+}
+
+impl Grid {
+    /// Longitude and latitude of the south west and north east samples.
+    fn bounds(&self) -> (LonLat, LonLat) {
+        (
+            (
+                self.west,
+                self.north - (self.height - 1) as f64 * self.step.1,
+            ),
+            (
+                self.west + (self.width - 1) as f64 * self.step.0,
+                self.north,
+            ),
+        )
+    }
+
+    /// Elevation interpolated between the four nearest samples, if the position is covered.
+    fn at(&self, lon: f64, lat: f64) -> Option<f32> {
+        let x = (lon - self.west) / self.step.0;
+        let y = (self.north - lat) / self.step.1;
+
+        if x < 0.0 || y < 0.0 || x > (self.width - 1) as f64 || y > (self.height - 1) as f64 {
+            return None;
+        }
+
+        // Clamp so that positions on the east and south edges use the last column and row
+        let (column, row) = (
+            (x as usize).min(self.width - 2),
+            (y as usize).min(self.height - 2),
+        );
+        let (tx, ty) = ((x - column as f64) as f32, (y - row as f64) as f32);
+
+        // Samples that don't contribute are skipped, so that voids only affect their surroundings
+        [
+            (column, row, (1.0 - tx) * (1.0 - ty)),
+            (column + 1, row, tx * (1.0 - ty)),
+            (column, row + 1, (1.0 - tx) * ty),
+            (column + 1, row + 1, tx * ty),
+        ]
+        .into_iter()
+        .filter(|&(_, _, weight)| weight > 0.0)
+        .map(|(column, row, weight)| self.samples[row * self.width + column] * weight)
+        .try_fold(0.0, |elevation, sample| {
+            (!sample.is_nan()).then_some(elevation + sample)
+        })
+    }
+}
+
+/// Elevation data from SRTM (`.hgt`) tiles and single band GeoTIFFs, covering (at least part of)
+/// the map.
+pub(crate) struct Elevation {
+    grids: Vec<Grid>,
+}
+
+impl Elevation {
+    /// Loads the files (or all the files in the directories) that overlap the area between two
+    /// corners, skipping any that are outside of it.
+    pub fn load(
+        paths: &[PathBuf],
+        south_west: LonLat,
+        north_east: LonLat,
+    ) -> Result<Elevation, ElevationError> {
+        let overlaps = |(west, south): LonLat, (east, north): LonLat| {
+            west <= north_east.0
+                && east >= south_west.0
+                && south <= north_east.1
+                && north >= south_west.1
+        };
+
+        let mut grids = Vec::new();
+
+        for path in files(paths)? {
+            let extension = path
+                .extension()
+                .map(|extension| extension.to_string_lossy().to_lowercase());
+
+            let grid = match extension.as_deref() {
+                Some("hgt") => {
+                    // The tile's position is in its name, so tiles elsewhere are never read
+                    let (lon, lat) =
+                        hgt::tile_origin(&path).ok_or(ElevationError::InvalidData(path.clone()))?;
+                    if !overlaps((lon, lat), (lon + 1.0, lat + 1.0)) {
+                        continue;
+                    }
+
+                    hgt::read_hgt(&path, (lon, lat))?
+                }
+                Some("tif" | "tiff") => geotiff::read_geotiff(&path)?,
+                _ => continue,
+            };
+
+            let (grid_south_west, grid_north_east) = grid.bounds();
+            if overlaps(grid_south_west, grid_north_east) {
+                grids.push(grid);
+            }
+        }
+
+        Ok(Elevation { grids })
+    }
+
+    /// Elevation (in metres) at a position, if there's data for it.
+    pub fn at(&self, lon: f64, lat: f64) -> Option<f32> {
+        self.grids.iter().find_map(|grid| grid.at(lon, lat))
+    }
+}
+
+/// Expands directories into the files inside them.
+fn files(paths: &[PathBuf]) -> Result<Vec<PathBuf>, ElevationError> {
+    let mut files = Vec::new();
+
+    for path in paths {
+        if path.is_dir() {
+            let mut entries = fs::read_dir(path)
+                .and_then(|entries| {
+                    entries
+                        .map(|entry| entry.map(|entry| entry.path()))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .map_err(ElevationError::IoError)?;
+
+            // Sorted so that overlapping files always take precedence in the same order
+            entries.sort();
+            files.extend(entries.into_iter().filter(|path| path.is_file()));
+        } else {
+            files.push(path.clone());
+        }
+    }
+
+    Ok(files)
+}
+
+/// Elevations sampled over the whole map on a regular grid in world space, with rows going from
+/// south to north.
+pub(crate) struct Heightmap {
+    /// World position of the south west sample
+    origin: Point,
+
+    /// Distance between neighbouring samples, in world units and metres
+    spacing: f32,
+    spacing_metres: f32,
+
+    width: usize,
+    height: usize,
+    samples: Vec<f32>,
+}
+
+impl Heightmap {
+    pub fn new(map_data: &MapData, elevation: &Elevation) -> Heightmap {
+        let bounding = &map_data.bounding;
+        let (width, height) = (HEIGHTMAP_RESOLUTION, HEIGHTMAP_RESOLUTION);
+        let spacing = bounding.dx().max(bounding.dy()) / (HEIGHTMAP_RESOLUTION - 1) as f64;
+
+        let samples = (0..height)
+            .flat_map(|row| (0..width).map(move |column| (column, row)))
+            .map(|(column, row)| {
+                let (lon, lat) = Node::new(
+                    bounding.min_x + column as f64 * spacing,
+                    bounding.min_y + row as f64 * spacing,
+                )
+                .lon_lat();
+
+                elevation.at(lon, lat).unwrap_or(f32::NAN)
+            })
+            .collect();
+
+        // Distances in world space are stretched away from the equator by the projection
+        let (_, lat) = Node::new(bounding.center_x, bounding.center_y).lon_lat();
+
+        Heightmap {
+            origin: map_data.translate(Point::new(bounding.min_x as f32, bounding.min_y as f32)),
+            spacing: spacing as f32,
+            spacing_metres: (spacing * lat.to_radians().cos()) as f32,
+            width,
+            height,
+            samples,
+        }
+    }
+
+    /// Elevation of a sample, clamped to the edges of the heightmap.
+    fn sample(&self, column: isize, row: isize) -> f32 {
+        let column = column.clamp(0, self.width as isize - 1) as usize;
+        let row = row.clamp(0, self.height as isize - 1) as usize;
+
+        self.samples[row * self.width + column]
+    }
+
+    /// World position of a sample (which may be between samples).
+    fn position(&self, column: f32, row: f32) -> Point {
+        Point::new(
+            self.origin.x + column * self.spacing,
+            self.origin.y + row * self.spacing,
+        )
+    }
+}
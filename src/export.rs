@@ -0,0 +1,80 @@
+use std::{fs, io, path::Path};
+
+use serde_json::{json, Value};
+
+/// Longitude and latitude (in degrees), in that order as in GeoJSON.
+pub type LonLat = (f64, f64);
+
+pub fn geojson_point(position: LonLat, properties: Value) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "Point",
+            "coordinates": [position.0, position.1],
+        },
+        "properties": properties,
+    })
+}
+
+pub fn geojson_line(points: &[LonLat], properties: Value) -> Value {
+    json!({
+        "type": "Feature",
+        "geometry": {
+            "type": "LineString",
+            "coordinates": points.iter().map(|p| [p.0, p.1]).collect::<Vec<_>>(),
+        },
+        "properties": properties,
+    })
+}
+
+pub fn write_geojson(path: impl AsRef<Path>, features: Vec<Value>) -> io::Result<()> {
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+
+    fs::write(path, serde_json::to_string_pretty(&collection)?)
+}
+
+/// Writes named waypoints, and a track through the given points.
+// https://www.topografix.com/GPX/1/1/
+pub fn write_gpx(
+    path: impl AsRef<Path>,
+    waypoints: &[(String, LonLat)],
+    track: &[LonLat],
+) -> io::Result<()> {
+    let mut gpx = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+        "\n",
+        r#"<gpx version="1.1" creator="map_renderer" xmlns="http://www.topografix.com/GPX/1/1">"#,
+        "\n"
+    ));
+
+    for (name, (lon, lat)) in waypoints {
+        gpx += &format!(
+            "  <wpt lat=\"{lat:.7}\" lon=\"{lon:.7}\"><name>{}</name></wpt>\n",
+            escape_xml(name)
+        );
+    }
+
+    gpx += "  <trk>\n    <trkseg>\n";
+    for (lon, lat) in track {
+        gpx += &format!("      <trkpt lat=\"{lat:.7}\" lon=\"{lon:.7}\"/>\n");
+    }
+    gpx += "    </trkseg>\n  </trk>\n</gpx>\n";
+
+    fs::write(path, gpx)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '&' => "&amp;".to_string(),
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&apos;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,671 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    map_data::MapData,
+    osm::{Node, Tags, Way},
+    Point,
+};
+
+/// Most results returned for a search.
+const MAX_RESULTS: usize = 5;
+
+/// Results with the same name closer than this (in metres) are treated as the same place, such as
+/// a shop mapped as both a node and a building.
+const DUPLICATE_DISTANCE: f32 = 1000.0;
+
+/// Furthest distance (in metres) that a clicked point can be from an address for it to be found.
+const MAX_REVERSE_DISTANCE: f32 = 250.0;
+
+/// How much a word in the query contributes to the score of a match, depending on how it matched.
+const EXACT_SCORE: f32 = 1.0;
+const PREFIX_SCORE: f32 = 0.8;
+const FUZZY_SCORE: f32 = 0.6;
+
+/// What kind of thing a search result is.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum PlaceKind {
+    /// Named place (`place=*`), such as a city or suburb
+    Settlement,
+    Street,
+    /// Building or entrance with a house number, but no name
+    Address,
+    /// Anything else with a name, such as a shop or park
+    Feature,
+}
+
+/// Something on the map that can be searched for.
+pub(crate) struct Place {
+    pub name: String,
+
+    /// Extra information shown alongside the name, such as the address of a shop
+    pub detail: Option<String>,
+
+    pub kind: PlaceKind,
+
+    /// Position in world space
+    pub position: Point,
+
+    /// Address (house number, street and postcode), for reverse geocoding
+    pub address: Option<String>,
+
+    /// Number of words in the name and detail, so that closer matches can be preferred
+    words: usize,
+
+    /// How well known the place is, from 0 to 1, to order results that match equally well
+    importance: f32,
+}
+
+impl Place {
+    /// Name along with any detail, as shown in search results.
+    pub fn description(&self) -> String {
+        match self.detail.as_ref() {
+            Some(detail) => format!("{}, {detail}", self.name),
+            None => self.name.clone(),
+        }
+    }
+}
+
+/// Address made up of the `addr:*` tags, if there's at least a house number and street.
+// https://wiki.openstreetmap.org/wiki/Key:addr:*
+fn address(tags: &Tags) -> Option<String> {
+    let number = tags.get("addr:housenumber")?;
+    let street = tags.get("addr:street")?;
+
+    Some(match tags.get("addr:postcode") {
+        Some(postcode) => format!("{number} {street}, {postcode}"),
+        None => format!("{number} {street}"),
+    })
+}
+
+/// Average of a set of positions.
+fn centre(points: &[Point]) -> Point {
+    let sum = points.iter().fold(Point::new(0.0, 0.0), |sum, p| {
+        Point::new(sum.x + p.x, sum.y + p.y)
+    });
+
+    Point::new(sum.x / points.len() as f32, sum.y / points.len() as f32)
+}
+
+/// Splits text into lowercase words, without accents, so that they can be compared.
+fn normalise(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            word.chars()
+                .flat_map(char::to_lowercase)
+                .map(fold)
+                .collect()
+        })
+        .collect()
+}
+
+/// Removes the accent from a (lowercase) letter, so that searches don't need them typed.
+fn fold(c: char) -> char {
+    match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' => 'a',
+        'ç' | 'č' => 'c',
+        'è' | 'é' | 'ê' | 'ë' | 'ē' => 'e',
+        'ì' | 'í' | 'î' | 'ï' | 'ī' => 'i',
+        'ñ' => 'n',
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' => 'o',
+        'š' => 's',
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' => 'u',
+        'ý' | 'ÿ' => 'y',
+        'ž' => 'z',
+        c => c,
+    }
+}
+
+/// Number of single character changes (insertions, deletions, substitutions and swaps of
+/// neighbouring characters) needed to turn one word into another.
+// https://en.wikipedia.org/wiki/Damerau%E2%80%93Levenshtein_distance
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut distances = vec![vec![0; b.len() + 1]; a.len() + 1];
+
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, distance) in distances[0].iter_mut().enumerate() {
+        *distance = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+
+            distances[i][j] = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distances[i][j] = distances[i][j].min(distances[i - 2][j - 2] + 1);
+            }
+        }
+    }
+
+    distances[a.len()][b.len()]
+}
+
+/// How well a word from a query matches a word in the index, if at all. Longer words are allowed
+/// more typos.
+fn match_word(query: &[char], word: &[char]) -> Option<f32> {
+    if query == word {
+        return Some(EXACT_SCORE);
+    }
+
+    if word.starts_with(query) {
+        return Some(PREFIX_SCORE);
+    }
+
+    let max_edits = match query.len() {
+        0..=3 => return None,
+        4..=7 => 1,
+        _ => 2,
+    };
+
+    if word.len().abs_diff(query.len()) > max_edits {
+        return None;
+    }
+
+    let edits = edit_distance(query, word);
+
+    (edits <= max_edits).then(|| FUZZY_SCORE / edits as f32)
+}
+
+/// Offline search of the places on the map by name or address (geocoding), and of the addresses
+/// near a point (reverse geocoding).
+pub(crate) struct Geocoder {
+    places: Vec<Place>,
+
+    /// Places containing each word
+    index: HashMap<Vec<char>, Vec<usize>>,
+
+    /// Metres in each world unit, at the middle of the map
+    scale: f32,
+}
+
+impl Geocoder {
+    pub fn new(map_data: &MapData) -> Self {
+        let mut places = Vec::new();
+
+        for node in map_data.osm_data.nodes.values() {
+            places.extend(Self::place(&node.tags, map_data.node_point(node)));
+        }
+
+        // Streets are usually split into many ways, so ways with the same name that meet are
+        // joined into a single street
+        let mut streets = HashMap::<&String, Vec<(HashSet<i64>, Vec<&Way>)>>::new();
+
+        for way in map_data.osm_data.ways.values() {
+            match way.tags.get("name") {
+                Some(name) if way.tags.contains("highway") => {
+                    let streets = streets.entry(name).or_default();
+
+                    let mut street = (way.nodes.iter().copied().collect::<HashSet<_>>(), vec![way]);
+                    while let Some(i) = streets
+                        .iter()
+                        .position(|(nodes, _)| !nodes.is_disjoint(&street.0))
+                    {
+                        let (nodes, ways) = streets.swap_remove(i);
+                        street.0.extend(nodes);
+                        street.1.extend(ways);
+                    }
+
+                    streets.push(street);
+                }
+                _ => {
+                    let points = map_data.way_points(way);
+
+                    if !points.is_empty() {
+                        places.extend(Self::place(&way.tags, centre(&points)));
+                    }
+                }
+            }
+        }
+
+        for (_, ways) in streets.into_values().flatten() {
+            let points = ways
+                .iter()
+                .flat_map(|way| map_data.way_points(way))
+                .collect::<Vec<_>>();
+
+            // Found at the point on the street closest to its centre, as the centre itself may
+            // not be on the street if it curves
+            let centre = centre(&points);
+            let position = points.into_iter().min_by(|a, b| {
+                let distance = |p: &Point| (p.x - centre.x).hypot(p.y - centre.y);
+
+                distance(a).total_cmp(&distance(b))
+            });
+
+            if let Some(position) = position {
+                places.extend(Self::place(&ways[0].tags, position));
+            }
+        }
+
+        // Streets are told apart by the place (suburb, town, etc) that they're closest to
+        let settlements = places
+            .iter()
+            .filter(|place| place.kind == PlaceKind::Settlement)
+            .map(|place| (place.name.clone(), place.position))
+            .collect::<Vec<_>>();
+
+        for street in places
+            .iter_mut()
+            .filter(|place| place.kind == PlaceKind::Street)
+        {
+            let distance = |p: &Point| (p.x - street.position.x).hypot(p.y - street.position.y);
+
+            street.detail = settlements
+                .iter()
+                .min_by(|(_, a), (_, b)| distance(a).total_cmp(&distance(b)))
+                .map(|(name, _)| name.clone());
+        }
+
+        let mut index = HashMap::<Vec<char>, Vec<usize>>::new();
+        for (i, place) in places.iter_mut().enumerate() {
+            let mut words = normalise(&place.description());
+            place.words = words.len();
+
+            words.sort();
+            words.dedup();
+            for word in words {
+                index.entry(word.chars().collect()).or_default().push(i);
+            }
+        }
+
+        let (_, lat) = Node::new(map_data.bounding.center_x, map_data.bounding.center_y).lon_lat();
+
+        Self {
+            places,
+            index,
+            scale: lat.to_radians().cos() as f32,
+        }
+    }
+
+    /// Place for an element, if it has a name or address that can be searched for.
+    fn place(tags: &Tags, position: Point) -> Option<Place> {
+        let address = address(tags);
+
+        let (name, detail, kind, importance) = match (tags.get("name"), address.as_ref()) {
+            (Some(name), _) if tags.contains("place") => {
+                let place = tags.get("place").cloned().unwrap_or_default();
+                let importance = match place.as_str() {
+                    "city" => 1.0,
+                    "town" => 0.9,
+                    "suburb" => 0.8,
+                    "village" => 0.7,
+                    "neighbourhood" | "quarter" => 0.6,
+                    _ => 0.5,
+                };
+
+                (name.clone(), Some(place), PlaceKind::Settlement, importance)
+            }
+            (Some(name), _) if tags.contains("highway") => {
+                (name.clone(), None, PlaceKind::Street, 0.4)
+            }
+            (Some(name), address) => (name.clone(), address.cloned(), PlaceKind::Feature, 0.3),
+            (None, Some(address)) => (address.clone(), None, PlaceKind::Address, 0.2),
+            (None, None) => return None,
+        };
+
+        Some(Place {
+            name,
+            detail,
+            kind,
+            position,
+            address,
+            words: 0,
+            importance,
+        })
+    }
+
+    /// Distance (in metres) between two positions in world space.
+    fn distance(&self, a: &Point, b: &Point) -> f32 {
+        (a.x - b.x).hypot(a.y - b.y) * self.scale
+    }
+
+    /// Places matching a query, best first. Every word in the query has to match, either exactly,
+    /// as the start of a word (so that results appear whilst typing), or with a typo or two.
+    pub fn search(&self, query: &str) -> Vec<&Place> {
+        let query = normalise(query);
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        // Total score of each place that every word so far has matched
+        let mut scores = HashMap::<usize, f32>::new();
+
+        for (i, query_word) in query.iter().enumerate() {
+            let query_word = query_word.chars().collect::<Vec<_>>();
+
+            let mut word_scores = HashMap::<usize, f32>::new();
+            for (word, places) in self.index.iter() {
+                let Some(score) = match_word(&query_word, word) else {
+                    continue;
+                };
+
+                for &place in places {
+                    let best = word_scores.entry(place).or_default();
+                    *best = best.max(score);
+                }
+            }
+
+            scores = if i == 0 {
+                word_scores
+            } else {
+                word_scores
+                    .into_iter()
+                    .filter_map(|(place, score)| Some((place, scores.get(&place)? + score)))
+                    .collect()
+            };
+        }
+
+        // Prefer places where more of the name was searched for, then more important places
+        let mut results = scores
+            .into_iter()
+            .map(|(i, score)| {
+                let place = &self.places[i];
+                let coverage = query.len().min(place.words) as f32 / place.words as f32;
+
+                (
+                    place,
+                    score / query.len() as f32 + 0.2 * coverage + 0.1 * place.importance,
+                )
+            })
+            .collect::<Vec<_>>();
+        results.sort_by(|(a, a_score), (b, b_score)| {
+            b_score.total_cmp(a_score).then_with(|| a.name.cmp(&b.name))
+        });
+
+        let mut found: Vec<&Place> = Vec::new();
+        for (place, _) in results {
+            let duplicate = found.iter().any(|other| {
+                other.description() == place.description()
+                    && self.distance(&other.position, &place.position) < DUPLICATE_DISTANCE
+            });
+
+            if !duplicate {
+                found.push(place);
+            }
+
+            if found.len() == MAX_RESULTS {
+                break;
+            }
+        }
+
+        found
+    }
+
+    /// Closest place with an address to a position in world space, along with its distance in
+    /// metres.
+    pub fn reverse(&self, position: &Point) -> Option<(&Place, f32)> {
+        self.places
+            .iter()
+            .filter(|place| place.address.is_some())
+            .map(|place| (place, self.distance(&place.position, position)))
+            .filter(|(_, distance)| *distance <= MAX_REVERSE_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{edit_distance, match_word, normalise, Geocoder, PlaceKind};
+    use crate::{
+        map_data::MapData,
+        osm::{Node, Osm, Tags, Way},
+    };
+
+    type Tag = (&'static str, &'static str);
+
+    /// Two settlements 5 km apart, each with a Main Street, along with a bakery, an address and
+    /// a cafe (mapped as both a node and a building) on the one in Springfield. Positions are
+    /// projected, so near the equator they're in metres.
+    fn map() -> MapData {
+        let nodes: [(i64, f64, f64, &[Tag]); 16] = [
+            (1, 0.0, 0.0, &[("name", "Springfield"), ("place", "town")]),
+            (
+                2,
+                5000.0,
+                0.0,
+                &[("name", "Shelbyville"), ("place", "village")],
+            ),
+            // Main Street, Springfield, split into two ways
+            (10, 0.0, 0.0, &[]),
+            (11, 100.0, 0.0, &[]),
+            (12, 200.0, 0.0, &[]),
+            // Main Street, Shelbyville
+            (20, 5000.0, 0.0, &[]),
+            (21, 5100.0, 0.0, &[]),
+            (
+                30,
+                50.0,
+                10.0,
+                &[
+                    ("name", "Main Street Bakery"),
+                    ("addr:housenumber", "1"),
+                    ("addr:street", "Main Street"),
+                ],
+            ),
+            (
+                31,
+                100.0,
+                10.0,
+                &[("addr:housenumber", "2"), ("addr:street", "Main Street")],
+            ),
+            (
+                40,
+                150.0,
+                20.0,
+                &[("name", "Corner Cafe"), ("amenity", "cafe")],
+            ),
+            (
+                41,
+                5000.0,
+                50.0,
+                &[("name", "Corner Cafe"), ("amenity", "cafe")],
+            ),
+            // Building around the cafe in Springfield
+            (50, 140.0, 10.0, &[]),
+            (51, 160.0, 10.0, &[]),
+            (52, 160.0, 30.0, &[]),
+            (53, 140.0, 30.0, &[]),
+            (54, 140.0, 10.0, &[]),
+        ];
+
+        let ways: [(i64, &[i64], &[Tag]); 4] = [
+            (
+                1,
+                &[10, 11],
+                &[("name", "Main Street"), ("highway", "residential")],
+            ),
+            (
+                2,
+                &[11, 12],
+                &[("name", "Main Street"), ("highway", "residential")],
+            ),
+            (
+                3,
+                &[20, 21],
+                &[("name", "Main Street"), ("highway", "residential")],
+            ),
+            (
+                4,
+                &[50, 51, 52, 53, 54],
+                &[("name", "Corner Cafe"), ("building", "yes")],
+            ),
+        ];
+
+        MapData::new(Osm {
+            nodes: nodes
+                .into_iter()
+                .map(|(id, x, y, tags)| {
+                    let mut node = Node::new(x, y);
+                    node.tags = Tags::from_iter(tags.iter().copied());
+
+                    (id, node)
+                })
+                .collect(),
+            ways: ways
+                .into_iter()
+                .map(|(id, nodes, tags)| {
+                    let way = Way {
+                        tags: Tags::from_iter(tags.iter().copied()),
+                        nodes: nodes.to_vec(),
+                    };
+
+                    (id, way)
+                })
+                .collect(),
+            relations: HashMap::new(),
+        })
+    }
+
+    fn chars(word: &str) -> Vec<char> {
+        word.chars().collect()
+    }
+
+    #[test]
+    fn normalises_words() {
+        assert_eq!(
+            normalise("Café de l'ÉGLISE, 12-b"),
+            ["cafe", "de", "l", "eglise", "12", "b"]
+        );
+        assert_eq!(normalise("Ørsted Straße"), ["orsted", "straße"]);
+        assert!(normalise(" ,- ").is_empty());
+    }
+
+    #[test]
+    fn edit_distances() {
+        let distance = |a, b| edit_distance(&chars(a), &chars(b));
+
+        assert_eq!(distance("street", "street"), 0);
+        assert_eq!(distance("", "road"), 4);
+        assert_eq!(distance("road", ""), 4);
+        assert_eq!(distance("kitten", "sitting"), 3);
+        // A swap of neighbouring characters is a single change
+        assert_eq!(distance("strete", "street"), 1);
+        assert_eq!(distance("ab", "ba"), 1);
+    }
+
+    #[test]
+    fn matches_words() {
+        let score = |query, word| match_word(&chars(query), &chars(word));
+
+        assert_eq!(score("main", "main"), Some(1.0));
+        assert_eq!(score("ma", "main"), Some(0.8));
+        assert_eq!(score("main", "ma"), None);
+
+        // Words of up to 3 letters have to be typed correctly
+        assert_eq!(score("mai", "man"), None);
+
+        // Words of 4 to 7 letters can have a typo
+        assert_eq!(score("mian", "main"), Some(0.6));
+        assert_eq!(score("maxn", "main"), Some(0.6));
+        assert_eq!(score("mxxn", "main"), None);
+
+        // Longer words can have two
+        assert_eq!(score("hospitla", "hospital"), Some(0.6));
+        assert_eq!(score("hxspitxl", "hospital"), Some(0.3));
+        assert_eq!(score("hxspxtxl", "hospital"), None);
+        assert_eq!(score("hospitalxyz", "hospital"), None);
+    }
+
+    #[test]
+    fn merges_streets() {
+        let geocoder = Geocoder::new(&map());
+
+        let mut streets = geocoder
+            .places
+            .iter()
+            .filter(|place| place.kind == PlaceKind::Street)
+            .map(|place| place.description())
+            .collect::<Vec<_>>();
+        streets.sort();
+
+        assert_eq!(
+            streets,
+            ["Main Street, Shelbyville", "Main Street, Springfield"]
+        );
+    }
+
+    #[test]
+    fn ranks_results() {
+        let geocoder = Geocoder::new(&map());
+        let search = |query| {
+            geocoder
+                .search(query)
+                .into_iter()
+                .map(|place| place.description())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            search("springfield"),
+            ["Springfield, town", "Main Street, Springfield"]
+        );
+
+        // Streets are better known than addresses, which are a closer match than the bakery
+        let kinds = geocoder
+            .search("main")
+            .into_iter()
+            .map(|place| place.kind)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                PlaceKind::Street,
+                PlaceKind::Street,
+                PlaceKind::Address,
+                PlaceKind::Feature
+            ]
+        );
+
+        // Every word has to match
+        assert_eq!(search("main shelby"), ["Main Street, Shelbyville"]);
+        assert!(search("main london").is_empty());
+        assert!(search("").is_empty());
+
+        // Typos and unfinished words
+        assert_eq!(search("bakrey"), ["Main Street Bakery, 1 Main Street"]);
+        assert_eq!(search("2 main st"), ["2 Main Street"]);
+    }
+
+    #[test]
+    fn removes_duplicates() {
+        let geocoder = Geocoder::new(&map());
+        let results = geocoder.search("corner cafe");
+
+        // The node and building in Springfield are the same cafe, but the one in Shelbyville
+        // isn't
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|place| place.description() == "Corner Cafe"));
+    }
+
+    #[test]
+    fn reverse_within_limit() {
+        let map_data = map();
+        let geocoder = Geocoder::new(&map_data);
+        let reverse = |x, y| {
+            geocoder
+                .reverse(&map_data.node_point(&Node::new(x, y)))
+                .map(|(place, distance)| (place.address.clone().unwrap(), distance.round()))
+        };
+
+        assert_eq!(
+            reverse(50.0, 0.0),
+            Some(("1 Main Street".to_string(), 10.0))
+        );
+        assert_eq!(
+            reverse(100.0, 200.0),
+            Some(("2 Main Street".to_string(), 190.0))
+        );
+        assert_eq!(reverse(100.0, 300.0), None);
+        assert_eq!(reverse(5000.0, 0.0), None);
+    }
+}
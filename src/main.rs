@@ -1,20 +1,41 @@
+mod coastline;
+mod elevation;
+mod export;
+mod geocoder;
+mod map_buildings;
 mod map_canvas;
 mod map_data;
+mod map_icons;
+mod map_labels;
+mod map_search;
+mod map_terrain;
+mod map_tiles;
+mod map_traces;
 mod objects;
+mod optimise;
 mod osm;
 mod path_finder;
 mod plugin;
 
-use std::rc::Rc;
+use std::{path::PathBuf, process, rc::Rc};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use elevation::Elevation;
 use glam::Vec3;
+use map_buildings::MapBuildings;
 use map_canvas::MapCanvas;
 use map_data::MapData;
+use map_icons::MapIcons;
+use map_labels::MapLabels;
+use map_search::MapSearch;
+use map_terrain::MapTerrain;
+use map_tiles::MapTiles;
+use map_traces::MapTraces;
 use osm::Osm;
 use osmpbf::ElementReader;
+use path_finder::{hierarchy_path, Analysis, Network, PathFinder, Profile};
 use plugin::Plugin;
-use renderer::{window::Window, Renderer};
+use renderer::{window::Window, Event, EventResponse, Renderer};
 
 #[derive(Parser)]
 struct Args {
@@ -24,6 +45,73 @@ struct Args {
     /// Minimum window size
     #[arg(long, default_value_t = 500)]
     size: usize,
+
+    /// Additional routing profile (TOML file), selectable alongside the built in car, bike and
+    /// foot profiles
+    #[arg(long = "profile")]
+    profiles: Vec<String>,
+
+    /// Build contraction hierarchies for faster routing, saved next to the data file
+    #[arg(long)]
+    contract: bool,
+
+    /// GPS trace (GPX file) to draw, along with the roads it was matched onto
+    #[arg(long = "trace")]
+    traces: Vec<PathBuf>,
+
+    /// Name of the routing profile used to match GPS traces
+    #[arg(long, default_value = "car")]
+    trace_mode: String,
+
+    /// Elevation data (SRTM `.hgt` tiles or GeoTIFFs, or directories of them) for shading the
+    /// terrain and drawing contour lines
+    #[arg(long)]
+    elevation: Vec<PathBuf>,
+
+    /// Distance (in metres) between contour lines
+    #[arg(long, default_value_t = 10.0)]
+    contour_interval: f32,
+
+    /// Raster tiles (an MBTiles file of PNG or JPEG images, such as satellite imagery) to draw
+    /// under the map
+    #[arg(long)]
+    tiles: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+/// Tasks to run on the data instead of opening the map.
+#[derive(Subcommand)]
+enum Command {
+    /// Finds the fastest order to visit stops in, starting from the first one
+    Optimise {
+        /// CSV file of stops, with `name`, `lat` and `lon` columns
+        stops: PathBuf,
+
+        /// File to write the ordered route to (`.geojson` or `.gpx`)
+        #[arg(long, short)]
+        output: PathBuf,
+
+        /// Name of the routing profile to use
+        #[arg(long, default_value = "car")]
+        mode: String,
+
+        /// Return to the first stop at the end
+        #[arg(long)]
+        round_trip: bool,
+    },
+
+    /// Reports problems with how the roads connect, such as roads that can't be reached
+    Analyse {
+        /// GeoJSON file to write the problems to, for fixing in a map editor
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Name of the routing profile whose roads are checked
+        #[arg(long, default_value = "car")]
+        mode: String,
+    },
 }
 
 #[derive(Clone, Copy)]
@@ -45,13 +133,66 @@ impl From<&Point> for Vec3 {
 fn main() -> osmpbf::Result<()> {
     let args = Args::parse();
 
-    // Load plugins
-    let mut plugins: [Box<dyn Plugin<()>>; 1] = [Box::new(MapCanvas::new())];
+    // Load routing profiles, with any custom profiles after the built in ones
+    let profiles: Vec<_> = [Profile::car(), Profile::bike(), Profile::foot()]
+        .into_iter()
+        .chain(args.profiles.iter().map(|path| {
+            Profile::from_file(path)
+                .unwrap_or_else(|e| panic!("profile `{path}` should be valid: {e}"))
+        }))
+        .collect();
 
     // Load map data from disk
     let reader = ElementReader::from_path(&args.pbf_file).expect("input file should exist");
     let osm_data = Osm::from_reader(reader)?;
-    let map_data = Rc::new(MapData::new(osm_data));
+
+    if let Some(command) = args.command {
+        if let Err(e) = run_command(command, &osm_data, profiles, &args.pbf_file, args.contract) {
+            eprintln!("{e}");
+            process::exit(1);
+        }
+
+        return Ok(());
+    }
+
+    let trace_profile = profiles
+        .iter()
+        .find(|profile| profile.name == args.trace_mode)
+        .cloned()
+        .unwrap_or_else(|| {
+            panic!(
+                "there should be a routing profile named `{}`",
+                args.trace_mode
+            )
+        });
+
+    let mut path_finder = PathFinder::new(profiles);
+    if args.contract {
+        path_finder = path_finder.with_contraction(&args.pbf_file);
+    }
+
+    // Load plugins
+    let mut plugins: [Box<dyn Plugin<()>>; 9] = [
+        Box::new(MapTiles::new(args.tiles)),
+        Box::new(MapTerrain::new()),
+        Box::new(MapCanvas::new().with_contour_interval(args.contour_interval)),
+        Box::new(MapBuildings::new()),
+        Box::new(MapTraces::new(args.traces, trace_profile)),
+        Box::new(path_finder),
+        Box::new(MapIcons::new()),
+        Box::new(MapLabels::new()),
+        Box::new(MapSearch::new()),
+    ];
+
+    let mut map_data = MapData::new(osm_data);
+    if !args.elevation.is_empty() {
+        let (south_west, north_east) = map_data.bounding.lon_lat();
+        let elevation = Elevation::load(&args.elevation, south_west, north_east)
+            .unwrap_or_else(|e| panic!("elevation data should be valid: {e}"));
+
+        map_data = map_data.with_elevation(elevation);
+    }
+    let map_data = Rc::new(map_data);
 
     // Initialise window and renderer
     let window = Window::new((args.size, args.size));
@@ -66,8 +207,75 @@ fn main() -> osmpbf::Result<()> {
     }
 
     renderer.run(move |event| {
-        for plugin in plugins.iter_mut() {
-            plugin.handle_event((), event);
+        // Whilst a plugin is taking text input, key presses are only for it
+        let typing = matches!(event, Event::Keyboard(_) | Event::Character(_));
+        let capturing = plugins
+            .iter()
+            .position(|plugin| typing && plugin.captures_keyboard());
+
+        // Render steps of the plugins whose state was changed by the event
+        let changed = plugins
+            .iter_mut()
+            .enumerate()
+            .filter(|&(i, _)| capturing.is_none_or(|capturing| capturing == i))
+            .filter_map(|(_, plugin)| {
+                plugin
+                    .handle_event((), event)
+                    .then(|| plugin.get_render_step())
+            })
+            .collect();
+
+        EventResponse {
+            handled: capturing.is_some(),
+            changed,
+            fly_to: plugins
+                .iter_mut()
+                .find_map(|plugin| plugin.take_camera_target()),
         }
     });
 }
+
+fn run_command(
+    command: Command,
+    osm_data: &Osm,
+    profiles: Vec<Profile>,
+    data_file: &str,
+    contract: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let find_profile = |mode: &str| {
+        profiles
+            .iter()
+            .find(|profile| profile.name == mode)
+            .ok_or_else(|| format!("there is no routing profile named `{mode}`"))
+    };
+
+    match command {
+        Command::Optimise {
+            stops,
+            output,
+            mode,
+            round_trip,
+        } => {
+            let profile = find_profile(&mode)?;
+
+            let mut network = Network::new(osm_data, profile);
+            if contract {
+                println!("Preparing contraction hierarchy for {}", profile.name);
+                network.contract(hierarchy_path(data_file.as_ref(), profile));
+            }
+
+            optimise::run(osm_data, &network, &stops, &output, round_trip)
+        }
+        Command::Analyse { output, mode } => {
+            let network = Network::new(osm_data, find_profile(&mode)?);
+            let analysis = Analysis::new(osm_data, &network);
+            println!("{analysis}");
+
+            if let Some(output) = output {
+                export::write_geojson(output, analysis.features(osm_data, &network))?;
+            }
+
+            Ok(())
+        }
+    }
+}
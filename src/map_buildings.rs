@@ -0,0 +1,52 @@
+use std::{cell::RefCell, rc::Rc};
+
+use renderer::{render_steps::extrusion::ExtrusionProgram, Event, RenderStep};
+
+use crate::{map_data::MapData, objects::Building, osm::Node, plugin::Plugin};
+
+/// Draws buildings as solid shapes standing on the map, using their heights where they're known.
+pub(crate) struct MapBuildings {
+    extrusions: Rc<RefCell<ExtrusionProgram>>,
+}
+
+impl MapBuildings {
+    pub fn new() -> Self {
+        Self {
+            extrusions: Rc::new(RefCell::new(ExtrusionProgram::default())),
+        }
+    }
+}
+
+impl Plugin<()> for MapBuildings {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let mut extrusions = self.extrusions.borrow_mut();
+        extrusions.clear();
+
+        // Distances on the map are stretched away from the equator by the projection, so heights
+        // have to be stretched by the same amount to keep buildings in proportion
+        let (_, lat) = Node::new(map_data.bounding.center_x, map_data.bounding.center_y).lon_lat();
+        let world_scale = 1.0 / lat.to_radians().cos() as f32;
+
+        for way in map_data.osm_data.ways.values() {
+            if !way.tags.contains("building") {
+                continue;
+            }
+
+            let points = map_data.way_points(way);
+            if points.len() < 4 || way.nodes.first() != way.nodes.last() {
+                continue;
+            }
+
+            extrusions
+                .add_extrusion(Building::from_tags(&way.tags).get_extrusion(&points, world_scale));
+        }
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.extrusions) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
+        false
+    }
+}
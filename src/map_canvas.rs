@@ -1,16 +1,23 @@
 use std::{cell::RefCell, rc::Rc};
 
-use glam::Vec3;
-use renderer::{
-    render_steps::canvas::{CanvasProgram, Path},
-    Event, RenderStep,
+use renderer::{render_steps::canvas::CanvasProgram, Event, RenderStep};
+
+use crate::{
+    coastline::coastline_paths,
+    elevation::{contour_paths, Heightmap},
+    map_data::MapData,
+    plugin::Plugin,
 };
 
-use crate::{map_data::MapData, plugin::Plugin, Point};
+/// Default distance (in metres) between contour lines.
+const DEFAULT_CONTOUR_INTERVAL: f32 = 10.0;
 
 pub(crate) struct MapCanvas {
     canvas: Rc<RefCell<CanvasProgram>>,
     map_data: Option<Rc<MapData>>,
+
+    /// Distance (in metres) between contour lines, drawn if there's elevation data
+    contour_interval: f32,
 }
 
 impl MapCanvas {
@@ -18,8 +25,14 @@ impl MapCanvas {
         Self {
             canvas: Rc::new(RefCell::new(CanvasProgram::default())),
             map_data: None,
+            contour_interval: DEFAULT_CONTOUR_INTERVAL,
         }
     }
+
+    pub fn with_contour_interval(mut self, contour_interval: f32) -> Self {
+        self.contour_interval = contour_interval;
+        self
+    }
 }
 
 impl Plugin<()> for MapCanvas {
@@ -29,18 +42,24 @@ impl Plugin<()> for MapCanvas {
         // Update canvas to re-draw all of the map elements
         canvas.clear();
 
+        for path in coastline_paths(&map_data) {
+            canvas.add_object(Box::new(path));
+        }
+
+        if let Some(elevation) = map_data.elevation.as_ref() {
+            let heightmap = Heightmap::new(&map_data, elevation);
+
+            for path in contour_paths(&heightmap, self.contour_interval) {
+                canvas.add_object(Box::new(path));
+            }
+        }
+
         for way in map_data.osm_data.ways.values() {
             if let Some(way_type) = way.to_object() {
-                let points = way
-                    .nodes
-                    .iter()
-                    .filter_map(|node_id| map_data.osm_data.nodes.get(node_id))
-                    .map(|node| Point::new(node.x as f32, node.y as f32))
-                    .map(|node| map_data.translate(node))
-                    .collect::<Vec<_>>();
+                let points = map_data.way_points(way);
 
                 for path in way_type.get_paths(&points) {
-                    canvas.add_object(Box::new(path));
+                    canvas.add_object(Box::new(path.with_layer(way.layer())));
                 }
             }
         }
@@ -53,26 +72,7 @@ impl Plugin<()> for MapCanvas {
         Rc::clone(&self.canvas) as Rc<RefCell<dyn RenderStep>>
     }
 
-    fn handle_event(&mut self, _app_state: (), event: Event) -> bool {
-        let mut canvas = self.canvas.borrow_mut();
-
-        if let Event::Click(point) = event {
-            canvas.add_object(Box::new(
-                Path::new(
-                    [
-                        Vec3::new(0.0, 0.0, 0.0),
-                        Vec3::new(20.0, 0.0, 0.0),
-                        Vec3::new(0.0, 0.0, 20.0),
-                        Vec3::new(0.0, 0.0, 0.0),
-                    ]
-                    .into_iter()
-                    .map(|p| point + p)
-                    .collect(),
-                )
-                .with_fill(Vec3::new(1.0, 0.0, 0.0)),
-            ));
-        }
-
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
         false
     }
 }
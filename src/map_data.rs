@@ -1,4 +1,9 @@
-use crate::{osm::Osm, Point};
+use crate::{
+    elevation::Elevation,
+    export::LonLat,
+    osm::{Node, Osm, Way},
+    Point,
+};
 
 #[derive(Debug)]
 pub(crate) struct Bounding {
@@ -31,11 +36,22 @@ impl Bounding {
 
         self
     }
+
+    /// Longitude and latitude of the south west and north east corners.
+    pub fn lon_lat(&self) -> (LonLat, LonLat) {
+        (
+            Node::new(self.min_x, self.min_y).lon_lat(),
+            Node::new(self.max_x, self.max_y).lon_lat(),
+        )
+    }
 }
 
 pub(crate) struct MapData {
     pub bounding: Bounding,
     pub osm_data: Osm,
+
+    /// Terrain covering the map, if any elevation data was provided
+    pub elevation: Option<Elevation>,
 }
 
 impl MapData {
@@ -70,7 +86,16 @@ impl MapData {
         bounding.center_x = (bounding.min_x + bounding.max_x) / 2.0;
         bounding.center_y = (bounding.min_y + bounding.max_y) / 2.0;
 
-        Self { bounding, osm_data }
+        Self {
+            bounding,
+            osm_data,
+            elevation: None,
+        }
+    }
+
+    pub fn with_elevation(mut self, elevation: Elevation) -> Self {
+        self.elevation = Some(elevation);
+        self
     }
 
     pub fn translate(&self, point: Point) -> Point {
@@ -80,4 +105,29 @@ impl MapData {
             point.y - (self.bounding.center_y as f32),
         )
     }
+
+    /// Position of a node, translated into world space
+    pub fn node_point(&self, node: &Node) -> Point {
+        self.translate(Point::new(node.x as f32, node.y as f32))
+    }
+
+    /// Positions of all the nodes of a way, translated into world space
+    pub fn way_points(&self, way: &Way) -> Vec<Point> {
+        way.nodes
+            .iter()
+            .filter_map(|node_id| self.osm_data.nodes.get(node_id))
+            .map(|node| self.node_point(node))
+            .collect()
+    }
+
+    /// Elevation (in metres) at a position in world space, if there's elevation data for it.
+    pub fn elevation_at(&self, point: &Point) -> Option<f32> {
+        let (lon, lat) = Node::new(
+            point.x as f64 + self.bounding.center_x,
+            point.y as f64 + self.bounding.center_y,
+        )
+        .lon_lat();
+
+        self.elevation.as_ref()?.at(lon, lat)
+    }
 }
@@ -0,0 +1,44 @@
+use std::{cell::RefCell, rc::Rc};
+
+use renderer::{render_steps::icon::IconProgram, Event, RenderStep};
+
+use crate::{map_data::MapData, plugin::Plugin};
+
+pub(crate) struct MapIcons {
+    icons: Rc<RefCell<IconProgram>>,
+}
+
+impl MapIcons {
+    pub fn new() -> Self {
+        Self {
+            icons: Rc::new(RefCell::new(IconProgram::default())),
+        }
+    }
+}
+
+impl Plugin<()> for MapIcons {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let mut icons = self.icons.borrow_mut();
+
+        icons.clear();
+
+        for node in map_data.osm_data.nodes.values() {
+            if let Some(poi) = node.to_poi() {
+                // Only rasterise each sprite once
+                if !icons.has_sprite(poi.sprite_name()) {
+                    icons.add_sprite(poi.sprite_name(), poi.get_sprite());
+                }
+
+                icons.add_icon(poi.get_icon(&map_data.node_point(node)));
+            }
+        }
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.icons) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
+        false
+    }
+}
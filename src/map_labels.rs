@@ -0,0 +1,147 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{Vec2, Vec3};
+use renderer::{
+    render_steps::label::{Label, LabelProgram},
+    Event, RenderStep,
+};
+
+use crate::{map_data::MapData, objects::Highway, osm::Tags, plugin::Plugin, Point};
+
+pub(crate) struct MapLabels {
+    labels: Rc<RefCell<LabelProgram>>,
+}
+
+impl MapLabels {
+    pub fn new() -> Self {
+        Self {
+            labels: Rc::new(RefCell::new(LabelProgram::default())),
+        }
+    }
+}
+
+/// Label for a named place (city, suburb, etc), with larger places being more prominent.
+fn place_label(position: Vec3, name: &str, place: &str) -> Label {
+    let (size, priority) = match place {
+        "city" => (18.0, 100),
+        "town" => (16.0, 90),
+        "suburb" => (15.0, 80),
+        "village" => (14.0, 70),
+        "neighbourhood" | "quarter" => (13.0, 60),
+        _ => (12.0, 50),
+    };
+
+    Label::point(position, name)
+        .with_size(size)
+        .with_priority(priority)
+        .with_color(Vec3::new(0.1, 0.1, 0.1))
+}
+
+/// Street names follow the road, with major roads taking priority.
+fn street_label(points: Vec<Vec3>, name: &str, tags: &Tags) -> Option<Label> {
+    let priority = match Highway::from_tags(tags)? {
+        Highway::Motorway | Highway::Trunk => 45,
+        Highway::Primary | Highway::Secondary => 40,
+        Highway::MotorwayLink | Highway::TrunkLink => 38,
+        Highway::Tertiary | Highway::PrimaryLink | Highway::SecondaryLink => 35,
+        Highway::TertiaryLink => 32,
+        Highway::Unclassified | Highway::Residential | Highway::LivingStreet => 30,
+        Highway::Pedestrian | Highway::PedestrianArea => 28,
+        Highway::Service | Highway::Track(_) | Highway::Other => 25,
+        Highway::Construction | Highway::Proposed => 22,
+        Highway::Footway
+        | Highway::Steps
+        | Highway::Path
+        | Highway::Cycleway
+        | Highway::Bridleway => 20,
+    };
+
+    Some(
+        Label::line(points, name)
+            .with_size(12.0)
+            .with_priority(priority),
+    )
+}
+
+/// Named points of interest (shops, cafes, etc), placed below the POI's icon if it has one.
+fn poi_label(position: Vec3, name: &str, has_icon: bool) -> Label {
+    let label = Label::point(position, name)
+        .with_size(11.0)
+        .with_priority(10)
+        .with_color(Vec3::new(0.45, 0.3, 0.2));
+
+    if has_icon {
+        label.with_offset(Vec2::new(0.0, -18.0))
+    } else {
+        label
+    }
+}
+
+/// House numbers are the least important, and are only shown when zoomed in enough for them to
+/// fit.
+fn house_number_label(position: Vec3, number: &str) -> Label {
+    Label::point(position, number)
+        .with_size(10.0)
+        .with_priority(0)
+        .with_color(Vec3::new(0.45, 0.45, 0.45))
+}
+
+impl Plugin<()> for MapLabels {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let mut labels = self.labels.borrow_mut();
+
+        labels.clear();
+
+        for node in map_data.osm_data.nodes.values() {
+            let Some(name) = node.tags.get("name") else {
+                continue;
+            };
+            let position = (&map_data.node_point(node)).into();
+
+            if let Some(place) = node.tags.get("place") {
+                labels.add_label(place_label(position, name, place));
+            } else if ["amenity", "shop", "tourism", "leisure"]
+                .iter()
+                .any(|key| node.tags.contains(key))
+            {
+                labels.add_label(poi_label(position, name, node.to_poi().is_some()));
+            }
+        }
+
+        for way in map_data.osm_data.ways.values() {
+            let points = map_data.way_points(way);
+            if points.is_empty() {
+                continue;
+            }
+
+            if way.tags.contains("highway") {
+                if let Some(name) = way.tags.get("name").or_else(|| way.tags.get("ref")) {
+                    let points = points.iter().map(|p| p.into()).collect();
+
+                    if let Some(label) = street_label(points, name, &way.tags) {
+                        labels.add_label(label);
+                    }
+                }
+            } else if let Some(number) = way.tags.get("addr:housenumber") {
+                // Place the house number in the middle of the building
+                let center = points.iter().fold(Point::new(0.0, 0.0), |sum, p| {
+                    Point::new(sum.x + p.x, sum.y + p.y)
+                });
+                let center = Point::new(
+                    center.x / points.len() as f32,
+                    center.y / points.len() as f32,
+                );
+
+                labels.add_label(house_number_label((&center).into(), number));
+            }
+        }
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.labels) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
+        false
+    }
+}
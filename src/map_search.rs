@@ -0,0 +1,232 @@
+use std::{cell::RefCell, rc::Rc};
+
+use glam::{Vec2, Vec3};
+use renderer::{
+    render_steps::label::{Label, LabelProgram},
+    Event, RenderStep, VirtualKeyCode,
+};
+
+use crate::{
+    geocoder::{Geocoder, PlaceKind},
+    map_data::MapData,
+    plugin::Plugin,
+    Point,
+};
+
+/// Character typed to open the search prompt.
+const SEARCH_KEY: char = '/';
+
+/// Distance (in pixels) between the lines of the prompt, from the top of the screen.
+const LINE_HEIGHT: f32 = 28.0;
+
+/// Labels of the prompt are placed before the marker.
+const PROMPT_PRIORITY: i32 = i32::MAX;
+
+const TEXT_COLOR: Vec3 = Vec3::new(0.1, 0.1, 0.1);
+const SELECTED_COLOR: Vec3 = Vec3::new(0.1, 0.35, 0.8);
+
+/// Height (in world units) that the camera looks at a result from, closer for smaller things.
+fn viewing_height(kind: PlaceKind) -> f32 {
+    match kind {
+        PlaceKind::Settlement => 1500.0,
+        PlaceKind::Street => 400.0,
+        PlaceKind::Address | PlaceKind::Feature => 150.0,
+    }
+}
+
+/// Query being typed, along with the places it currently matches.
+#[derive(Default)]
+struct Prompt {
+    query: String,
+
+    /// Description and position (in world space) of each result, with the height to view it from
+    results: Vec<(String, Point, f32)>,
+    selected: usize,
+}
+
+/// Searches for places by name or address, flying the camera to whichever is picked, and reports
+/// the address closest to wherever is clicked.
+///
+/// Typing `/` opens the search prompt, with the up and down arrows choosing between the results,
+/// enter going to the chosen result, and escape closing the prompt.
+pub(crate) struct MapSearch {
+    labels: Rc<RefCell<LabelProgram>>,
+    geocoder: Option<Geocoder>,
+
+    /// Open search prompt, if any
+    prompt: Option<Prompt>,
+
+    /// Result (or address) that was last found, marked on the map
+    marker: Option<(String, Point)>,
+
+    /// Where the camera should fly to next
+    camera_target: Option<(Vec3, f32)>,
+}
+
+impl MapSearch {
+    pub fn new() -> Self {
+        Self {
+            labels: Rc::new(RefCell::new(LabelProgram::default())),
+            geocoder: None,
+            prompt: None,
+            marker: None,
+            camera_target: None,
+        }
+    }
+
+    fn search(&mut self) {
+        let (Some(prompt), Some(geocoder)) = (self.prompt.as_mut(), self.geocoder.as_ref()) else {
+            return;
+        };
+
+        prompt.results = geocoder
+            .search(&prompt.query)
+            .into_iter()
+            .map(|place| {
+                (
+                    place.description(),
+                    place.position,
+                    viewing_height(place.kind),
+                )
+            })
+            .collect();
+        prompt.selected = 0;
+    }
+
+    /// Rebuilds the labels for the prompt and marker.
+    fn update_labels(&self) {
+        let mut labels = self.labels.borrow_mut();
+        labels.clear();
+
+        if let Some((description, position)) = self.marker.as_ref() {
+            labels.add_label(
+                Label::point(position.into(), description)
+                    .with_size(14.0)
+                    .with_priority(PROMPT_PRIORITY - 1)
+                    .with_color(SELECTED_COLOR)
+                    .with_allow_overlap(true),
+            );
+        }
+
+        let Some(prompt) = self.prompt.as_ref() else {
+            return;
+        };
+
+        // Lines going down from the top of the screen
+        let line = |i: usize, text: &str, color: Vec3| {
+            Label::screen(Vec2::new(0.0, 1.0), text)
+                .with_offset(Vec2::new(0.0, -LINE_HEIGHT * (i as f32 + 1.0)))
+                .with_size(16.0)
+                .with_priority(PROMPT_PRIORITY)
+                .with_color(color)
+                .with_allow_overlap(true)
+        };
+
+        labels.add_label(line(0, &format!("Search: {}_", prompt.query), TEXT_COLOR));
+
+        if prompt.results.is_empty() && !prompt.query.is_empty() {
+            labels.add_label(line(1, "No results", TEXT_COLOR));
+        }
+
+        for (i, (description, _, _)) in prompt.results.iter().enumerate() {
+            let (text, color) = if i == prompt.selected {
+                (format!("> {description}"), SELECTED_COLOR)
+            } else {
+                (description.clone(), TEXT_COLOR)
+            };
+
+            labels.add_label(line(i + 1, &text, color));
+        }
+    }
+
+    /// Handles a key pressed whilst the prompt is open.
+    fn handle_key(&mut self, keycode: VirtualKeyCode) {
+        let Some(prompt) = self.prompt.as_mut() else {
+            return;
+        };
+
+        match keycode {
+            VirtualKeyCode::Escape => {
+                self.prompt = None;
+            }
+            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                if let Some((description, position, height)) =
+                    prompt.results.get(prompt.selected).cloned()
+                {
+                    println!("Found: {description}");
+
+                    self.camera_target = Some(((&position).into(), height));
+                    self.marker = Some((description, position));
+                    self.prompt = None;
+                }
+            }
+            VirtualKeyCode::Up => {
+                prompt.selected = prompt.selected.saturating_sub(1);
+            }
+            VirtualKeyCode::Down => {
+                prompt.selected = (prompt.selected + 1).min(prompt.results.len().saturating_sub(1));
+            }
+            VirtualKeyCode::Back => {
+                prompt.query.pop();
+                self.search();
+            }
+            _ => (),
+        }
+    }
+}
+
+impl Plugin<()> for MapSearch {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        self.geocoder = Some(Geocoder::new(&map_data));
+        self.prompt = None;
+        self.marker = None;
+
+        self.update_labels();
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.labels) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), event: Event) -> bool {
+        match (event, self.prompt.as_mut()) {
+            (Event::Character(SEARCH_KEY), None) => {
+                self.prompt = Some(Prompt::default());
+            }
+            (Event::Character(c), Some(prompt)) if !c.is_control() => {
+                prompt.query.push(c);
+                self.search();
+            }
+            (Event::Keyboard(keycode), Some(_)) => {
+                self.handle_key(keycode);
+            }
+            (Event::Click(position), None) => {
+                let position = Point::new(position.x, position.z);
+
+                self.marker = self
+                    .geocoder
+                    .as_ref()
+                    .and_then(|geocoder| geocoder.reverse(&position))
+                    .and_then(|(place, distance)| {
+                        let address = place.address.clone()?;
+                        println!("Nearest address: {address} ({distance:.0} m away)");
+
+                        Some((address, place.position))
+                    });
+            }
+            _ => return false,
+        }
+
+        self.update_labels();
+
+        true
+    }
+
+    fn captures_keyboard(&self) -> bool {
+        self.prompt.is_some()
+    }
+
+    fn take_camera_target(&mut self) -> Option<(Vec3, f32)> {
+        self.camera_target.take()
+    }
+}
@@ -0,0 +1,52 @@
+use std::{cell::RefCell, rc::Rc};
+
+use renderer::{render_steps::raster::RasterProgram, Event, RenderStep};
+
+use crate::{
+    elevation::{hillshade, Heightmap},
+    map_data::MapData,
+    plugin::Plugin,
+    Point,
+};
+
+/// Shades the terrain under the map, and reports the elevation of wherever is clicked.
+pub(crate) struct MapTerrain {
+    rasters: Rc<RefCell<RasterProgram>>,
+    map_data: Option<Rc<MapData>>,
+}
+
+impl MapTerrain {
+    pub fn new() -> Self {
+        Self {
+            rasters: Rc::new(RefCell::new(RasterProgram::default())),
+            map_data: None,
+        }
+    }
+}
+
+impl Plugin<()> for MapTerrain {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let mut rasters = self.rasters.borrow_mut();
+        rasters.clear();
+
+        if let Some(elevation) = map_data.elevation.as_ref() {
+            rasters.add_raster(hillshade(&Heightmap::new(&map_data, elevation)));
+        }
+
+        self.map_data = Some(map_data);
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.rasters) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), event: Event) -> bool {
+        if let (Event::Click(position), Some(map_data)) = (event, self.map_data.as_ref()) {
+            if let Some(elevation) = map_data.elevation_at(&Point::new(position.x, position.z)) {
+                println!("Elevation: {elevation:.0} m");
+            }
+        }
+
+        false
+    }
+}
@@ -0,0 +1,209 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::Display,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use glam::{UVec2, Vec3};
+use renderer::{
+    render_steps::tiles::{TileId, TileImage, TileProgram, TileSource},
+    Event, RenderStep,
+};
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+
+use crate::{map_data::MapData, osm::Node, plugin::Plugin};
+
+/// Furthest latitude (in degrees) north or south covered by Web Mercator tiles, where the map
+/// becomes square.
+const MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+#[derive(Debug)]
+pub(crate) enum TilesError {
+    SqliteError(rusqlite::Error),
+    Empty,
+}
+impl Display for TilesError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SqliteError(e) => write!(f, "Problem reading MBTiles: {e}"),
+            Self::Empty => write!(f, "Problem reading MBTiles: there aren't any tiles"),
+        }
+    }
+}
+impl Error for TilesError {}
+
+/// Raster tiles (PNG or JPEG) stored in an SQLite database.
+// https://github.com/mapbox/mbtiles-spec/blob/master/1.3/spec.md
+pub(crate) struct MbTiles {
+    connection: Connection,
+    zoom_range: (u32, u32),
+}
+
+impl MbTiles {
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, TilesError> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(TilesError::SqliteError)?;
+
+        let zoom_range = connection
+            .query_row(
+                "SELECT MIN(zoom_level), MAX(zoom_level) FROM tiles",
+                [],
+                |row| Ok((row.get::<_, Option<u32>>(0)?, row.get::<_, Option<u32>>(1)?)),
+            )
+            .map_err(TilesError::SqliteError)?;
+
+        let (Some(min_zoom), Some(max_zoom)) = zoom_range else {
+            return Err(TilesError::Empty);
+        };
+
+        Ok(Self {
+            connection,
+            zoom_range: (min_zoom, max_zoom),
+        })
+    }
+}
+
+impl TileSource for MbTiles {
+    fn zoom_range(&self) -> (u32, u32) {
+        self.zoom_range
+    }
+
+    fn get_tile(&mut self, tile: TileId) -> Option<TileImage> {
+        // Rows are numbered from the south (the TMS scheme), rather than from the north
+        let row = (1 << tile.zoom) - 1 - tile.y;
+
+        let data = self
+            .connection
+            .prepare_cached(
+                "SELECT tile_data FROM tiles
+                WHERE zoom_level = ?1 AND tile_column = ?2 AND tile_row = ?3",
+            )
+            .and_then(|mut statement| {
+                statement
+                    .query_row([tile.zoom, tile.x, row], |row| row.get::<_, Vec<u8>>(0))
+                    .optional()
+            })
+            .unwrap_or_else(|e| {
+                eprintln!("{}", TilesError::SqliteError(e));
+                None
+            })?;
+
+        decode_png(&data).or_else(|| decode_jpeg(&data))
+    }
+}
+
+/// Decodes a PNG of any colour type into RGBA.
+fn decode_png(data: &[u8]) -> Option<TileImage> {
+    let mut decoder = png::Decoder::new(data);
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+
+    let mut reader = decoder.read_info().ok()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer).ok()?;
+    let buffer = &buffer[..info.buffer_size()];
+
+    let pixels = match info.color_type {
+        png::ColorType::Rgba => buffer.to_vec(),
+        png::ColorType::Rgb => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => buffer
+            .chunks_exact(2)
+            .flat_map(|gray| [gray[0], gray[0], gray[0], gray[1]])
+            .collect(),
+        png::ColorType::Grayscale => buffer
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, u8::MAX])
+            .collect(),
+        png::ColorType::Indexed => return None,
+    };
+
+    Some(TileImage::from_rgba8(
+        UVec2::new(info.width, info.height),
+        pixels,
+    ))
+}
+
+/// Decodes a colour or greyscale JPEG into RGBA.
+fn decode_jpeg(data: &[u8]) -> Option<TileImage> {
+    let mut decoder = jpeg_decoder::Decoder::new(data);
+    let buffer = decoder.decode().ok()?;
+    let info = decoder.info()?;
+
+    let pixels = match info.pixel_format {
+        jpeg_decoder::PixelFormat::RGB24 => buffer
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], u8::MAX])
+            .collect(),
+        jpeg_decoder::PixelFormat::L8 => buffer
+            .iter()
+            .flat_map(|&gray| [gray, gray, gray, u8::MAX])
+            .collect(),
+        _ => return None,
+    };
+
+    Some(TileImage::from_rgba8(
+        UVec2::new(info.width as u32, info.height as u32),
+        pixels,
+    ))
+}
+
+/// Draws raster tiles (such as satellite imagery) from an MBTiles file under the map.
+pub(crate) struct MapTiles {
+    tiles: Rc<RefCell<TileProgram>>,
+
+    /// MBTiles file to draw
+    file: Option<PathBuf>,
+}
+
+impl MapTiles {
+    pub fn new(file: Option<PathBuf>) -> Self {
+        Self {
+            tiles: Rc::new(RefCell::new(TileProgram::default())),
+            file,
+        }
+    }
+}
+
+impl Plugin<()> for MapTiles {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let Some(file) = self.file.as_ref() else {
+            return;
+        };
+
+        let source = match MbTiles::open(file) {
+            Ok(source) => source,
+            Err(e) => {
+                eprintln!("{e} ({})", file.display());
+                return;
+            }
+        };
+
+        // Tiles are positioned relative to the single tile covering the whole world at zoom 0,
+        // translated in full precision as it's so far from the map
+        let north_west = Node::from_lon_lat(-180.0, MAX_LATITUDE);
+        let south_east = Node::from_lon_lat(180.0, -MAX_LATITUDE);
+        let origin = Vec3::new(
+            (north_west.x - map_data.bounding.center_x) as f32,
+            0.0,
+            (north_west.y - map_data.bounding.center_y) as f32,
+        );
+
+        self.tiles.borrow_mut().set_source(
+            Box::new(source),
+            origin,
+            (south_east.x - north_west.x) as f32,
+        );
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.tiles) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
+        false
+    }
+}
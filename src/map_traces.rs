@@ -0,0 +1,181 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    fmt::Display,
+    fs,
+    path::{Path as FilePath, PathBuf},
+    rc::Rc,
+};
+
+use glam::Vec3;
+use renderer::{
+    render_steps::canvas::{CanvasProgram, LineCap, LineJoin, Path, Stroke},
+    Event, RenderStep,
+};
+
+use crate::{
+    export::LonLat,
+    map_data::MapData,
+    osm::Node,
+    path_finder::{MapMatcher, Network, Profile},
+    plugin::Plugin,
+};
+
+#[derive(Debug)]
+pub(crate) enum TraceError {
+    IoError(std::io::Error),
+    ParseError(roxmltree::Error),
+    InvalidPoint,
+}
+impl Display for TraceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "Problem reading GPS trace: {e}"),
+            Self::ParseError(e) => write!(f, "Problem parsing GPS trace: {e}"),
+            Self::InvalidPoint => write!(f, "Problem parsing GPS trace: invalid point position"),
+        }
+    }
+}
+impl Error for TraceError {}
+
+/// Reads the positions of each track segment (and route) in a GPX file.
+// https://www.topografix.com/GPX/1/1/
+pub(crate) fn read_gpx(path: impl AsRef<FilePath>) -> Result<Vec<Vec<LonLat>>, TraceError> {
+    let source = fs::read_to_string(path).map_err(TraceError::IoError)?;
+    let document = roxmltree::Document::parse(&source).map_err(TraceError::ParseError)?;
+
+    document
+        .descendants()
+        .filter(|node| matches!(node.tag_name().name(), "trkseg" | "rte"))
+        .map(|segment| {
+            segment
+                .children()
+                .filter(|node| matches!(node.tag_name().name(), "trkpt" | "rtept"))
+                .map(|point| {
+                    let coordinate = |name| {
+                        point
+                            .attribute(name)
+                            .and_then(|value: &str| value.trim().parse::<f64>().ok())
+                            .ok_or(TraceError::InvalidPoint)
+                    };
+
+                    Ok((coordinate("lon")?, coordinate("lat")?))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Draws GPS traces over the map, along with the roads they were matched onto.
+pub(crate) struct MapTraces {
+    canvas: Rc<RefCell<CanvasProgram>>,
+
+    /// GPX files to draw
+    files: Vec<PathBuf>,
+
+    /// Routing profile of the vehicle that recorded the traces, deciding which roads they can be
+    /// matched onto
+    profile: Profile,
+}
+
+impl MapTraces {
+    pub fn new(files: Vec<PathBuf>, profile: Profile) -> Self {
+        Self {
+            canvas: Rc::new(RefCell::new(CanvasProgram::default())),
+            files,
+            profile,
+        }
+    }
+}
+
+impl Plugin<()> for MapTraces {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.clear();
+
+        if self.files.is_empty() {
+            return;
+        }
+
+        let network = Network::new(&map_data.osm_data, &self.profile);
+        let matcher = MapMatcher::new(&map_data.osm_data, &network);
+
+        let node_position = |node: &Node| -> Vec3 { (&map_data.node_point(node)).into() };
+
+        for file in &self.files {
+            let segments = match read_gpx(file) {
+                Ok(segments) => segments,
+                Err(e) => {
+                    eprintln!("{e} ({})", file.display());
+                    continue;
+                }
+            };
+
+            for trace in segments {
+                let routes = matcher.match_trace(&trace);
+                println!(
+                    "Trace {}: {} positions matched onto {:.2} km of road",
+                    file.display(),
+                    trace.len(),
+                    routes.iter().map(|route| route.distance).sum::<f64>() / 1000.0
+                );
+
+                for route in routes {
+                    let points = network
+                        .route_nodes(&route)
+                        .into_iter()
+                        .filter_map(|node| map_data.osm_data.nodes.get(&node))
+                        .map(node_position)
+                        .collect::<Vec<_>>();
+
+                    for path in matched_paths(points) {
+                        canvas.add_object(Box::new(path));
+                    }
+                }
+
+                let points = trace
+                    .into_iter()
+                    .map(|(lon, lat)| node_position(&Node::from_lon_lat(lon, lat)))
+                    .collect();
+
+                canvas.add_object(Box::new(
+                    Path::new(points)
+                        .with_stroke(
+                            Stroke::new(2.0, Vec3::new(0.85, 0.1, 0.1))
+                                .with_join(LineJoin::Round)
+                                .with_cap(LineCap::Round),
+                        )
+                        .with_z_index(2),
+                ));
+            }
+        }
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.canvas) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), _event: Event) -> bool {
+        false
+    }
+}
+
+/// Roads that a trace was matched onto, drawn underneath the raw trace with a white casing.
+fn matched_paths(points: Vec<Vec3>) -> Vec<Path> {
+    vec![
+        Path::new(points.clone())
+            .with_stroke(
+                Stroke::new(9.0, Vec3::ONE)
+                    .with_join(LineJoin::Round)
+                    .with_cap(LineCap::Round),
+            )
+            .with_z_index(0),
+        Path::new(points)
+            .with_stroke(
+                Stroke::new(5.0, Vec3::new(0.55, 0.25, 0.75))
+                    .with_join(LineJoin::Round)
+                    .with_cap(LineCap::Round),
+            )
+            .with_z_index(1),
+    ]
+}
@@ -0,0 +1,17 @@
+use super::Object;
+use crate::Point;
+use glam::Vec3;
+use renderer::render_steps::canvas::{Path, Stroke};
+
+pub struct Boundary;
+
+impl Object for Boundary {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        vec![
+            Path::new(points.iter().map(|p| p.into()).collect()).with_stroke(
+                Stroke::new(1.5, Vec3::new(172.0, 70.0, 172.0) / 255.0)
+                    .with_dash(&[8.0, 3.0, 2.0, 3.0]),
+            ),
+        ]
+    }
+}
@@ -1,13 +1,60 @@
-use super::Object;
-use crate::Point;
+use crate::{osm::Tags, Point};
 use glam::Vec3;
-use renderer::render_steps::canvas::Path;
+use renderer::render_steps::extrusion::Extrusion;
 
-pub struct Building;
+/// Height (in metres) of each level of a building.
+const LEVEL_HEIGHT: f32 = 3.0;
 
-impl Object for Building {
-    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
-        vec![Path::new(points.iter().map(|p| p.into()).collect())
-            .with_fill(Vec3::new(0.7, 0.7, 0.7))]
+/// Number of levels assumed for buildings without a height, which are mostly houses.
+const DEFAULT_LEVELS: f32 = 2.0;
+
+const BUILDING_COLOR: Vec3 = Vec3::new(0.7, 0.7, 0.7);
+
+// https://wiki.openstreetmap.org/wiki/Simple_3D_Buildings
+pub struct Building {
+    /// Height (in metres) of the roof above the ground
+    height: f32,
+
+    /// Height (in metres) of the bottom of the building above the ground, for parts that overhang
+    min_height: f32,
+}
+
+impl Building {
+    pub fn from_tags(tags: &Tags) -> Building {
+        // Heights are in metres unless another unit is given, which is rare enough to be ignored
+        let metres = |key| {
+            tags.get(key).and_then(|value: &String| {
+                value
+                    .trim()
+                    .trim_end_matches('m')
+                    .trim_end()
+                    .parse::<f32>()
+                    .ok()
+            })
+        };
+        let levels = |key| {
+            tags.get(key)
+                .and_then(|value: &String| value.trim().parse::<f32>().ok())
+                .map(|levels| levels * LEVEL_HEIGHT)
+        };
+
+        let height = metres("height")
+            .or_else(|| levels("building:levels"))
+            .unwrap_or(DEFAULT_LEVELS * LEVEL_HEIGHT);
+        let min_height = metres("min_height")
+            .or_else(|| levels("building:min_level"))
+            .unwrap_or_default();
+
+        Building { height, min_height }
+    }
+
+    /// Solid shape of the building, where `world_scale` is the number of world units per metre.
+    pub fn get_extrusion(&self, points: &[Point], world_scale: f32) -> Extrusion {
+        Extrusion::new(
+            points.iter().map(|p| p.into()).collect(),
+            self.height * world_scale,
+            BUILDING_COLOR,
+        )
+        .with_min_height(self.min_height * world_scale)
     }
 }
@@ -1,77 +1,354 @@
 use glam::Vec3;
-use renderer::render_steps::canvas::{Path, Stroke};
+use renderer::render_steps::canvas::{LineCap, LineJoin, Path, Stroke};
 
 use super::Object;
 use crate::{osm::Tags, Point};
 
 // https://wiki.openstreetmap.org/wiki/Key:highway?uselang=en-GB
-#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Highway {
     Motorway,
+    MotorwayLink,
     Trunk,
+    TrunkLink,
     Primary,
+    PrimaryLink,
     Secondary,
+    SecondaryLink,
     Tertiary,
+    TertiaryLink,
     Unclassified,
     Residential,
+    LivingStreet,
     Service,
+    Pedestrian,
+
+    /// Agricultural or forestry road, with how firm its surface is from 1 (paved) to 5 (soft)
+    // https://wiki.openstreetmap.org/wiki/Key:tracktype
+    Track(Option<u8>),
     Footway,
+    Steps,
     Path,
     Cycleway,
+    Bridleway,
+    Construction,
+    Proposed,
+
+    /// Pedestrian square, drawn as an area rather than a line
+    PedestrianArea,
     Other,
 }
 
 impl Highway {
     pub fn from_tags(tags: &Tags) -> Option<Highway> {
+        let is_area = tags.get("area").filter(|&area| area == "yes").is_some();
+
         tags.get("highway").map(|tag| match tag.as_str() {
+            "pedestrian" | "footway" if is_area => Highway::PedestrianArea,
+            "motorway" => Highway::Motorway,
+            "motorway_link" => Highway::MotorwayLink,
+            "trunk" => Highway::Trunk,
+            "trunk_link" => Highway::TrunkLink,
+            "primary" => Highway::Primary,
+            "primary_link" => Highway::PrimaryLink,
+            "secondary" => Highway::Secondary,
+            "secondary_link" => Highway::SecondaryLink,
             "tertiary" => Highway::Tertiary,
+            "tertiary_link" => Highway::TertiaryLink,
+            "unclassified" => Highway::Unclassified,
             "residential" => Highway::Residential,
+            "living_street" => Highway::LivingStreet,
             "service" => Highway::Service,
+            "pedestrian" => Highway::Pedestrian,
+            "track" => Highway::Track(
+                tags.get("tracktype")
+                    .and_then(|grade| grade.strip_prefix("grade")?.parse().ok())
+                    .filter(|grade| (1..=5).contains(grade)),
+            ),
             "footway" => Highway::Footway,
+            "steps" => Highway::Steps,
             "path" => Highway::Path,
             "cycleway" => Highway::Cycleway,
+            "bridleway" => Highway::Bridleway,
+            "construction" => Highway::Construction,
+            "proposed" => Highway::Proposed,
             _ => Highway::Other,
         })
     }
+
+    /// Rank of the road, used so that more important roads are drawn over less important ones.
+    /// Links are drawn just under the roads they join.
+    fn rank(&self) -> i32 {
+        match self {
+            Self::Motorway => 18,
+            Self::MotorwayLink => 17,
+            Self::Trunk => 16,
+            Self::TrunkLink => 15,
+            Self::Primary => 14,
+            Self::PrimaryLink => 13,
+            Self::Secondary => 12,
+            Self::SecondaryLink => 11,
+            Self::Tertiary => 10,
+            Self::TertiaryLink => 9,
+            Self::Unclassified | Self::Residential | Self::LivingStreet => 8,
+            Self::Pedestrian => 7,
+            Self::Service => 6,
+            Self::Other | Self::Construction | Self::Proposed => 5,
+            Self::Track(_) => 4,
+            Self::Cycleway | Self::Bridleway => 3,
+            Self::Footway | Self::Path | Self::Steps => 2,
+            Self::PedestrianArea => 0,
+        }
+    }
+
+    /// Width (in pixels) of the road's fill.
+    fn width(&self) -> f32 {
+        match self {
+            Self::Motorway => 8.0,
+            Self::Trunk | Self::Primary | Self::Secondary | Self::Tertiary => 6.0,
+            Self::MotorwayLink => 5.0,
+            Self::Unclassified | Self::Residential | Self::LivingStreet | Self::Pedestrian => 4.0,
+            Self::TrunkLink | Self::PrimaryLink | Self::SecondaryLink | Self::TertiaryLink => 4.0,
+            Self::Steps | Self::Construction => 4.0,
+            Self::Service | Self::Proposed => 3.0,
+            Self::Footway | Self::Path => 1.5,
+            Self::PedestrianArea => 1.0,
+            _ => 2.0,
+        }
+    }
+
+    fn color(&self) -> Vec3 {
+        match self {
+            Self::Motorway | Self::MotorwayLink => Vec3::new(223.0, 46.0, 107.0) / 255.0,
+            Self::Trunk | Self::TrunkLink => Vec3::new(234.0, 144.0, 161.0) / 255.0,
+            Self::Primary | Self::PrimaryLink => Vec3::new(252.0, 192.0, 171.0) / 255.0,
+            Self::Secondary | Self::SecondaryLink => Vec3::new(253.0, 214.0, 1.0) / 255.0,
+            Self::Tertiary | Self::TertiaryLink => Vec3::new(246.0, 250.0, 187.0) / 255.0,
+            Self::Unclassified | Self::Residential | Self::Service => Vec3::ONE,
+            Self::LivingStreet => Vec3::new(237.0, 237.0, 237.0) / 255.0,
+            Self::Pedestrian | Self::PedestrianArea => Vec3::new(221.0, 221.0, 232.0) / 255.0,
+            Self::Track(_) => Vec3::new(153.0, 102.0, 0.0) / 255.0,
+            Self::Footway | Self::Steps => Vec3::new(250.0, 164.0, 156.0) / 255.0,
+            Self::Path => Vec3::new(220.0, 120.0, 110.0) / 255.0,
+            Self::Cycleway => Vec3::new(80.0, 80.0, 255.0) / 255.0,
+            Self::Bridleway => Vec3::new(0.0, 128.0, 0.0) / 255.0,
+            Self::Construction => Vec3::new(200.0, 200.0, 200.0) / 255.0,
+            Self::Proposed => Vec3::new(225.0, 225.0, 225.0) / 255.0,
+            Self::Other => Vec3::new(169.0, 175.0, 182.0) / 255.0,
+        }
+    }
+
+    /// Dash pattern (lengths in pixels) for minor and unfinished ways, which aren't solid.
+    fn dash(&self) -> Option<&'static [f32]> {
+        match self {
+            Self::Footway => Some(&[4.0, 3.0]),
+            Self::Path => Some(&[2.0, 2.0]),
+            Self::Steps => Some(&[1.5, 1.5]),
+            Self::Cycleway => Some(&[6.0, 3.0]),
+            Self::Bridleway => Some(&[5.0, 3.0]),
+            Self::Construction => Some(&[8.0, 6.0]),
+            Self::Proposed => Some(&[3.0, 6.0]),
+
+            // Softer tracks are drawn with shorter dashes
+            Self::Track(Some(1)) => None,
+            Self::Track(Some(2)) => Some(&[10.0, 3.0]),
+            Self::Track(Some(3)) => Some(&[6.0, 3.0]),
+            Self::Track(Some(4)) => Some(&[4.0, 4.0]),
+            Self::Track(Some(5)) => Some(&[2.0, 4.0]),
+            Self::Track(_) => Some(&[6.0, 3.0, 2.0, 3.0]),
+            _ => None,
+        }
+    }
+
+    /// Darker outline drawn underneath the road, for roads wide enough to have one.
+    fn casing(&self) -> Option<Stroke> {
+        let color = match self {
+            Self::Footway
+            | Self::Steps
+            | Self::Path
+            | Self::Cycleway
+            | Self::Bridleway
+            | Self::Track(_)
+            | Self::Construction
+            | Self::Proposed
+            | Self::PedestrianArea
+            | Self::Other => return None,
+            Self::Unclassified
+            | Self::Residential
+            | Self::LivingStreet
+            | Self::Service
+            | Self::Pedestrian => Vec3::new(169.0, 175.0, 182.0) / 255.0,
+            _ => self.color() * 0.7,
+        };
+
+        Some(
+            Stroke::new(self.width() + 2.0, color)
+                .with_join(LineJoin::Round)
+                .with_cap(LineCap::Round),
+        )
+    }
 }
 
+/// Casings are all drawn before any road fills (within a layer), so that casings never cut across
+/// the fill of a road at a junction.
+pub(super) const FILL_Z_INDEX: i32 = 100;
+
 impl Object for Highway {
     fn get_paths(&self, points: &[Point]) -> Vec<Path> {
-        vec![
-            Path::new(points.iter().map(|p| p.into()).collect()).with_stroke({
-                let width = match self {
-                    Self::Motorway => 5.0,
-                    Self::Trunk | Self::Primary | Self::Secondary | Self::Tertiary => 4.0,
-                    Self::Service | Self::Residential => 2.0,
-                    Self::Footway | Self::Path => 0.75,
-                    _ => 1.0,
-                };
-                let color = match self {
-                    Self::Motorway => Vec3::new(223.0, 46.0, 107.0) / 255.0,
-                    Self::Trunk => Vec3::new(234.0, 144.0, 161.0) / 255.0,
-                    Self::Primary => Vec3::new(252.0, 192.0, 171.0) / 255.0,
-                    Self::Secondary => Vec3::new(253.0, 214.0, 1.0) / 255.0,
-                    Self::Tertiary => Vec3::new(246.0, 250.0, 187.0) / 255.0,
-                    Self::Footway | Self::Path => Vec3::new(250.0, 164.0, 156.0) / 255.0,
-                    _ => Vec3::new(169.0, 175.0, 182.0) / 255.0,
-                };
-
-                // let dash = match self {
-                //     Self::Footway | Self::Path => Some(0.05),
-                //     Self::Motorway
-                //     | Self::Trunk
-                //     | Self::Primary
-                //     | Self::Secondary
-                //     | Self::Tertiary => Some(0.1),
-                //     _ => None,
-                // };
-                //
-                // if let Some(dash) = dash {
-                //     stroke = stroke.with_dash(dash);
-                // }
-
-                Stroke::new(width, color)
-            }),
-        ]
+        let points = points.iter().map(|p| p.into()).collect::<Vec<_>>();
+
+        if *self == Self::PedestrianArea {
+            return vec![Path::new(points)
+                .with_fill(self.color())
+                .with_stroke(Stroke::new(
+                    self.width(),
+                    Vec3::new(169.0, 175.0, 182.0) / 255.0,
+                ))
+                .with_z_index(self.rank())];
+        }
+
+        let fill = {
+            let stroke = Stroke::new(self.width(), self.color())
+                .with_join(LineJoin::Round)
+                .with_cap(LineCap::Round);
+
+            let stroke = match self.dash() {
+                Some(pattern) => stroke.with_dash(pattern),
+                None => stroke,
+            };
+
+            Path::new(points.clone())
+                .with_stroke(stroke)
+                .with_z_index(FILL_Z_INDEX + self.rank())
+        };
+
+        self.casing()
+            .map(|casing| {
+                Path::new(points)
+                    .with_stroke(casing)
+                    .with_z_index(self.rank())
+            })
+            .into_iter()
+            .chain([fill])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Highway;
+    use crate::osm::Tags;
+
+    fn highway(tags: &[(&str, &str)]) -> Option<Highway> {
+        Highway::from_tags(&tags.iter().copied().collect::<Tags>())
+    }
+
+    #[test]
+    fn main_classes() {
+        for (value, expected) in [
+            ("motorway", Highway::Motorway),
+            ("trunk", Highway::Trunk),
+            ("primary", Highway::Primary),
+            ("secondary", Highway::Secondary),
+            ("tertiary", Highway::Tertiary),
+            ("unclassified", Highway::Unclassified),
+            ("residential", Highway::Residential),
+            ("living_street", Highway::LivingStreet),
+            ("pedestrian", Highway::Pedestrian),
+            ("footway", Highway::Footway),
+            ("steps", Highway::Steps),
+            ("path", Highway::Path),
+            ("cycleway", Highway::Cycleway),
+            ("bridleway", Highway::Bridleway),
+            ("construction", Highway::Construction),
+            ("proposed", Highway::Proposed),
+        ] {
+            assert_eq!(highway(&[("highway", value)]), Some(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn links() {
+        for (value, expected) in [
+            ("motorway_link", Highway::MotorwayLink),
+            ("trunk_link", Highway::TrunkLink),
+            ("primary_link", Highway::PrimaryLink),
+            ("secondary_link", Highway::SecondaryLink),
+            ("tertiary_link", Highway::TertiaryLink),
+        ] {
+            assert_eq!(highway(&[("highway", value)]), Some(expected), "{value}");
+        }
+    }
+
+    #[test]
+    fn service_and_tracks() {
+        for (tags, expected) in [
+            (&[("highway", "service")][..], Highway::Service),
+            (
+                &[("highway", "service"), ("service", "driveway")],
+                Highway::Service,
+            ),
+            (&[("highway", "track")], Highway::Track(None)),
+            (
+                &[("highway", "track"), ("tracktype", "grade1")],
+                Highway::Track(Some(1)),
+            ),
+            (
+                &[("highway", "track"), ("tracktype", "grade5")],
+                Highway::Track(Some(5)),
+            ),
+            (
+                &[("highway", "track"), ("tracktype", "grade6")],
+                Highway::Track(None),
+            ),
+            (
+                &[("highway", "track"), ("tracktype", "grade0")],
+                Highway::Track(None),
+            ),
+            (
+                &[("highway", "track"), ("tracktype", "gravel")],
+                Highway::Track(None),
+            ),
+        ] {
+            assert_eq!(highway(tags), Some(expected), "{tags:?}");
+        }
+    }
+
+    #[test]
+    fn areas() {
+        for (tags, expected) in [
+            (
+                &[("highway", "pedestrian"), ("area", "yes")][..],
+                Highway::PedestrianArea,
+            ),
+            (
+                &[("highway", "footway"), ("area", "yes")],
+                Highway::PedestrianArea,
+            ),
+            (
+                &[("highway", "pedestrian"), ("area", "no")],
+                Highway::Pedestrian,
+            ),
+            (
+                &[("highway", "residential"), ("area", "yes")],
+                Highway::Residential,
+            ),
+        ] {
+            assert_eq!(highway(tags), Some(expected), "{tags:?}");
+        }
+    }
+
+    #[test]
+    fn unknown_values() {
+        for value in ["", "road", "bus_guideway", "Primary", "primary "] {
+            assert_eq!(
+                highway(&[("highway", value)]),
+                Some(Highway::Other),
+                "{value:?}"
+            );
+        }
+
+        assert_eq!(highway(&[]), None);
+        assert_eq!(highway(&[("railway", "rail")]), None);
     }
 }
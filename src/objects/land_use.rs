@@ -0,0 +1,118 @@
+use glam::Vec3;
+use renderer::render_steps::canvas::{Path, Stroke};
+
+use super::{Object, AMENITY_Z_INDEX, LAND_COVER_Z_INDEX, LAND_USE_Z_INDEX};
+use crate::{osm::Tags, Point};
+
+// https://wiki.openstreetmap.org/wiki/Key:landuse?uselang=en-GB
+// https://wiki.openstreetmap.org/wiki/Key:natural?uselang=en-GB
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum LandUse {
+    Residential,
+    Industrial,
+    Commercial,
+    Farmland,
+    Forest,
+    Wood,
+    Scrub,
+    Grass,
+    Wetland,
+    Beach,
+    Cemetery,
+    School,
+    Hospital,
+    Parking,
+}
+
+impl LandUse {
+    pub fn from_tags(tags: &Tags) -> Option<LandUse> {
+        let amenity = tags.get("amenity").and_then(|tag| match tag.as_str() {
+            "school" | "college" | "university" | "kindergarten" => Some(LandUse::School),
+            "hospital" => Some(LandUse::Hospital),
+            "parking" => Some(LandUse::Parking),
+            "grave_yard" => Some(LandUse::Cemetery),
+            _ => None,
+        });
+
+        let natural = tags.get("natural").and_then(|tag| match tag.as_str() {
+            "wood" => Some(LandUse::Wood),
+            "scrub" | "heath" => Some(LandUse::Scrub),
+            "wetland" => Some(LandUse::Wetland),
+            "beach" | "sand" => Some(LandUse::Beach),
+            "grassland" => Some(LandUse::Grass),
+            _ => None,
+        });
+
+        let land_use = tags.get("landuse").and_then(|tag| match tag.as_str() {
+            "residential" => Some(LandUse::Residential),
+            "industrial" => Some(LandUse::Industrial),
+            "commercial" | "retail" => Some(LandUse::Commercial),
+            "farmland" | "meadow" | "orchard" => Some(LandUse::Farmland),
+            "forest" => Some(LandUse::Forest),
+            "grass" | "village_green" => Some(LandUse::Grass),
+            "cemetery" => Some(LandUse::Cemetery),
+            _ => None,
+        });
+
+        // The most specific tag wins, eg a school on residential land is drawn as a school
+        amenity.or(natural).or(land_use)
+    }
+
+    fn color(&self) -> Vec3 {
+        match self {
+            Self::Residential => Vec3::new(224.0, 223.0, 223.0) / 255.0,
+            Self::Industrial => Vec3::new(235.0, 219.0, 232.0) / 255.0,
+            Self::Commercial => Vec3::new(242.0, 218.0, 217.0) / 255.0,
+            Self::Farmland => Vec3::new(238.0, 240.0, 213.0) / 255.0,
+            Self::Forest | Self::Wood => Vec3::new(173.0, 209.0, 158.0) / 255.0,
+            Self::Scrub => Vec3::new(200.0, 215.0, 171.0) / 255.0,
+            Self::Grass => Vec3::new(205.0, 235.0, 176.0) / 255.0,
+            Self::Wetland => Vec3::new(196.0, 224.0, 214.0) / 255.0,
+            Self::Beach => Vec3::new(255.0, 241.0, 186.0) / 255.0,
+            Self::Cemetery => Vec3::new(170.0, 203.0, 175.0) / 255.0,
+            Self::School | Self::Hospital => Vec3::new(255.0, 255.0, 229.0) / 255.0,
+            Self::Parking => Vec3::new(238.0, 238.0, 238.0) / 255.0,
+        }
+    }
+
+    /// Colour of the outline for areas that need one to be told apart from their surroundings.
+    fn outline(&self) -> Option<Vec3> {
+        match self {
+            Self::School | Self::Hospital => Some(Vec3::new(212.0, 170.0, 170.0) / 255.0),
+            Self::Parking => Some(Vec3::new(210.0, 210.0, 210.0) / 255.0),
+            _ => None,
+        }
+    }
+
+    /// Broad land use is drawn first, with land cover and then amenities over it. Parking is
+    /// drawn over the other amenities, as car parks are usually within their grounds.
+    fn z_index(&self) -> i32 {
+        match self {
+            Self::Residential | Self::Industrial | Self::Commercial | Self::Farmland => {
+                LAND_USE_Z_INDEX
+            }
+            Self::Forest
+            | Self::Wood
+            | Self::Scrub
+            | Self::Grass
+            | Self::Wetland
+            | Self::Beach
+            | Self::Cemetery => LAND_COVER_Z_INDEX,
+            Self::School | Self::Hospital => AMENITY_Z_INDEX,
+            Self::Parking => AMENITY_Z_INDEX + 1,
+        }
+    }
+}
+
+impl Object for LandUse {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        let path = Path::new(points.iter().map(|p| p.into()).collect()).with_fill(self.color());
+
+        let path = match self.outline() {
+            Some(color) => path.with_stroke(Stroke::new(1.0, color)),
+            None => path,
+        };
+
+        vec![path.with_z_index(self.z_index())]
+    }
+}
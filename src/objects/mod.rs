@@ -1,15 +1,37 @@
+mod boundary;
 mod building;
 mod highway;
+mod land_use;
+mod oneway;
 mod park;
+mod poi;
 mod railway;
+mod structure;
+mod water;
 
 use crate::Point;
 
+pub use boundary::Boundary;
 pub use building::Building;
 pub use highway::Highway;
+pub use land_use::LandUse;
+pub use oneway::Oneway;
 pub use park::Park;
+pub use poi::Poi;
 pub use railway::Railway;
 use renderer::render_steps::canvas::Path;
+pub use structure::{Structure, StructureType};
+pub use water::{Water, Waterway, WATER_COLOR};
+
+// Order that areas are drawn in (within a layer), so that smaller features are drawn over the
+// larger areas that they're in. Buildings and roads are drawn over all of them.
+const LAND_USE_Z_INDEX: i32 = -40;
+const LAND_COVER_Z_INDEX: i32 = -35;
+const PARK_Z_INDEX: i32 = -30;
+const AMENITY_Z_INDEX: i32 = -25;
+pub(crate) const CONTOUR_Z_INDEX: i32 = -20;
+const WATER_Z_INDEX: i32 = -10;
+const WATERWAY_Z_INDEX: i32 = -9;
 
 pub(crate) trait Object {
     fn get_paths(&self, points: &[Point]) -> Vec<Path>;
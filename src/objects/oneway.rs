@@ -0,0 +1,100 @@
+use glam::Vec3;
+use renderer::render_steps::canvas::{Decoration, Path, Symbol};
+
+use super::{highway::FILL_Z_INDEX, Object};
+use crate::{osm::Tags, Point};
+
+/// Length (in pixels) of each oneway arrow.
+const ONEWAY_ARROW_SIZE: f32 = 10.0;
+
+/// Distance (in pixels) between oneway arrows.
+const ONEWAY_ARROW_SPACING: f32 = 80.0;
+
+const ONEWAY_ARROW_COLOR: Vec3 = Vec3::new(108.0 / 255.0, 112.0 / 255.0, 213.0 / 255.0);
+
+/// Arrows are drawn over the fill of every road, whatever its rank.
+const ONEWAY_ARROW_Z_INDEX: i32 = FILL_Z_INDEX + 100;
+
+/// Road that can only be travelled in one direction, marked with arrows pointing that way.
+pub struct Oneway {
+    /// Whether the road goes against the direction of the way
+    reversed: bool,
+    object: Box<dyn Object>,
+}
+
+impl Oneway {
+    /// Wraps an object if it's tagged as being one way (or is a roundabout, which always is).
+    // https://wiki.openstreetmap.org/wiki/Key:oneway
+    pub fn wrap(tags: &Tags, object: Box<dyn Object>) -> Box<dyn Object> {
+        let roundabout = matches!(
+            tags.get("junction").map(String::as_str),
+            Some("roundabout" | "circular")
+        );
+
+        let reversed = match tags.get("oneway").map(String::as_str) {
+            Some("yes" | "true" | "1") => false,
+            Some("-1" | "reverse") => true,
+            None if roundabout => false,
+            _ => return object,
+        };
+
+        Box::new(Oneway { reversed, object })
+    }
+}
+
+impl Object for Oneway {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        let arrows = Path::new(points.iter().map(|p| p.into()).collect())
+            .with_decoration(
+                Decoration::new(Symbol::Arrow, ONEWAY_ARROW_SIZE, ONEWAY_ARROW_COLOR)
+                    .with_spacing(ONEWAY_ARROW_SPACING)
+                    .with_reversed(self.reversed),
+            )
+            .with_z_index(ONEWAY_ARROW_Z_INDEX);
+
+        self.object
+            .get_paths(points)
+            .into_iter()
+            .chain([arrows])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use renderer::render_steps::canvas::CanvasObject;
+
+    use super::{super::Highway, Oneway};
+    use crate::{osm::Tags, Point};
+
+    #[test]
+    fn arrows_are_drawn_over_roads() {
+        let points = [Point::new(0.0, 0.0), Point::new(100.0, 0.0)];
+
+        for highway in [
+            "motorway",
+            "trunk_link",
+            "primary",
+            "residential",
+            "service",
+            "track",
+            "footway",
+            "cycleway",
+        ] {
+            let tags = Tags::from_iter([("highway", highway), ("oneway", "yes")]);
+            let object = Oneway::wrap(&tags, Box::new(Highway::from_tags(&tags).unwrap()));
+
+            let (arrows, road) = object
+                .get_paths(&points)
+                .into_iter()
+                .partition::<Vec<_>, _>(|path| path.get_decoration().is_some());
+
+            assert_eq!(arrows.len(), 1, "{highway}");
+            assert!(
+                road.iter()
+                    .all(|path| path.get_draw_order() < arrows[0].get_draw_order()),
+                "{highway} arrows should be drawn over the road"
+            );
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use glam::Vec3;
+use renderer::render_steps::icon::{Icon, Sprite};
+
+use crate::{osm::Tags, Point};
+
+// https://wiki.openstreetmap.org/wiki/Map_features
+pub enum Poi {
+    Cafe,
+    Restaurant,
+    FastFood,
+    Pub,
+    Parking,
+    Fuel,
+    ChargingStation,
+    Hospital,
+    Clinic,
+    Pharmacy,
+    School,
+    University,
+    Bank,
+    Atm,
+    Toilets,
+    PostOffice,
+    Police,
+    Library,
+    PlaceOfWorship(Religion),
+    Cinema,
+    Theatre,
+    Telephone,
+    Recycling,
+    Supermarket,
+    Bakery,
+    Shop,
+    Hotel,
+    Museum,
+    Attraction,
+    Viewpoint,
+    Information,
+    BusStop,
+    TramStop,
+    Station,
+}
+
+pub enum Religion {
+    Christian,
+    Muslim,
+    Jewish,
+    Other,
+}
+
+/// Broad groups of POIs, which share a colour
+enum Category {
+    Food,
+    Transport,
+    Health,
+    Civic,
+    Shop,
+    Tourism,
+    Other,
+}
+
+impl Poi {
+    pub fn from_tags(tags: &Tags) -> Option<Poi> {
+        let tag = |key| tags.get(key).map(|value| value.as_str());
+
+        let poi = match tag("amenity") {
+            Some("cafe") => Poi::Cafe,
+            Some("restaurant") => Poi::Restaurant,
+            Some("fast_food") => Poi::FastFood,
+            Some("pub" | "bar" | "biergarten") => Poi::Pub,
+            Some("parking") => Poi::Parking,
+            Some("fuel") => Poi::Fuel,
+            Some("charging_station") => Poi::ChargingStation,
+            Some("hospital") => Poi::Hospital,
+            Some("clinic" | "doctors") => Poi::Clinic,
+            Some("pharmacy") => Poi::Pharmacy,
+            Some("school" | "kindergarten") => Poi::School,
+            Some("university" | "college") => Poi::University,
+            Some("bank") => Poi::Bank,
+            Some("atm") => Poi::Atm,
+            Some("toilets") => Poi::Toilets,
+            Some("post_office") => Poi::PostOffice,
+            Some("police") => Poi::Police,
+            Some("library") => Poi::Library,
+            Some("place_of_worship") => Poi::PlaceOfWorship(match tag("religion") {
+                Some("christian") => Religion::Christian,
+                Some("muslim") => Religion::Muslim,
+                Some("jewish") => Religion::Jewish,
+                _ => Religion::Other,
+            }),
+            Some("cinema") => Poi::Cinema,
+            Some("theatre") => Poi::Theatre,
+            Some("telephone") => Poi::Telephone,
+            Some("recycling") => Poi::Recycling,
+            _ => match tag("shop") {
+                Some("supermarket") => Poi::Supermarket,
+                Some("bakery") => Poi::Bakery,
+                Some(_) => Poi::Shop,
+                None => match tag("tourism") {
+                    Some("hotel" | "motel" | "hostel" | "guest_house") => Poi::Hotel,
+                    Some("museum" | "gallery") => Poi::Museum,
+                    Some("attraction") => Poi::Attraction,
+                    Some("viewpoint") => Poi::Viewpoint,
+                    Some("information") => Poi::Information,
+                    _ => Self::transit_from_tags(tags)?,
+                },
+            },
+        };
+
+        Some(poi)
+    }
+
+    /// Stops and stations, tagged either in the older style (eg `highway=bus_stop`) or with the
+    /// public transport scheme, where the vehicles that call there are given separately.
+    // https://wiki.openstreetmap.org/wiki/Public_transport
+    fn transit_from_tags(tags: &Tags) -> Option<Poi> {
+        let tag = |key| tags.get(key).map(|value| value.as_str());
+
+        if tag("highway") == Some("bus_stop") {
+            return Some(Poi::BusStop);
+        }
+
+        match tag("railway") {
+            Some("tram_stop") => return Some(Poi::TramStop),
+            Some("station" | "halt") => return Some(Poi::Station),
+            _ => (),
+        }
+
+        match tag("public_transport") {
+            Some("station") => Some(Poi::Station),
+            Some("stop_position" | "platform") => {
+                if tag("train") == Some("yes") || tag("subway") == Some("yes") {
+                    Some(Poi::Station)
+                } else if tag("tram") == Some("yes") {
+                    Some(Poi::TramStop)
+                } else {
+                    Some(Poi::BusStop)
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Unique name for the sprite used to draw this POI.
+    pub fn sprite_name(&self) -> &'static str {
+        match self {
+            Self::Cafe => "cafe",
+            Self::Restaurant => "restaurant",
+            Self::FastFood => "fast_food",
+            Self::Pub => "pub",
+            Self::Parking => "parking",
+            Self::Fuel => "fuel",
+            Self::ChargingStation => "charging_station",
+            Self::Hospital => "hospital",
+            Self::Clinic => "clinic",
+            Self::Pharmacy => "pharmacy",
+            Self::School => "school",
+            Self::University => "university",
+            Self::Bank => "bank",
+            Self::Atm => "atm",
+            Self::Toilets => "toilets",
+            Self::PostOffice => "post_office",
+            Self::Police => "police",
+            Self::Library => "library",
+            Self::PlaceOfWorship(Religion::Christian) => "place_of_worship_christian",
+            Self::PlaceOfWorship(Religion::Muslim) => "place_of_worship_muslim",
+            Self::PlaceOfWorship(Religion::Jewish) => "place_of_worship_jewish",
+            Self::PlaceOfWorship(Religion::Other) => "place_of_worship",
+            Self::Cinema => "cinema",
+            Self::Theatre => "theatre",
+            Self::Telephone => "telephone",
+            Self::Recycling => "recycling",
+            Self::Supermarket => "supermarket",
+            Self::Bakery => "bakery",
+            Self::Shop => "shop",
+            Self::Hotel => "hotel",
+            Self::Museum => "museum",
+            Self::Attraction => "attraction",
+            Self::Viewpoint => "viewpoint",
+            Self::Information => "information",
+            Self::BusStop => "bus_stop",
+            Self::TramStop => "tram_stop",
+            Self::Station => "station",
+        }
+    }
+
+    fn category(&self) -> Category {
+        match self {
+            Self::Cafe | Self::Restaurant | Self::FastFood | Self::Pub => Category::Food,
+            Self::Parking
+            | Self::Fuel
+            | Self::ChargingStation
+            | Self::BusStop
+            | Self::TramStop
+            | Self::Station => Category::Transport,
+            Self::Hospital | Self::Clinic | Self::Pharmacy => Category::Health,
+            Self::School
+            | Self::University
+            | Self::PostOffice
+            | Self::Police
+            | Self::Library
+            | Self::PlaceOfWorship(_)
+            | Self::Telephone => Category::Civic,
+            Self::Supermarket | Self::Bakery | Self::Shop | Self::Bank | Self::Atm => {
+                Category::Shop
+            }
+            Self::Hotel
+            | Self::Museum
+            | Self::Attraction
+            | Self::Viewpoint
+            | Self::Information
+            | Self::Cinema
+            | Self::Theatre => Category::Tourism,
+            Self::Toilets | Self::Recycling => Category::Other,
+        }
+    }
+
+    /// Symbol drawn in the middle of the badge (limited to characters available in the bundled
+    /// font).
+    fn symbol(&self) -> &'static str {
+        match self {
+            Self::Cafe => "☕",
+            Self::Restaurant => "R",
+            Self::FastFood => "F",
+            Self::Pub => "P",
+            Self::Parking => "P",
+            Self::Fuel => "F",
+            Self::ChargingStation => "⚡",
+            Self::Hospital => "✚",
+            Self::Clinic => "✚",
+            Self::Pharmacy => "⚕",
+            Self::School => "S",
+            Self::University => "U",
+            Self::Bank => "$",
+            Self::Atm => "€",
+            Self::Toilets => "WC",
+            Self::PostOffice => "✉",
+            Self::Police => "★",
+            Self::Library => "L",
+            Self::PlaceOfWorship(Religion::Christian) => "✝",
+            Self::PlaceOfWorship(Religion::Muslim) => "☪",
+            Self::PlaceOfWorship(Religion::Jewish) => "✡",
+            Self::PlaceOfWorship(Religion::Other) => "⚑",
+            Self::Cinema => "C",
+            Self::Theatre => "T",
+            Self::Telephone => "☎",
+            Self::Recycling => "♻",
+            Self::Supermarket => "S",
+            Self::Bakery => "B",
+            Self::Shop => "✂",
+            Self::Hotel => "H",
+            Self::Museum => "M",
+            Self::Attraction => "★",
+            Self::Viewpoint => "V",
+            Self::Information => "i",
+            Self::BusStop => "B",
+            Self::TramStop => "T",
+            Self::Station => "⌂",
+        }
+    }
+
+    pub fn get_sprite(&self) -> Sprite {
+        let color = match self.category() {
+            Category::Food => Vec3::new(199.0, 116.0, 0.0),
+            Category::Transport => Vec3::new(0.0, 146.0, 218.0),
+            Category::Health => Vec3::new(191.0, 0.0, 0.0),
+            Category::Civic => Vec3::new(115.0, 74.0, 8.0),
+            Category::Shop => Vec3::new(172.0, 57.0, 172.0),
+            Category::Tourism => Vec3::new(0.0, 128.0, 128.0),
+            Category::Other => Vec3::new(102.0, 102.0, 102.0),
+        } / 255.0;
+
+        Sprite::badge(self.symbol(), color)
+    }
+
+    /// Icon for the POI, with more important places (eg transport and health) taking priority
+    /// when icons collide.
+    pub fn get_icon(&self, point: &Point) -> Icon {
+        let priority = match self.category() {
+            Category::Transport | Category::Health => 20,
+            Category::Civic | Category::Tourism => 15,
+            Category::Food | Category::Shop => 10,
+            Category::Other => 5,
+        };
+
+        Icon::new(point.into(), self.sprite_name()).with_priority(priority)
+    }
+}
@@ -1,13 +1,114 @@
 use super::Object;
-use crate::Point;
+use crate::{osm::Tags, Point};
 use glam::Vec3;
-use renderer::render_steps::canvas::{Path, Stroke};
+use renderer::render_steps::canvas::{LineCap, LineJoin, Path, Stroke};
 
-pub struct Railway;
+/// Railways are drawn over roads on the same layer, as the tracks cross over the road at level
+/// crossings.
+const RAILWAY_Z_INDEX: i32 = 200;
+
+// https://wiki.openstreetmap.org/wiki/Key:railway?uselang=en-GB
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RailwayType {
+    Rail,
+    LightRail,
+    Subway,
+    Tram,
+    NarrowGauge,
+
+    /// Track that's no longer used, or has been removed altogether
+    Disused,
+
+    /// Drawn as an area if the way is closed, otherwise as the edge of the platform
+    Platform,
+    Other,
+}
+
+pub struct Railway {
+    ty: RailwayType,
+}
+
+impl Railway {
+    pub fn from_tags(tags: &Tags) -> Option<Railway> {
+        let ty = tags.get("railway").map(|tag| match tag.as_str() {
+            "rail" | "preserved" => RailwayType::Rail,
+            "light_rail" => RailwayType::LightRail,
+            "subway" => RailwayType::Subway,
+            "tram" => RailwayType::Tram,
+            "narrow_gauge" => RailwayType::NarrowGauge,
+            "disused" | "abandoned" | "razed" => RailwayType::Disused,
+            "platform" => RailwayType::Platform,
+            _ => RailwayType::Other,
+        })?;
+
+        Some(Railway { ty })
+    }
+
+    /// Width (in pixels) of the track.
+    fn width(&self) -> f32 {
+        match self.ty {
+            RailwayType::Rail => 3.0,
+            RailwayType::LightRail | RailwayType::Subway | RailwayType::NarrowGauge => 2.0,
+            RailwayType::Platform => 3.0,
+            _ => 1.5,
+        }
+    }
+
+    fn color(&self) -> Vec3 {
+        match self.ty {
+            RailwayType::Rail => Vec3::new(112.0, 112.0, 112.0) / 255.0,
+            RailwayType::LightRail | RailwayType::NarrowGauge => {
+                Vec3::new(102.0, 102.0, 102.0) / 255.0
+            }
+            RailwayType::Subway => Vec3::new(153.0, 153.0, 153.0) / 255.0,
+            RailwayType::Tram => Vec3::new(68.0, 68.0, 68.0) / 255.0,
+            RailwayType::Disused => Vec3::new(170.0, 170.0, 170.0) / 255.0,
+            RailwayType::Platform => Vec3::new(187.0, 187.0, 187.0) / 255.0,
+            RailwayType::Other => Vec3::new(150.0, 150.0, 150.0) / 255.0,
+        }
+    }
+
+    /// White dashes drawn along the middle of the track, giving the classic alternating pattern of
+    /// main line railways.
+    fn inner_dashes(&self) -> Option<Stroke> {
+        let (width, pattern): (f32, &[f32]) = match self.ty {
+            RailwayType::Rail => (1.5, &[8.0, 8.0]),
+            RailwayType::NarrowGauge => (1.0, &[6.0, 6.0]),
+            _ => return None,
+        };
+
+        Some(Stroke::new(width, Vec3::ONE).with_dash(pattern))
+    }
+}
 
 impl Object for Railway {
     fn get_paths(&self, points: &[Point]) -> Vec<Path> {
-        vec![Path::new(points.iter().map(|p| p.into()).collect())
-            .with_stroke(Stroke::new(0.1, Vec3::new(164.0, 214.0, 255.0) / 255.0))]
+        let points = points.iter().map(|p| p.into()).collect::<Vec<_>>();
+
+        if self.ty == RailwayType::Platform && points.len() > 2 && points.first() == points.last() {
+            return vec![Path::new(points)
+                .with_fill(self.color())
+                .with_stroke(Stroke::new(1.0, Vec3::new(136.0, 136.0, 136.0) / 255.0))];
+        }
+
+        let stroke = Stroke::new(self.width(), self.color()).with_join(LineJoin::Round);
+        let stroke = match self.ty {
+            RailwayType::Disused => stroke.with_dash(&[3.0, 3.0]),
+            RailwayType::Platform => stroke.with_cap(LineCap::Round),
+            _ => stroke,
+        };
+
+        let track = Path::new(points.clone())
+            .with_stroke(stroke)
+            .with_z_index(RAILWAY_Z_INDEX);
+
+        [track]
+            .into_iter()
+            .chain(self.inner_dashes().map(|dashes| {
+                Path::new(points)
+                    .with_stroke(dashes.with_join(LineJoin::Round))
+                    .with_z_index(RAILWAY_Z_INDEX + 1)
+            }))
+            .collect()
     }
 }
@@ -0,0 +1,134 @@
+use glam::Vec3;
+use renderer::render_steps::canvas::{CanvasObject, Path, Stroke};
+
+use super::Object;
+use crate::{osm::Tags, Point};
+
+/// Bridge casings are drawn under everything else on the bridge's layer, so that they only show
+/// around the edges.
+const BRIDGE_CASING_Z_INDEX: i32 = -1000;
+
+/// Width (in pixels) of the casing either side of the widest line on a bridge.
+const BRIDGE_CASING_WIDTH: f32 = 2.0;
+
+const BRIDGE_CASING_COLOR: Vec3 = Vec3::new(0.3, 0.3, 0.3);
+
+/// Dash pattern (in pixels) of the outline of lines through tunnels.
+const TUNNEL_DASH: [f32; 2] = [6.0, 4.0];
+
+/// How far (between 0 and 1) the colours of lines under ground are faded towards white.
+const TUNNEL_FADE: f32 = 0.5;
+
+// https://wiki.openstreetmap.org/wiki/Key:bridge?uselang=en-GB
+// https://wiki.openstreetmap.org/wiki/Key:tunnel?uselang=en-GB
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StructureType {
+    Bridge,
+    Tunnel,
+
+    /// Passes under a building or roof, without being under ground
+    Covered,
+}
+
+impl StructureType {
+    pub fn from_tags(tags: &Tags) -> Option<StructureType> {
+        let is_set = |key| tags.get(key).filter(|&value| value != "no").is_some();
+
+        if is_set("bridge") {
+            Some(StructureType::Bridge)
+        } else if tags
+            .get("tunnel")
+            .is_some_and(|value| value == "building_passage")
+        {
+            // Passes through a building at ground level
+            Some(StructureType::Covered)
+        } else if is_set("tunnel") {
+            Some(StructureType::Tunnel)
+        } else if is_set("covered") {
+            Some(StructureType::Covered)
+        } else {
+            None
+        }
+    }
+
+    /// Layer that ways on the structure are on, unless they have a `layer` tag.
+    pub fn layer(&self) -> i32 {
+        match self {
+            Self::Bridge => 1,
+            Self::Tunnel => -1,
+            Self::Covered => 0,
+        }
+    }
+}
+
+/// Line (such as a road, railway or waterway) carried over a bridge or through a tunnel.
+///
+/// Bridges get a dark casing around the line, whilst lines under ground (or under cover) are faded.
+/// The widest line through a tunnel is also dashed, so that for roads only the casing is.
+pub struct Structure {
+    ty: StructureType,
+    object: Box<dyn Object>,
+}
+
+impl Structure {
+    /// Wraps an object if it's tagged as being on a structure.
+    pub fn wrap(tags: &Tags, object: Box<dyn Object>) -> Box<dyn Object> {
+        match StructureType::from_tags(tags) {
+            Some(ty) => Box::new(Structure { ty, object }),
+            None => object,
+        }
+    }
+}
+
+impl Object for Structure {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        let paths = self.object.get_paths(points);
+
+        let widest = paths
+            .iter()
+            .filter_map(|path| path.get_stroke())
+            .map(|stroke| stroke.width())
+            .fold(0.0, f32::max);
+
+        match self.ty {
+            StructureType::Bridge if widest > 0.0 => {
+                let casing = Path::new(points.iter().map(|p| p.into()).collect())
+                    .with_stroke(Stroke::new(
+                        widest + 2.0 * BRIDGE_CASING_WIDTH,
+                        BRIDGE_CASING_COLOR,
+                    ))
+                    .with_z_index(BRIDGE_CASING_Z_INDEX);
+
+                [casing].into_iter().chain(paths).collect()
+            }
+            StructureType::Bridge => paths,
+            StructureType::Tunnel | StructureType::Covered => {
+                let mut dashed = false;
+
+                paths
+                    .into_iter()
+                    .map(|path| {
+                        let Some(stroke) = path.get_stroke() else {
+                            return path;
+                        };
+
+                        let faded = stroke.color().lerp(Vec3::ONE, TUNNEL_FADE);
+                        let stroke = stroke.with_color(faded);
+                        let stroke = if self.ty == StructureType::Tunnel
+                            && !dashed
+                            && stroke.width() == widest
+                            && !stroke.is_dashed()
+                        {
+                            dashed = true;
+                            stroke.with_dash(&TUNNEL_DASH)
+                        } else {
+                            stroke
+                        };
+
+                        path.with_stroke(stroke)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
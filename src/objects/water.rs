@@ -0,0 +1,95 @@
+use glam::Vec3;
+use renderer::render_steps::canvas::{Decoration, LineCap, LineJoin, Path, Stroke, Symbol};
+
+use super::{Object, WATERWAY_Z_INDEX, WATER_Z_INDEX};
+use crate::{osm::Tags, Point};
+
+pub const WATER_COLOR: Vec3 = Vec3::new(170.0 / 255.0, 211.0 / 255.0, 223.0 / 255.0);
+
+/// Length (in pixels) of the chevrons showing which way a waterway flows.
+const FLOW_CHEVRON_SIZE: f32 = 6.0;
+
+/// Distance (in pixels) between flow chevrons, which are spread out so as not to clutter the map.
+const FLOW_CHEVRON_SPACING: f32 = 200.0;
+
+/// Lake, pond, reservoir or the area of a wide river.
+pub struct Water;
+
+impl Water {
+    pub fn is_water(tags: &Tags) -> bool {
+        let is = |key, values: &[&str]| {
+            tags.get(key)
+                .filter(|value| values.contains(&value.as_str()))
+                .is_some()
+        };
+
+        is("natural", &["water"])
+            || is("waterway", &["riverbank", "dock"])
+            || is("landuse", &["reservoir", "basin"])
+    }
+}
+
+impl Object for Water {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        vec![Path::new(points.iter().map(|p| p.into()).collect())
+            .with_fill(WATER_COLOR)
+            .with_z_index(WATER_Z_INDEX)]
+    }
+}
+
+// https://wiki.openstreetmap.org/wiki/Key:waterway?uselang=en-GB
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Waterway {
+    River,
+    Canal,
+    Stream,
+    Drain,
+    Ditch,
+}
+
+impl Waterway {
+    pub fn from_tags(tags: &Tags) -> Option<Waterway> {
+        tags.get("waterway").and_then(|tag| match tag.as_str() {
+            "river" => Some(Waterway::River),
+            "canal" => Some(Waterway::Canal),
+            "stream" | "brook" => Some(Waterway::Stream),
+            "drain" => Some(Waterway::Drain),
+            "ditch" => Some(Waterway::Ditch),
+            _ => None,
+        })
+    }
+
+    /// Width (in pixels) of the waterway's line.
+    fn width(&self) -> f32 {
+        match self {
+            Self::River => 4.0,
+            Self::Canal => 3.5,
+            Self::Stream => 1.5,
+            Self::Drain | Self::Ditch => 1.0,
+        }
+    }
+}
+
+impl Object for Waterway {
+    fn get_paths(&self, points: &[Point]) -> Vec<Path> {
+        let path = Path::new(points.iter().map(|p| p.into()).collect())
+            .with_stroke(
+                Stroke::new(self.width(), WATER_COLOR)
+                    .with_join(LineJoin::Round)
+                    .with_cap(LineCap::Round),
+            )
+            .with_z_index(WATERWAY_Z_INDEX);
+
+        // Canals are often level, without a direction of flow
+        if *self == Self::Canal {
+            return vec![path];
+        }
+
+        // Ways are drawn in the direction that the water flows
+        // https://wiki.openstreetmap.org/wiki/Tag:waterway%3Driver
+        vec![path.with_decoration(
+            Decoration::new(Symbol::Chevron, FLOW_CHEVRON_SIZE, WATER_COLOR * 0.7)
+                .with_spacing(FLOW_CHEVRON_SPACING),
+        )]
+    }
+}
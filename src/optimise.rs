@@ -0,0 +1,168 @@
+use std::{error::Error, path::Path};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::{
+    export::{self, LonLat},
+    osm::{Node, Osm},
+    path_finder::{instructions, order_stops, Network},
+};
+
+/// Row of the stops CSV file.
+#[derive(Deserialize)]
+struct Stop {
+    #[serde(default)]
+    name: String,
+
+    #[serde(alias = "latitude")]
+    lat: f64,
+
+    #[serde(alias = "lng", alias = "longitude")]
+    lon: f64,
+}
+
+/// Orders the stops in a CSV file (with `name`, `lat` and `lon` columns, starting from the depot)
+/// to minimise the total travel time, writing the ordered route to a GeoJSON or GPX file
+/// depending on its extension.
+pub fn run(
+    data: &Osm,
+    network: &Network,
+    stops_file: &Path,
+    output: &Path,
+    round_trip: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut stops = csv::Reader::from_path(stops_file)?
+        .deserialize()
+        .collect::<Result<Vec<Stop>, _>>()?;
+
+    if stops.is_empty() {
+        return Err("no stops to visit".into());
+    }
+
+    for (i, stop) in stops.iter_mut().enumerate() {
+        if stop.name.is_empty() {
+            stop.name = format!("Stop {}", i + 1);
+        }
+    }
+
+    // Start and end routes from the closest point on the network to each stop
+    let nodes = stops
+        .iter()
+        .map(|stop| {
+            let point = Node::from_lon_lat(stop.lon, stop.lat);
+
+            network
+                .nearest_node(data, point.x, point.y)
+                .ok_or("there are no roads to route along")
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // A single stop is already in order, with nowhere else to go
+    let order = if nodes.len() == 1 {
+        vec![0]
+    } else {
+        order_stops(&network.matrix(&nodes, &nodes), round_trip)
+    };
+
+    let mut legs = order
+        .windows(2)
+        .map(|leg| (leg[0], leg[1]))
+        .collect::<Vec<_>>();
+    if round_trip && order.len() > 1 {
+        legs.push((order[order.len() - 1], order[0]));
+    }
+
+    let lon_lat = |node: i64| data.nodes[&node].lon_lat();
+
+    let mut track = vec![lon_lat(nodes[0])];
+    let (mut distance, mut time) = (0.0, 0.0);
+
+    // Arrival time (in seconds) at each stop, in the order they're visited
+    let mut arrivals = vec![(order[0], 0.0)];
+
+    // Turn-by-turn directions for each leg
+    let mut directions = Vec::new();
+
+    for (from, to) in legs {
+        let route = network
+            .route(data, nodes[from], nodes[to])
+            .ok_or_else(|| format!("no route from {} to {}", stops[from].name, stops[to].name))?;
+
+        distance += route.distance;
+        time += route.time;
+        arrivals.push((to, time));
+
+        directions.push(
+            instructions(data, network, &route)
+                .into_iter()
+                .map(|instruction| {
+                    json!({
+                        "manoeuvre": instruction.manoeuvre,
+                        "street": instruction.street,
+                        "distance": instruction.distance,
+                        "position": <[f64; 2]>::from(lon_lat(instruction.node)),
+                        "text": instruction.to_string(),
+                    })
+                })
+                .collect::<Vec<_>>(),
+        );
+
+        track.extend(network.route_nodes(&route).into_iter().skip(1).map(lon_lat));
+    }
+
+    println!(
+        "Ordered {} stops: {:.2} km, {:.0} min",
+        stops.len(),
+        distance / 1000.0,
+        time / 60.0
+    );
+    for (position, &(stop, arrival)) in arrivals.iter().enumerate() {
+        println!(
+            "{:>3}. {} (+{:.0} min)",
+            position + 1,
+            stops[stop].name,
+            arrival / 60.0
+        );
+    }
+
+    let stop_position = |stop: usize| -> LonLat { (stops[stop].lon, stops[stop].lat) };
+
+    match output.extension().and_then(|extension| extension.to_str()) {
+        Some("geojson" | "json") => export::write_geojson(
+            output,
+            arrivals
+                .iter()
+                .enumerate()
+                .map(|(position, &(stop, arrival))| {
+                    export::geojson_point(
+                        stop_position(stop),
+                        json!({
+                            "name": stops[stop].name,
+                            "order": position + 1,
+                            "arrival": arrival,
+                        }),
+                    )
+                })
+                // Lines need at least two points, which a single stop doesn't have
+                .chain((track.len() > 1).then(|| {
+                    export::geojson_line(
+                        &track,
+                        json!({ "distance": distance, "time": time, "directions": directions }),
+                    )
+                }))
+                .collect(),
+        )?,
+        Some("gpx") => export::write_gpx(
+            output,
+            &arrivals
+                .iter()
+                .map(|&(stop, _)| (stops[stop].name.clone(), stop_position(stop)))
+                .collect::<Vec<_>>(),
+            &track,
+        )?,
+        _ => return Err("output should be a .geojson or .gpx file".into()),
+    }
+
+    Ok(())
+}
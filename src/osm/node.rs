@@ -1,6 +1,7 @@
 use std::f64::consts::PI;
 
 use super::Tags;
+use crate::objects::Poi;
 
 const WGS84_A: f64 = 6378137.0;
 
@@ -11,7 +12,6 @@ pub struct Node {
 }
 
 impl Node {
-    #[allow(dead_code)]
     pub fn new(x: f64, y: f64) -> Node {
         Node {
             x,
@@ -34,6 +34,34 @@ impl Node {
             tags: Tags::new(),
         }
     }
+
+    /// Converts the projected position back to longitude and latitude (in degrees).
+    pub fn lon_lat(&self) -> (f64, f64) {
+        let lon = (self.x / WGS84_A) - (2.0 * PI);
+        let lat = (2.0 * f64::atan(f64::exp(self.y / WGS84_A))) - (PI / 2.0);
+
+        (lon * 180.0 / PI, lat * 180.0 / PI)
+    }
+
+    /// Great circle distance (in metres) to another node.
+    pub fn distance(&self, other: &Node) -> f64 {
+        let (lon_a, lat_a) = self.lon_lat();
+        let (lon_b, lat_b) = other.lon_lat();
+
+        let (lat_a, lat_b) = (lat_a.to_radians(), lat_b.to_radians());
+        let d_lat = lat_b - lat_a;
+        let d_lon = (lon_b - lon_a).to_radians();
+
+        // Haversine formula
+        let a =
+            (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_lon / 2.0).sin().powi(2);
+
+        2.0 * WGS84_A * a.sqrt().asin()
+    }
+
+    pub(crate) fn to_poi(&self) -> Option<Poi> {
+        Poi::from_tags(&self.tags)
+    }
 }
 
 impl From<osmpbf::Node<'_>> for Node {
@@ -17,6 +17,16 @@ impl Tags {
     }
 }
 
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Tags {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(tags: I) -> Self {
+        Self(
+            tags.into_iter()
+                .map(|(key, value)| (key.into(), value.into()))
+                .collect(),
+        )
+    }
+}
+
 impl From<TagIter<'_>> for Tags {
     fn from(tags: TagIter<'_>) -> Self {
         Self(
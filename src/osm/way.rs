@@ -9,7 +9,17 @@ pub struct Way {
 impl Way {
     pub(crate) fn to_object(&self) -> Option<Box<dyn Object>> {
         if self.tags.contains("highway") {
-            return Some(Box::new(Highway::from_tags(&self.tags).unwrap()));
+            let highway = Box::new(Highway::from_tags(&self.tags).unwrap());
+
+            return Some(Structure::wrap(
+                &self.tags,
+                Oneway::wrap(&self.tags, highway),
+            ));
+        } else if Water::is_water(&self.tags) {
+            // Before waterways, as riverbanks are areas rather than lines
+            return Some(Box::new(Water));
+        } else if let Some(waterway) = Waterway::from_tags(&self.tags) {
+            return Some(Structure::wrap(&self.tags, Box::new(waterway)));
         } else if self
             .tags
             .get("leisure")
@@ -17,14 +27,42 @@ impl Way {
             .is_some()
         {
             return Some(Box::new(Park));
-        } else if self.tags.contains("railway") {
-            return Some(Box::new(Railway));
+        } else if let Some(railway) = Railway::from_tags(&self.tags) {
+            return Some(Structure::wrap(&self.tags, Box::new(railway)));
         } else if self.tags.contains("building") {
-            return Some(Box::new(Building));
+            // Drawn in 3D by `MapBuildings`, rather than as an area on the map
+            return None;
+        } else if let Some(land_use) = LandUse::from_tags(&self.tags) {
+            return Some(Box::new(land_use));
+        } else if self
+            .tags
+            .get("boundary")
+            .filter(|&ty| ty == "administrative")
+            .is_some()
+        {
+            return Some(Box::new(Boundary));
         }
 
         None
     }
+
+    /// Vertical layer of the way, from the `layer` tag or otherwise whether it's a bridge or tunnel.
+    /// Tunnels are always below ground level, even if they're tagged with a higher layer.
+    pub fn layer(&self) -> i32 {
+        let structure = StructureType::from_tags(&self.tags);
+
+        let layer = self
+            .tags
+            .get("layer")
+            .and_then(|layer| layer.parse().ok())
+            .or_else(|| structure.map(|structure| structure.layer()))
+            .unwrap_or_default();
+
+        match structure {
+            Some(StructureType::Tunnel) => layer.min(StructureType::Tunnel.layer()),
+            _ => layer,
+        }
+    }
 }
 
 impl From<osmpbf::Way<'_>> for Way {
@@ -0,0 +1,298 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use glam::Vec3;
+use renderer::render_steps::canvas::{LineCap, LineJoin, Path, Stroke};
+use serde_json::{json, Value};
+
+use crate::{export, osm::Osm};
+
+use super::{index::EdgeIndex, marker, EdgeId, Network};
+
+/// Distance (in metres) from another road that a dead end is considered to be a near miss.
+const NEAR_MISS_DISTANCE: f64 = 10.0;
+
+const COMPONENT_COLOR: Vec3 = Vec3::new(0.85, 0.2, 0.75);
+const DEAD_END_COLOR: Vec3 = Vec3::new(0.95, 0.6, 0.1);
+const NEAR_MISS_COLOR: Vec3 = Vec3::new(0.9, 0.1, 0.1);
+const ONEWAY_TRAP_COLOR: Vec3 = Vec3::new(0.45, 0.2, 0.9);
+
+/// Dead end that comes close to another road without joining it.
+pub(crate) struct NearMiss {
+    pub node: i64,
+
+    /// Closest edge to the dead end
+    pub edge: EdgeId,
+
+    /// Distance in metres to the closest edge
+    pub distance: f64,
+}
+
+/// Problems with how the roads of a network connect, which are usually mistakes in the map data.
+pub(crate) struct Analysis {
+    /// Nodes of each part of the network that's connected (ignoring the direction of travel),
+    /// largest first. Everything besides the first is disconnected from the main network.
+    pub components: Vec<Vec<i64>>,
+
+    /// Nodes that only lead to one other node
+    pub dead_ends: Vec<i64>,
+
+    pub near_misses: Vec<NearMiss>,
+
+    /// Nodes that can be travelled into, but not out of
+    pub oneway_traps: Vec<i64>,
+
+    /// Nodes that lead to three or more other nodes
+    pub intersections: usize,
+
+    edges: usize,
+}
+
+impl Analysis {
+    pub fn new(data: &Osm, network: &Network) -> Self {
+        // Nodes that each node is joined to, in either direction
+        let mut neighbours = HashMap::<i64, HashSet<i64>>::new();
+        for edge in &network.edges {
+            neighbours.entry(edge.from).or_default();
+            neighbours.entry(edge.to).or_default();
+
+            if edge.from != edge.to {
+                neighbours.entry(edge.from).or_default().insert(edge.to);
+                neighbours.entry(edge.to).or_default().insert(edge.from);
+            }
+        }
+
+        // Sorted, so that the results are the same every time
+        let mut nodes = neighbours.keys().copied().collect::<Vec<_>>();
+        nodes.sort_unstable();
+
+        let mut components = Vec::<Vec<i64>>::new();
+        let mut visited = HashSet::new();
+        for &node in &nodes {
+            if !visited.insert(node) {
+                continue;
+            }
+
+            let mut component = vec![node];
+            let mut i = 0;
+            while i < component.len() {
+                for &neighbour in &neighbours[&component[i]] {
+                    if visited.insert(neighbour) {
+                        component.push(neighbour);
+                    }
+                }
+
+                i += 1;
+            }
+
+            components.push(component);
+        }
+        components.sort_by_key(|component| std::cmp::Reverse(component.len()));
+
+        let dead_ends = nodes
+            .iter()
+            .copied()
+            .filter(|node| neighbours[node].len() == 1)
+            .collect::<Vec<_>>();
+
+        let index = EdgeIndex::new(data, network);
+        let near_misses = dead_ends
+            .iter()
+            .filter_map(|&node| {
+                let position = data.nodes.get(&node)?;
+
+                // Roads at the other end of a short dead end are close, but already joined to it
+                let joined = |id: EdgeId| {
+                    let edge = network.edge(id);
+
+                    [edge.from, edge.to]
+                        .iter()
+                        .any(|end| *end == node || neighbours[&node].contains(end))
+                };
+
+                let closest = index
+                    .nearby(position, NEAR_MISS_DISTANCE)
+                    .into_iter()
+                    .find(|projection| !joined(projection.edge))?;
+
+                Some(NearMiss {
+                    node,
+                    edge: closest.edge,
+                    distance: closest.distance,
+                })
+            })
+            .collect();
+
+        let oneway_traps = nodes
+            .iter()
+            .copied()
+            .filter(|&node| network.adjacent(node).next().is_none())
+            .collect();
+
+        Self {
+            intersections: nodes
+                .iter()
+                .filter(|node| neighbours[node].len() >= 3)
+                .count(),
+            edges: network.edges.len(),
+            components,
+            dead_ends,
+            near_misses,
+            oneway_traps,
+        }
+    }
+
+    /// Edges of the parts of the network that are disconnected from the main part, with the index
+    /// of the component they're in.
+    fn disconnected_edges(&self, network: &Network) -> Vec<(EdgeId, usize)> {
+        let components = self
+            .components
+            .iter()
+            .enumerate()
+            .skip(1)
+            .flat_map(|(i, component)| component.iter().map(move |&node| (node, i)))
+            .collect::<HashMap<_, _>>();
+
+        (0..network.edges.len())
+            .filter_map(|id| Some((id, *components.get(&network.edge(id).from)?)))
+            .collect()
+    }
+
+    /// Problems drawn over the map, colour coded by the kind of problem.
+    pub fn paths(
+        &self,
+        network: &Network,
+        node_position: impl Fn(i64) -> Option<Vec3>,
+    ) -> Vec<Path> {
+        let mut paths = Vec::new();
+
+        for (id, _) in self.disconnected_edges(network) {
+            let Some(points) = network
+                .edge(id)
+                .nodes
+                .iter()
+                .map(|&node| node_position(node))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            paths.push(
+                Path::new(points)
+                    .with_stroke(
+                        Stroke::new(5.0, COMPONENT_COLOR)
+                            .with_join(LineJoin::Round)
+                            .with_cap(LineCap::Round),
+                    )
+                    .with_z_index(0),
+            );
+        }
+
+        let markers = self
+            .dead_ends
+            .iter()
+            .map(|&node| (node, DEAD_END_COLOR))
+            .chain(
+                self.oneway_traps
+                    .iter()
+                    .map(|&node| (node, ONEWAY_TRAP_COLOR)),
+            )
+            .chain(
+                self.near_misses
+                    .iter()
+                    .map(|near_miss| (near_miss.node, NEAR_MISS_COLOR)),
+            );
+
+        for (node, color) in markers {
+            if let Some(position) = node_position(node) {
+                paths.extend(marker(position, color));
+            }
+        }
+
+        paths
+    }
+
+    /// Problems as GeoJSON features, with the OSM ids involved so that they can be fixed.
+    pub fn features(&self, data: &Osm, network: &Network) -> Vec<Value> {
+        let lon_lat = |node: &i64| data.nodes.get(node).map(|node| node.lon_lat());
+        let mut features = Vec::new();
+
+        // Both directions of a two way road are the same line
+        let mut drawn = HashSet::new();
+        for (id, component) in self.disconnected_edges(network) {
+            let edge = network.edge(id);
+            if !drawn.insert((edge.way, edge.from.min(edge.to), edge.from.max(edge.to))) {
+                continue;
+            }
+
+            let Some(points) = edge.nodes.iter().map(lon_lat).collect::<Option<Vec<_>>>() else {
+                continue;
+            };
+
+            features.push(export::geojson_line(
+                &points,
+                json!({
+                    "issue": "disconnected",
+                    "way": edge.way,
+                    "component": component,
+                    "component_nodes": self.components[component].len(),
+                }),
+            ));
+        }
+
+        let mut point = |node: i64, properties: Value| {
+            if let Some(position) = lon_lat(&node) {
+                features.push(export::geojson_point(position, properties));
+            }
+        };
+
+        for &node in &self.dead_ends {
+            point(node, json!({ "issue": "dead_end", "node": node }));
+        }
+
+        for near_miss in &self.near_misses {
+            point(
+                near_miss.node,
+                json!({
+                    "issue": "near_miss",
+                    "node": near_miss.node,
+                    "way": network.edge(near_miss.edge).way,
+                    "distance": near_miss.distance,
+                }),
+            );
+        }
+
+        for &node in &self.oneway_traps {
+            point(node, json!({ "issue": "oneway_trap", "node": node }));
+        }
+
+        features
+    }
+}
+
+impl Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let nodes = self.components.iter().map(Vec::len).sum::<usize>();
+
+        writeln!(
+            f,
+            "Network: {nodes} nodes, {} edges, {} intersections",
+            self.edges, self.intersections
+        )?;
+        writeln!(
+            f,
+            "  {} connected components (largest has {} nodes)",
+            self.components.len(),
+            self.components.first().map(Vec::len).unwrap_or_default()
+        )?;
+        writeln!(f, "  {} dead ends", self.dead_ends.len())?;
+        writeln!(
+            f,
+            "  {} near misses (dead ends within {NEAR_MISS_DISTANCE} m of another road)",
+            self.near_misses.len()
+        )?;
+        write!(f, "  {} oneway traps", self.oneway_traps.len())
+    }
+}
@@ -0,0 +1,1039 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    error::Error,
+    fmt::Display,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use super::{restriction::EdgeState, Candidate, EdgeId, Network};
+
+/// Identifies files containing a contraction hierarchy, followed by the format version.
+const MAGIC: &[u8; 4] = b"MRCH";
+const VERSION: u32 = 2;
+
+/// Starting value and multiplier of the FNV-1a hash.
+// http://www.isthe.com/chongo/tech/comp/fnv/index.html
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Marks arcs that aren't shortcuts.
+const NO_MIDDLE: u32 = u32::MAX;
+
+/// Most vertices settled while looking for a witness path. Stopping early only adds unnecessary
+/// shortcuts, so doesn't affect the routes found.
+const WITNESS_SETTLE_LIMIT: usize = 500;
+
+/// Most vertices settled while estimating the shortcuts needed to contract a vertex.
+const PRIORITY_SETTLE_LIMIT: usize = 50;
+
+/// Arc between two vertices of the hierarchy, which may be a shortcut past a lower vertex.
+#[derive(Clone, Copy)]
+struct Arc {
+    target: u32,
+
+    /// Travel time in seconds
+    weight: f64,
+
+    /// Vertex that a shortcut skips over, or `NO_MIDDLE`
+    middle: u32,
+}
+
+/// Cost to reach each vertex during a search, and the vertex (and arc middle) it was reached from.
+type Costs = HashMap<u32, (f64, Option<(u32, u32)>)>;
+
+#[derive(Debug)]
+pub(crate) enum HierarchyError {
+    IoError(io::Error),
+
+    /// The file was made for a different network (or with an older format)
+    Mismatch,
+}
+impl Display for HierarchyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "Problem reading contraction hierarchy: {e}"),
+            Self::Mismatch => write!(f, "Contraction hierarchy doesn't match the network"),
+        }
+    }
+}
+impl Error for HierarchyError {}
+impl From<io::Error> for HierarchyError {
+    fn from(e: io::Error) -> Self {
+        Self::IoError(e)
+    }
+}
+
+/// Graph with a vertex for every state of the edge based search (so that turn restrictions are
+/// respected), and an arc for every allowed turn. The first vertices are the states for starting
+/// along each edge, so share the edge's index.
+struct StateGraph {
+    states: Vec<EdgeState>,
+    arcs: Vec<Vec<(u32, f64)>>,
+}
+
+impl StateGraph {
+    fn new(network: &Network) -> Self {
+        let mut states = Vec::new();
+        let mut index = HashMap::new();
+
+        let mut vertex = |state: EdgeState, states: &mut Vec<EdgeState>| {
+            *index.entry(state).or_insert_with(|| {
+                states.push(state);
+                states.len() as u32 - 1
+            })
+        };
+
+        for edge in 0..network.edges.len() {
+            vertex(network.restrictions.enter(edge), &mut states);
+        }
+
+        // States part way along via way restrictions are only found by following turns
+        let mut arcs = Vec::new();
+        let mut next = 0;
+        while next < states.len() {
+            let state = states[next];
+            let turns = network
+                .turns(state)
+                .map(|(state, weight)| (vertex(state, &mut states), weight))
+                .collect::<Vec<_>>();
+
+            arcs.push(turns);
+            next += 1;
+        }
+
+        Self { states, arcs }
+    }
+
+    /// Hash of the graph, so that a saved hierarchy can be checked against it. This is saved with
+    /// the hierarchy, so uses a fixed algorithm rather than `DefaultHasher` (which can change
+    /// between Rust releases).
+    fn fingerprint(&self) -> u64 {
+        let hash = fnv1a(FNV_OFFSET_BASIS, &(self.states.len() as u64).to_le_bytes());
+
+        self.arcs
+            .iter()
+            .flatten()
+            .fold(hash, |hash, (target, weight)| {
+                let hash = fnv1a(hash, &target.to_le_bytes());
+                fnv1a(hash, &weight.to_bits().to_le_bytes())
+            })
+    }
+}
+
+/// Continues an FNV-1a hash with more bytes.
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// Contraction hierarchy over the states of a network, answering the same queries as a search
+/// over the network but settling far fewer vertices.
+///
+/// Every vertex is given a rank, and shortcuts are added so that the fastest route between any two
+/// vertices can be found by only travelling upwards in rank from both ends.
+pub(crate) struct ContractionHierarchy {
+    states: Vec<EdgeState>,
+    fingerprint: u64,
+
+    /// Vertices with a state ending at each node
+    arriving: HashMap<i64, Vec<u32>>,
+
+    rank: Vec<u32>,
+
+    /// Arcs to higher ranked vertices
+    upward: Vec<Vec<Arc>>,
+
+    /// Arcs from higher ranked vertices, stored against the lower vertex with the higher vertex as
+    /// the target
+    downward: Vec<Vec<Arc>>,
+}
+
+impl ContractionHierarchy {
+    /// Contracts the network, which may take some time for large networks.
+    pub fn new(network: &Network) -> Self {
+        let graph = StateGraph::new(network);
+        let fingerprint = graph.fingerprint();
+        let (rank, upward, downward) = contract(&graph.arcs);
+
+        Self::from_parts(network, graph.states, fingerprint, rank, upward, downward)
+    }
+
+    fn from_parts(
+        network: &Network,
+        states: Vec<EdgeState>,
+        fingerprint: u64,
+        rank: Vec<u32>,
+        upward: Vec<Vec<Arc>>,
+        downward: Vec<Vec<Arc>>,
+    ) -> Self {
+        let mut arriving = HashMap::<i64, Vec<u32>>::new();
+        for (vertex, state) in states.iter().enumerate() {
+            arriving
+                .entry(network.edge(state.edge).to)
+                .or_default()
+                .push(vertex as u32);
+        }
+
+        Self {
+            states,
+            fingerprint,
+            arriving,
+            rank,
+            upward,
+            downward,
+        }
+    }
+
+    /// Loads a hierarchy previously saved for the network.
+    pub fn load(path: impl AsRef<Path>, network: &Network) -> Result<Self, HierarchyError> {
+        let mut file = BufReader::new(File::open(path)?);
+
+        let mut magic = [0; 4];
+        file.read_exact(&mut magic)?;
+        if &magic != MAGIC || read_u32(&mut file)? != VERSION {
+            return Err(HierarchyError::Mismatch);
+        }
+
+        let graph = StateGraph::new(network);
+        let fingerprint = read_u64(&mut file)?;
+        if fingerprint != graph.fingerprint() {
+            return Err(HierarchyError::Mismatch);
+        }
+
+        let count = graph.states.len();
+        let rank = (0..count)
+            .map(|_| read_u32(&mut file))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        let mut read_arcs = || {
+            (0..count)
+                .map(|_| {
+                    let length = read_u32(&mut file)?;
+
+                    (0..length)
+                        .map(|_| {
+                            Ok(Arc {
+                                target: read_u32(&mut file)?,
+                                weight: f64::from_bits(read_u64(&mut file)?),
+                                middle: read_u32(&mut file)?,
+                            })
+                        })
+                        .collect::<io::Result<Vec<_>>>()
+                })
+                .collect::<io::Result<Vec<_>>>()
+        };
+        let upward = read_arcs()?;
+        let downward = read_arcs()?;
+
+        Ok(Self::from_parts(
+            network,
+            graph.states,
+            fingerprint,
+            rank,
+            upward,
+            downward,
+        ))
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        file.write_all(MAGIC)?;
+        file.write_all(&VERSION.to_le_bytes())?;
+        file.write_all(&self.fingerprint.to_le_bytes())?;
+
+        for rank in &self.rank {
+            file.write_all(&rank.to_le_bytes())?;
+        }
+
+        for arcs in self.upward.iter().chain(&self.downward) {
+            file.write_all(&(arcs.len() as u32).to_le_bytes())?;
+
+            for arc in arcs {
+                file.write_all(&arc.target.to_le_bytes())?;
+                file.write_all(&arc.weight.to_bits().to_le_bytes())?;
+                file.write_all(&arc.middle.to_le_bytes())?;
+            }
+        }
+
+        file.flush()
+    }
+
+    /// Edges along the fastest route between two nodes, searching upwards from both ends at once.
+    pub fn route(&self, network: &Network, from: i64, to: i64) -> Option<Vec<EdgeId>> {
+        let mut forward = Costs::new();
+        let mut backward = Costs::new();
+
+        let mut forward_queue = BinaryHeap::new();
+        let mut backward_queue = BinaryHeap::new();
+
+        for (id, edge) in network.adjacent(from) {
+            let vertex = id as u32;
+
+            forward.insert(vertex, (edge.travel_time, None));
+            forward_queue.push(Candidate {
+                estimate: edge.travel_time,
+                state: vertex,
+            });
+        }
+
+        for &vertex in self.arriving.get(&to).into_iter().flatten() {
+            backward.insert(vertex, (0.0, None));
+            backward_queue.push(Candidate {
+                estimate: 0.0,
+                state: vertex,
+            });
+        }
+
+        // Lowest cost found so far, and the vertex where the searches met
+        let mut best = forward
+            .iter()
+            .filter_map(|(vertex, &(cost, _))| Some((cost + backward.get(vertex)?.0, *vertex)))
+            .min_by(|a, b| a.0.total_cmp(&b.0));
+
+        let search = |queue: &mut BinaryHeap<Candidate<u32>>,
+                      costs: &mut Costs,
+                      other: &Costs,
+                      arcs: &[Vec<Arc>],
+                      best: &mut Option<(f64, u32)>| {
+            let Some(Candidate {
+                estimate: cost,
+                state: vertex,
+            }) = queue.pop()
+            else {
+                return;
+            };
+
+            // Skip outdated entries
+            if cost > costs[&vertex].0 {
+                return;
+            }
+
+            for arc in &arcs[vertex as usize] {
+                let next_cost = cost + arc.weight;
+
+                if costs
+                    .get(&arc.target)
+                    .map(|&(known_cost, _)| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(arc.target, (next_cost, Some((vertex, arc.middle))));
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: arc.target,
+                    });
+
+                    if let Some(&(other_cost, _)) = other.get(&arc.target) {
+                        if best
+                            .map(|(best, _)| next_cost + other_cost < best)
+                            .unwrap_or(true)
+                        {
+                            *best = Some((next_cost + other_cost, arc.target));
+                        }
+                    }
+                }
+            }
+        };
+
+        // Neither search can improve on the best route once its lowest cost is higher
+        let is_done = |queue: &BinaryHeap<Candidate<u32>>, best: &Option<(f64, u32)>| match (
+            queue.peek(),
+            best,
+        ) {
+            (None, _) => true,
+            (Some(candidate), Some((best, _))) => candidate.estimate >= *best,
+            (Some(_), None) => false,
+        };
+
+        loop {
+            let forward_done = is_done(&forward_queue, &best);
+            let backward_done = is_done(&backward_queue, &best);
+
+            match (forward_done, backward_done) {
+                (true, true) => break,
+                (false, true) => search(
+                    &mut forward_queue,
+                    &mut forward,
+                    &backward,
+                    &self.upward,
+                    &mut best,
+                ),
+                (true, false) => search(
+                    &mut backward_queue,
+                    &mut backward,
+                    &forward,
+                    &self.downward,
+                    &mut best,
+                ),
+                (false, false) => {
+                    // Advance whichever search has the cheaper vertex to visit
+                    if forward_queue.peek() > backward_queue.peek() {
+                        search(
+                            &mut forward_queue,
+                            &mut forward,
+                            &backward,
+                            &self.upward,
+                            &mut best,
+                        )
+                    } else {
+                        search(
+                            &mut backward_queue,
+                            &mut backward,
+                            &forward,
+                            &self.downward,
+                            &mut best,
+                        )
+                    }
+                }
+            }
+        }
+
+        let (_, meeting) = best?;
+
+        // Arcs (as the vertices at each end, and the middle) from the start to the meeting vertex,
+        // then from the meeting vertex to the end
+        let mut arcs = Vec::new();
+        let mut vertex = meeting;
+        while let Some((previous, middle)) = forward[&vertex].1 {
+            arcs.push((previous, vertex, middle));
+            vertex = previous;
+        }
+        let start = vertex;
+        arcs.reverse();
+
+        let mut vertex = meeting;
+        while let Some((next, middle)) = backward[&vertex].1 {
+            arcs.push((vertex, next, middle));
+            vertex = next;
+        }
+
+        let mut vertices = vec![start];
+        for (from, to, middle) in arcs {
+            self.unpack(from, to, middle, &mut vertices);
+        }
+
+        Some(
+            vertices
+                .into_iter()
+                .map(|vertex| self.states[vertex as usize].edge)
+                .collect(),
+        )
+    }
+
+    /// Travel times from every source to every target node (`None` where there's no route).
+    ///
+    /// Upward searches are done once from each target (recording the cost at every vertex they
+    /// reach) and once from each source, so that each pair doesn't need its own search.
+    pub fn matrix(
+        &self,
+        network: &Network,
+        sources: &[i64],
+        targets: &[i64],
+    ) -> Vec<Vec<Option<f64>>> {
+        // Targets reachable upwards from each vertex, with the cost to reach them
+        let mut buckets = HashMap::<u32, Vec<(usize, f64)>>::new();
+        for (target, node) in targets.iter().enumerate() {
+            let starts = self
+                .arriving
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|&vertex| (vertex, 0.0));
+
+            for (vertex, cost) in self.upward_search(starts, &self.downward) {
+                buckets.entry(vertex).or_default().push((target, cost));
+            }
+        }
+
+        sources
+            .iter()
+            .map(|&source| {
+                let mut times = targets
+                    .iter()
+                    .map(|&target| (target == source).then_some(0.0))
+                    .collect::<Vec<_>>();
+
+                let starts = network
+                    .adjacent(source)
+                    .map(|(id, edge)| (id as u32, edge.travel_time));
+
+                for (vertex, cost) in self.upward_search(starts, &self.upward) {
+                    for &(target, target_cost) in buckets.get(&vertex).into_iter().flatten() {
+                        let time = &mut times[target];
+                        if time.map(|time| cost + target_cost < time).unwrap_or(true) {
+                            *time = Some(cost + target_cost);
+                        }
+                    }
+                }
+
+                times
+            })
+            .collect()
+    }
+
+    /// Lowest cost to every vertex reachable by only following arcs to higher ranked vertices.
+    fn upward_search(
+        &self,
+        starts: impl Iterator<Item = (u32, f64)>,
+        arcs: &[Vec<Arc>],
+    ) -> HashMap<u32, f64> {
+        let mut costs = HashMap::new();
+        let mut queue = BinaryHeap::new();
+
+        for (vertex, cost) in starts {
+            costs.insert(vertex, cost);
+            queue.push(Candidate {
+                estimate: cost,
+                state: vertex,
+            });
+        }
+
+        while let Some(Candidate {
+            estimate: cost,
+            state: vertex,
+        }) = queue.pop()
+        {
+            // Skip outdated entries
+            if cost > costs[&vertex] {
+                continue;
+            }
+
+            for arc in &arcs[vertex as usize] {
+                let next_cost = cost + arc.weight;
+
+                if costs
+                    .get(&arc.target)
+                    .map(|&known_cost| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(arc.target, next_cost);
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: arc.target,
+                    });
+                }
+            }
+        }
+
+        costs
+    }
+
+    /// Adds the vertices along an arc (excluding the first) to a path, expanding shortcuts.
+    fn unpack(&self, from: u32, to: u32, middle: u32, vertices: &mut Vec<u32>) {
+        let mut stack = vec![(from, to, middle)];
+
+        while let Some((from, to, middle)) = stack.pop() {
+            if middle == NO_MIDDLE {
+                vertices.push(to);
+                continue;
+            }
+
+            // Second half is pushed first, so that the first half is unpacked first
+            stack.push((middle, to, self.arc(middle, to).middle));
+            stack.push((from, middle, self.arc(from, middle).middle));
+        }
+    }
+
+    /// Arc between two vertices, which is stored against the lower ranked vertex.
+    fn arc(&self, from: u32, to: u32) -> &Arc {
+        let (arcs, target) = if self.rank[from as usize] < self.rank[to as usize] {
+            (&self.upward[from as usize], to)
+        } else {
+            (&self.downward[to as usize], from)
+        };
+
+        arcs.iter()
+            .find(|arc| arc.target == target)
+            .expect("shortcut should skip over existing arcs")
+    }
+}
+
+/// Contracts vertices one at a time (least important first), adding shortcuts between their
+/// neighbours wherever the vertex was on the only fastest path between them. Returns the rank of
+/// each vertex, and the upward and downward arcs.
+#[allow(clippy::type_complexity)]
+fn contract(graph: &[Vec<(u32, f64)>]) -> (Vec<u32>, Vec<Vec<Arc>>, Vec<Vec<Arc>>) {
+    let count = graph.len();
+    let mut contraction = Contraction::new(count);
+
+    for (from, arcs) in graph.iter().enumerate() {
+        for &(to, weight) in arcs {
+            contraction.add_arc(from as u32, to, weight, NO_MIDDLE);
+        }
+    }
+
+    let mut rank = vec![0; count];
+    let mut upward = vec![Vec::new(); count];
+    let mut downward = vec![Vec::new(); count];
+
+    let mut queue = (0..count as u32)
+        .map(|vertex| Reverse((contraction.priority(vertex), vertex)))
+        .collect::<BinaryHeap<_>>();
+
+    let mut next_rank = 0;
+    while let Some(Reverse((_, vertex))) = queue.pop() {
+        // Priorities change as neighbours are contracted, so only contract the vertex if it's
+        // still the least important
+        let current = contraction.priority(vertex);
+        if let Some(Reverse((next, _))) = queue.peek() {
+            if current > *next {
+                queue.push(Reverse((current, vertex)));
+                continue;
+            }
+        }
+
+        rank[vertex as usize] = next_rank;
+        next_rank += 1;
+
+        let (arcs_out, arcs_in) = contraction.remove(vertex);
+        upward[vertex as usize] = arcs_out;
+        downward[vertex as usize] = arcs_in;
+    }
+
+    (rank, upward, downward)
+}
+
+/// Graph of the vertices that haven't been contracted yet.
+struct Contraction {
+    outgoing: Vec<Vec<Arc>>,
+    incoming: Vec<Vec<Arc>>,
+
+    /// Contracted neighbours of each vertex, so that contraction is spread across the graph
+    contracted_neighbours: Vec<i64>,
+
+    /// Longest chain of contracted vertices below each vertex, so that the hierarchy stays shallow
+    depth: Vec<i64>,
+
+    /// Costs from the start of the last witness search, reused as clearing them is faster than
+    /// allocating them for every search
+    witness_costs: Vec<f64>,
+    witness_touched: Vec<u32>,
+}
+
+impl Contraction {
+    fn new(count: usize) -> Self {
+        Self {
+            outgoing: vec![Vec::new(); count],
+            incoming: vec![Vec::new(); count],
+            contracted_neighbours: vec![0; count],
+            depth: vec![0; count],
+            witness_costs: vec![f64::INFINITY; count],
+            witness_touched: Vec::new(),
+        }
+    }
+
+    /// Adds an arc, replacing any slower arc between the same vertices.
+    fn add_arc(&mut self, from: u32, to: u32, weight: f64, middle: u32) {
+        // Turning back onto the same state is never part of a fastest route
+        if from == to {
+            return;
+        }
+
+        for (arcs, target) in [
+            (&mut self.outgoing[from as usize], to),
+            (&mut self.incoming[to as usize], from),
+        ] {
+            match arcs.iter_mut().find(|arc| arc.target == target) {
+                Some(arc) if arc.weight <= weight => (),
+                Some(arc) => {
+                    arc.weight = weight;
+                    arc.middle = middle;
+                }
+                None => arcs.push(Arc {
+                    target,
+                    weight,
+                    middle,
+                }),
+            }
+        }
+    }
+
+    /// Lower priority vertices are contracted first: those that need few shortcuts compared to
+    /// the arcs they remove.
+    fn priority(&mut self, vertex: u32) -> i64 {
+        let shortcuts = self.shortcuts(vertex, PRIORITY_SETTLE_LIMIT).len() as i64;
+        let removed =
+            (self.outgoing[vertex as usize].len() + self.incoming[vertex as usize].len()) as i64;
+
+        2 * (shortcuts - removed)
+            + self.contracted_neighbours[vertex as usize]
+            + self.depth[vertex as usize]
+    }
+
+    /// Contracts a vertex, adding the shortcuts needed between its neighbours. Returns the arcs to
+    /// and from the vertex, as its remaining neighbours will all be ranked higher.
+    fn remove(&mut self, vertex: u32) -> (Vec<Arc>, Vec<Arc>) {
+        let shortcuts = self.shortcuts(vertex, WITNESS_SETTLE_LIMIT);
+
+        let arcs_out = std::mem::take(&mut self.outgoing[vertex as usize]);
+        let arcs_in = std::mem::take(&mut self.incoming[vertex as usize]);
+
+        let depth = self.depth[vertex as usize] + 1;
+        for arc in &arcs_out {
+            self.incoming[arc.target as usize].retain(|arc| arc.target != vertex);
+            self.contracted_neighbours[arc.target as usize] += 1;
+            self.depth[arc.target as usize] = self.depth[arc.target as usize].max(depth);
+        }
+        for arc in &arcs_in {
+            self.outgoing[arc.target as usize].retain(|arc| arc.target != vertex);
+            self.contracted_neighbours[arc.target as usize] += 1;
+            self.depth[arc.target as usize] = self.depth[arc.target as usize].max(depth);
+        }
+
+        for (from, to, weight) in shortcuts {
+            self.add_arc(from, to, weight, vertex);
+        }
+
+        (arcs_out, arcs_in)
+    }
+
+    /// Shortcuts (from, to, weight) needed to contract a vertex, for each pair of neighbours
+    /// without another path between them that is at least as fast.
+    fn shortcuts(&mut self, vertex: u32, settle_limit: usize) -> Vec<(u32, u32, f64)> {
+        let mut shortcuts = Vec::new();
+
+        for arc_in in self.incoming[vertex as usize].clone() {
+            let from = arc_in.target;
+            let arcs_out = || {
+                self.outgoing[vertex as usize]
+                    .iter()
+                    .filter(move |arc| arc.target != from)
+            };
+
+            let Some(limit) = arcs_out()
+                .map(|arc| arc_in.weight + arc.weight)
+                .max_by(f64::total_cmp)
+            else {
+                continue;
+            };
+            let targets = arcs_out().map(|arc| arc.target).collect::<Vec<_>>();
+
+            self.witness_search(from, vertex, &targets, limit, settle_limit);
+
+            for arc_out in self.outgoing[vertex as usize]
+                .iter()
+                .filter(|arc| arc.target != from)
+            {
+                let weight = arc_in.weight + arc_out.weight;
+
+                if self.witness_costs[arc_out.target as usize] > weight {
+                    shortcuts.push((from, arc_out.target, weight));
+                }
+            }
+        }
+
+        shortcuts
+    }
+
+    /// Finds the costs from a vertex to others nearby (stopping once the targets are reached),
+    /// without passing through the vertex being contracted.
+    fn witness_search(
+        &mut self,
+        from: u32,
+        avoid: u32,
+        targets: &[u32],
+        limit: f64,
+        settle_limit: usize,
+    ) {
+        for vertex in self.witness_touched.drain(..) {
+            self.witness_costs[vertex as usize] = f64::INFINITY;
+        }
+
+        self.witness_costs[from as usize] = 0.0;
+        self.witness_touched.push(from);
+
+        let mut queue = BinaryHeap::from([Candidate {
+            estimate: 0.0,
+            state: from,
+        }]);
+        let mut settled = 0;
+        let mut targets_settled = 0;
+
+        while let Some(Candidate {
+            estimate: cost,
+            state: vertex,
+        }) = queue.pop()
+        {
+            if cost > limit || settled >= settle_limit || targets_settled == targets.len() {
+                break;
+            }
+
+            if cost > self.witness_costs[vertex as usize] {
+                continue;
+            }
+            settled += 1;
+
+            if targets.contains(&vertex) {
+                targets_settled += 1;
+            }
+
+            for arc in &self.outgoing[vertex as usize] {
+                let next_cost = cost + arc.weight;
+
+                if arc.target != avoid && next_cost < self.witness_costs[arc.target as usize] {
+                    if self.witness_costs[arc.target as usize] == f64::INFINITY {
+                        self.witness_touched.push(arc.target);
+                    }
+
+                    self.witness_costs[arc.target as usize] = next_cost;
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: arc.target,
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+
+    Ok(u64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BinaryHeap, HashMap};
+
+    use super::{
+        super::test_map::{grid_node, TestMap, Via},
+        fnv1a, Candidate, ContractionHierarchy, EdgeId, EdgeState, HierarchyError, Network,
+        FNV_OFFSET_BASIS,
+    };
+
+    /// Width and height (in nodes) of the grid of streets.
+    const SIZE: i64 = 4;
+
+    fn node_id(x: i64, y: i64) -> i64 {
+        grid_node(SIZE, x, y)
+    }
+
+    /// Grid of streets with a faster road through the middle and some one way streets.
+    fn streets() -> TestMap {
+        let mut map = TestMap::grid(SIZE);
+
+        for x in 0..SIZE - 1 {
+            map = map.retag(100 + SIZE + x, &[("highway", "primary")]);
+        }
+        for x in 1..SIZE - 1 {
+            map = map.retag(100 + 2 * SIZE + x, &[("oneway", "yes")]);
+        }
+        for y in 0..SIZE - 1 {
+            map = map.retag(200 + 2 * SIZE + y, &[("oneway", "-1")]);
+        }
+
+        map
+    }
+
+    /// Streets with some turn restrictions (both via nodes and via ways) added, so that the
+    /// fastest routes are far from straightforward.
+    fn grid() -> TestMap {
+        streets()
+            .restriction(1, "no_left_turn", 105, Via::Node(node_id(2, 1)), 208)
+            .restriction(2, "only_right_turn", 109, Via::Node(node_id(2, 2)), 209)
+            .restriction(3, "no_left_turn", 104, Via::Ways(&[205]), 109)
+    }
+
+    /// Every node of the grid.
+    fn nodes() -> Vec<i64> {
+        (0..SIZE * SIZE).map(|i| i + 1).collect()
+    }
+
+    /// Edges along the fastest route, found with a plain Dijkstra search over the states of the
+    /// network (with no heuristic or shortcuts, so that it's simple enough to trust).
+    fn dijkstra(network: &Network, from: i64, to: i64) -> Option<Vec<EdgeId>> {
+        let mut costs = HashMap::<EdgeState, (f64, Option<EdgeState>)>::new();
+        let mut queue = BinaryHeap::new();
+
+        for (id, edge) in network.adjacent(from) {
+            let state = network.restrictions.enter(id);
+
+            costs.insert(state, (edge.travel_time, None));
+            queue.push(Candidate {
+                estimate: edge.travel_time,
+                state,
+            });
+        }
+
+        while let Some(Candidate {
+            estimate: cost,
+            state,
+        }) = queue.pop()
+        {
+            if cost > costs[&state].0 {
+                continue;
+            }
+
+            if network.edge(state.edge).to == to {
+                let mut edges = vec![state.edge];
+                let mut previous = costs[&state].1;
+                while let Some(state) = previous {
+                    edges.push(state.edge);
+                    previous = costs[&state].1;
+                }
+                edges.reverse();
+
+                return Some(edges);
+            }
+
+            for (next, travel_time) in network.turns(state) {
+                let next_cost = cost + travel_time;
+
+                if costs
+                    .get(&next)
+                    .is_none_or(|&(known_cost, _)| next_cost < known_cost)
+                {
+                    costs.insert(next, (next_cost, Some(state)));
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Travel time (in seconds) along a route, adding up the edges in order as searches do.
+    fn route_time(network: &Network, edges: &[EdgeId]) -> f64 {
+        edges.iter().map(|&id| network.edge(id).travel_time).sum()
+    }
+
+    /// Checks that the hierarchy finds exactly the same routes as a Dijkstra search.
+    fn assert_matches_dijkstra(network: &Network, hierarchy: &ContractionHierarchy) {
+        for from in nodes() {
+            for to in nodes().into_iter().filter(|&to| to != from) {
+                let route = hierarchy.route(network, from, to);
+
+                if let Some(edges) = &route {
+                    // Route must follow any turn restrictions
+                    let mut state = network.restrictions.enter(edges[0]);
+                    for &edge in &edges[1..] {
+                        state = network
+                            .restrictions
+                            .turn(state, edge)
+                            .expect("route should only make allowed turns");
+                    }
+                }
+
+                assert_eq!(route, dijkstra(network, from, to), "from {from} to {to}");
+            }
+        }
+    }
+
+    #[test]
+    fn restrictions_change_routes() {
+        let unrestricted = streets();
+        let unrestricted_network = unrestricted.network();
+
+        // Each restriction makes a route slower
+        for restriction in [1, 2, 3] {
+            let mut map = grid();
+            map.remove_restrictions_except(restriction);
+            let network = map.network();
+
+            let slower = nodes().into_iter().any(|from| {
+                nodes().into_iter().any(|to| {
+                    dijkstra(&network, from, to) != dijkstra(&unrestricted_network, from, to)
+                })
+            });
+
+            assert!(slower, "restriction {restriction} should apply");
+        }
+    }
+
+    #[test]
+    fn routes_match_dijkstra() {
+        let network = grid().network();
+        let hierarchy = ContractionHierarchy::new(&network);
+
+        assert_matches_dijkstra(&network, &hierarchy);
+    }
+
+    #[test]
+    fn matrix_matches_dijkstra() {
+        let mut network = grid().network();
+        let sources = nodes();
+        let targets = nodes().into_iter().rev().step_by(2).collect::<Vec<_>>();
+
+        let expected = sources
+            .iter()
+            .map(|&from| {
+                targets
+                    .iter()
+                    .map(|&to| match from == to {
+                        true => Some(0.0),
+                        false => {
+                            dijkstra(&network, from, to).map(|edges| route_time(&network, &edges))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        // Searching the network adds up travel times in the same order
+        assert_eq!(network.matrix(&sources, &targets), expected);
+
+        network.hierarchy = Some(ContractionHierarchy::new(&network));
+        let matrix = network.matrix(&sources, &targets);
+
+        for (i, &from) in sources.iter().enumerate() {
+            for (j, &to) in targets.iter().enumerate() {
+                match (matrix[i][j], expected[i][j]) {
+                    // Shortcuts add up the travel times of the edges they skip before they're added
+                    // to the rest of the route, so the total can differ in the last few bits
+                    (Some(time), Some(expected)) => assert!(
+                        (time - expected).abs() <= expected * 1e-12,
+                        "from {from} to {to}: {time} s with the hierarchy, {expected} s without"
+                    ),
+                    (time, expected) => assert_eq!(time, expected, "from {from} to {to}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn fingerprint_hash() {
+        // Published test vectors for 64 bit FNV-1a
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn save_and_load() {
+        let map = grid();
+        let network = map.network();
+        let path = std::env::temp_dir().join(format!("hierarchy-test-{}", std::process::id()));
+
+        ContractionHierarchy::new(&network).save(&path).unwrap();
+        let loaded = ContractionHierarchy::load(&path, &network);
+
+        // Hierarchies don't load for other networks
+        let other = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .road(10, &[1, 2], &[])
+            .network();
+        let mismatched = ContractionHierarchy::load(&path, &other);
+
+        std::fs::remove_file(&path).unwrap();
+
+        assert_matches_dijkstra(&network, &loaded.unwrap());
+        assert!(matches!(mismatched, Err(HierarchyError::Mismatch)));
+    }
+}
@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::osm::{Node, Osm};
+
+use super::{EdgeId, Network};
+
+/// Size (in projected units) of the grid cells that edges are indexed by.
+const CELL_SIZE: f64 = 100.0;
+
+/// Closest point along an edge to a position.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Projection {
+    pub edge: EdgeId,
+
+    /// Distance in metres from the start of the edge
+    pub offset: f64,
+
+    /// Distance in metres from the position
+    pub distance: f64,
+}
+
+/// Grid of the edges of a network, to find the roads near a position quickly.
+pub(crate) struct EdgeIndex<'a> {
+    data: &'a Osm,
+    network: &'a Network,
+
+    /// Edges passing through each cell of the grid
+    cells: HashMap<(i64, i64), Vec<EdgeId>>,
+}
+
+impl<'a> EdgeIndex<'a> {
+    pub fn new(data: &'a Osm, network: &'a Network) -> Self {
+        let mut cells = HashMap::<(i64, i64), Vec<EdgeId>>::new();
+
+        for (id, edge) in network.edges.iter().enumerate() {
+            let Some(points) = edge
+                .nodes
+                .iter()
+                .map(|node| data.nodes.get(node))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            for pair in points.windows(2) {
+                let (min_x, max_x) = (pair[0].x.min(pair[1].x), pair[0].x.max(pair[1].x));
+                let (min_y, max_y) = (pair[0].y.min(pair[1].y), pair[0].y.max(pair[1].y));
+
+                for x in cell(min_x)..=cell(max_x) {
+                    for y in cell(min_y)..=cell(max_y) {
+                        let edges = cells.entry((x, y)).or_default();
+
+                        if edges.last() != Some(&id) {
+                            edges.push(id);
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            data,
+            network,
+            cells,
+        }
+    }
+
+    /// Closest points on the edges within a distance (in metres) of a position, closest first.
+    pub fn nearby(&self, position: &Node, radius: f64) -> Vec<Projection> {
+        // Projected distances are stretched away from the equator
+        let (_, lat) = position.lon_lat();
+        let scale = lat.to_radians().cos();
+        let projected_radius = radius / scale;
+
+        let mut edges = HashSet::new();
+        for x in cell(position.x - projected_radius)..=cell(position.x + projected_radius) {
+            for y in cell(position.y - projected_radius)..=cell(position.y + projected_radius) {
+                edges.extend(self.cells.get(&(x, y)).into_iter().flatten());
+            }
+        }
+
+        let mut projections = edges
+            .into_iter()
+            .filter_map(|&id| self.project(id, position, scale))
+            .filter(|projection| projection.distance <= radius)
+            .collect::<Vec<_>>();
+
+        projections.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.edge.cmp(&b.edge)));
+
+        projections
+    }
+
+    /// Closest point along an edge to a position, with projected distances multiplied by the scale
+    /// to get metres.
+    fn project(&self, id: EdgeId, position: &Node, scale: f64) -> Option<Projection> {
+        let edge = self.network.edge(id);
+        let mut best: Option<Projection> = None;
+        let mut offset = 0.0;
+
+        for pair in edge.nodes.windows(2) {
+            let (start, end) = (
+                self.data.nodes.get(&pair[0])?,
+                self.data.nodes.get(&pair[1])?,
+            );
+            let (dx, dy) = (end.x - start.x, end.y - start.y);
+            let length_squared = dx * dx + dy * dy;
+
+            let t = if length_squared > 0.0 {
+                (((position.x - start.x) * dx + (position.y - start.y) * dy) / length_squared)
+                    .clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+
+            let (x, y) = (start.x + t * dx, start.y + t * dy);
+            let distance = (position.x - x).hypot(position.y - y) * scale;
+            let length = length_squared.sqrt() * scale;
+
+            if best.map(|best| distance < best.distance).unwrap_or(true) {
+                best = Some(Projection {
+                    edge: id,
+                    offset: (offset + t * length).min(edge.length),
+                    distance,
+                });
+            }
+
+            offset += length;
+        }
+
+        best
+    }
+}
+
+/// Grid cell that a projected coordinate is in.
+fn cell(coordinate: f64) -> i64 {
+    (coordinate / CELL_SIZE).floor() as i64
+}
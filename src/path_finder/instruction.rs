@@ -0,0 +1,474 @@
+use std::fmt::{self, Display};
+
+use serde::Serialize;
+
+use crate::osm::{Osm, Tags};
+
+use super::{EdgeId, Network, Route};
+
+/// Change of direction (in degrees) that is still considered to be straight on.
+const STRAIGHT_ANGLE: f64 = 20.0;
+const SLIGHT_ANGLE: f64 = 45.0;
+const TURN_ANGLE: f64 = 120.0;
+const SHARP_ANGLE: f64 = 170.0;
+
+/// Manoeuvre made at a point along a route.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub(crate) enum Manoeuvre {
+    /// Start of the route, heading in a direction (in degrees clockwise from north)
+    Depart {
+        bearing: f64,
+    },
+    Continue,
+    SlightLeft,
+    SlightRight,
+    Left,
+    Right,
+    SharpLeft,
+    SharpRight,
+    UTurn,
+
+    /// Leave a roundabout at an exit, counting from the first one after entering it
+    Roundabout {
+        exit: usize,
+    },
+    Arrive,
+}
+
+/// Step of turn-by-turn directions along a route.
+#[derive(Clone, Debug)]
+pub(crate) struct Instruction {
+    pub manoeuvre: Manoeuvre,
+
+    /// Name (or reference number) of the street that the manoeuvre is onto
+    pub street: Option<String>,
+
+    /// Distance in metres to travel after the manoeuvre, until the next one
+    pub distance: f64,
+
+    /// Node that the manoeuvre is made at
+    pub node: i64,
+}
+
+/// Turn-by-turn directions for a route, from departure to arrival.
+///
+/// A manoeuvre is only given where the street changes, or where the route turns at a junction that
+/// has somewhere else to go, so that following a street around a bend isn't called a turn.
+pub(crate) fn instructions(data: &Osm, network: &Network, route: &Route) -> Vec<Instruction> {
+    let (Some(&first), Some(&last)) = (route.edges.first(), route.edges.last()) else {
+        return Vec::new();
+    };
+
+    let tags = |edge: EdgeId| data.ways.get(&network.edge(edge).way).map(|way| &way.tags);
+    let street = |edge: EdgeId| tags(edge).and_then(street_name);
+    let roundabout = |edge: EdgeId| tags(edge).is_some_and(is_roundabout);
+
+    let mut instructions = vec![Instruction {
+        manoeuvre: Manoeuvre::Depart {
+            bearing: bearing(data, network, first).unwrap_or_default(),
+        },
+        street: street(first),
+        distance: network.edge(first).length,
+        node: network.edge(first).from,
+    }];
+
+    // Exits passed on the roundabout the route is currently on
+    let mut exits = 0;
+
+    for pair in route.edges.windows(2) {
+        let (previous, next) = (pair[0], pair[1]);
+        let node = network.edge(next).from;
+        let length = network.edge(next).length;
+
+        let current = instructions
+            .last_mut()
+            .expect("instructions should start with departing");
+
+        if roundabout(next) {
+            if roundabout(previous) {
+                // Count the exits that are passed while going around
+                if network.adjacent(node).any(|(id, _)| !roundabout(id)) {
+                    exits += 1;
+                }
+
+                current.distance += length;
+            } else {
+                exits = 0;
+                instructions.push(Instruction {
+                    manoeuvre: Manoeuvre::Roundabout { exit: 0 },
+                    street: None,
+                    distance: length,
+                    node,
+                });
+            }
+
+            continue;
+        }
+
+        if roundabout(previous) {
+            let manoeuvre = Manoeuvre::Roundabout { exit: exits + 1 };
+
+            if let Manoeuvre::Roundabout { .. } = current.manoeuvre {
+                // The distance around the roundabout is included with its exit, as that's part of
+                // the same manoeuvre
+                current.manoeuvre = manoeuvre;
+                current.street = street(next);
+                current.distance += length;
+            } else {
+                // Routes starting on a roundabout depart along it, then leave at an exit
+                instructions.push(Instruction {
+                    manoeuvre,
+                    street: street(next),
+                    distance: length,
+                    node,
+                });
+            }
+
+            continue;
+        }
+
+        let manoeuvre = turn(data, network, previous, next);
+        let next_street = street(next);
+
+        // Other roads (besides going back the way the route came) to take at the junction
+        let junction = network
+            .adjacent(node)
+            .filter(|(id, _)| !network.is_u_turn(previous, *id))
+            .count()
+            > 1;
+
+        if next_street != current.street || (manoeuvre != Manoeuvre::Continue && junction) {
+            instructions.push(Instruction {
+                manoeuvre,
+                street: next_street,
+                distance: length,
+                node,
+            });
+        } else {
+            current.distance += length;
+        }
+    }
+
+    instructions.push(Instruction {
+        manoeuvre: Manoeuvre::Arrive,
+        street: None,
+        distance: 0.0,
+        node: network.edge(last).to,
+    });
+
+    instructions
+}
+
+/// Name to call a street by, falling back to its reference number if it doesn't have one.
+fn street_name(tags: &Tags) -> Option<String> {
+    tags.get("name").or_else(|| tags.get("ref")).cloned()
+}
+
+fn is_roundabout(tags: &Tags) -> bool {
+    matches!(
+        tags.get("junction").map(String::as_str),
+        Some("roundabout" | "circular")
+    )
+}
+
+/// Direction (in degrees clockwise from north) that an edge sets off in.
+fn bearing(data: &Osm, network: &Network, edge: EdgeId) -> Option<f64> {
+    let (dx, dy) = segment(data, &network.edge(edge).nodes[..2])?;
+
+    Some(dx.atan2(dy).to_degrees().rem_euclid(360.0))
+}
+
+/// Classifies the change in direction between the end of one edge and the start of the next.
+fn turn(data: &Osm, network: &Network, from: EdgeId, to: EdgeId) -> Manoeuvre {
+    let from_nodes = &network.edge(from).nodes;
+    let (Some(incoming), Some(outgoing)) = (
+        segment(data, &from_nodes[from_nodes.len() - 2..]),
+        segment(data, &network.edge(to).nodes[..2]),
+    ) else {
+        return Manoeuvre::Continue;
+    };
+
+    // Anticlockwise (left) is positive, as y points north
+    let cross = incoming.0 * outgoing.1 - incoming.1 * outgoing.0;
+    let dot = incoming.0 * outgoing.0 + incoming.1 * outgoing.1;
+    let angle = cross.atan2(dot).to_degrees();
+
+    let left = angle > 0.0;
+    match angle.abs() {
+        a if a < STRAIGHT_ANGLE => Manoeuvre::Continue,
+        a if a < SLIGHT_ANGLE && left => Manoeuvre::SlightLeft,
+        a if a < SLIGHT_ANGLE => Manoeuvre::SlightRight,
+        a if a < TURN_ANGLE && left => Manoeuvre::Left,
+        a if a < TURN_ANGLE => Manoeuvre::Right,
+        a if a < SHARP_ANGLE && left => Manoeuvre::SharpLeft,
+        a if a < SHARP_ANGLE => Manoeuvre::SharpRight,
+        _ => Manoeuvre::UTurn,
+    }
+}
+
+/// Vector between a pair of nodes.
+fn segment(data: &Osm, nodes: &[i64]) -> Option<(f64, f64)> {
+    let (start, end) = (data.nodes.get(&nodes[0])?, data.nodes.get(&nodes[1])?);
+
+    Some((end.x - start.x, end.y - start.y))
+}
+
+/// Distance rounded to a sensible precision for directions.
+fn format_distance(metres: f64) -> String {
+    if metres < 1000.0 {
+        format!("{:.0} m", (metres / 10.0).round() * 10.0)
+    } else {
+        format!("{:.1} km", metres / 1000.0)
+    }
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{n}{suffix}")
+}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let turn = |f: &mut fmt::Formatter<'_>, direction: &str| {
+            write!(f, "Turn {direction}")?;
+
+            match &self.street {
+                Some(street) => write!(f, " onto {street}"),
+                None => Ok(()),
+            }
+        };
+
+        match self.manoeuvre {
+            Manoeuvre::Depart { bearing } => {
+                const COMPASS: [&str; 8] = [
+                    "north",
+                    "north east",
+                    "east",
+                    "south east",
+                    "south",
+                    "south west",
+                    "west",
+                    "north west",
+                ];
+
+                let direction = COMPASS[((bearing / 45.0).round() as usize) % COMPASS.len()];
+                write!(f, "Head {direction}")?;
+
+                if let Some(street) = &self.street {
+                    write!(f, " on {street}")?;
+                }
+            }
+            Manoeuvre::Continue => match &self.street {
+                Some(street) => write!(f, "Continue onto {street}")?,
+                None => write!(f, "Continue straight on")?,
+            },
+            Manoeuvre::SlightLeft => turn(f, "slightly left")?,
+            Manoeuvre::SlightRight => turn(f, "slightly right")?,
+            Manoeuvre::Left => turn(f, "left")?,
+            Manoeuvre::Right => turn(f, "right")?,
+            Manoeuvre::SharpLeft => turn(f, "sharply left")?,
+            Manoeuvre::SharpRight => turn(f, "sharply right")?,
+            Manoeuvre::UTurn => write!(f, "Make a U-turn")?,
+            // The route finishes on the roundabout
+            Manoeuvre::Roundabout { exit: 0 } => write!(f, "Enter the roundabout")?,
+            Manoeuvre::Roundabout { exit } => {
+                write!(f, "At the roundabout, take the {} exit", ordinal(exit))?;
+
+                if let Some(street) = &self.street {
+                    write!(f, " onto {street}")?;
+                }
+            }
+            Manoeuvre::Arrive => return write!(f, "Arrive at the destination"),
+        }
+
+        write!(f, ", then continue for {}", format_distance(self.distance))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_map::TestMap, instructions};
+
+    /// Directions for the fastest route between two nodes.
+    fn directions(map: &TestMap, from: i64, to: i64) -> Vec<String> {
+        let network = map.network();
+        let route = network.route(map.osm(), from, to).unwrap();
+
+        instructions(map.osm(), &network, &route)
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect()
+    }
+
+    /// Crossroads at node 2, with West Street and East Street meeting North Road and South Road.
+    ///
+    /// ```text
+    ///      3
+    ///      |
+    /// 1 -- 2 -- 4 -- 6
+    ///      |    |
+    ///      5    7
+    /// ```
+    fn crossroads() -> TestMap {
+        TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 1.0, 1.0)
+            .node(4, 2.0, 0.0)
+            .node(5, 1.0, -1.0)
+            .node(6, 3.0, 0.0)
+            .node(7, 2.0, -1.0)
+            .road(10, &[1, 2], &[("name", "West Street")])
+            .road(11, &[2, 3], &[("name", "North Road")])
+            .road(12, &[2, 4], &[("name", "East Street")])
+            .road(13, &[2, 5], &[("name", "South Road")])
+            .road(14, &[4, 6], &[("name", "East Street")])
+            .road(15, &[4, 7], &[])
+    }
+
+    #[test]
+    fn turns() {
+        let map = crossroads();
+
+        assert_eq!(
+            directions(&map, 1, 3),
+            vec![
+                "Head east on West Street, then continue for 100 m",
+                "Turn left onto North Road, then continue for 100 m",
+                "Arrive at the destination",
+            ]
+        );
+        assert_eq!(
+            directions(&map, 1, 5),
+            vec![
+                "Head east on West Street, then continue for 100 m",
+                "Turn right onto South Road, then continue for 100 m",
+                "Arrive at the destination",
+            ]
+        );
+        assert_eq!(
+            directions(&map, 3, 4),
+            vec![
+                "Head south on North Road, then continue for 100 m",
+                "Turn left onto East Street, then continue for 100 m",
+                "Arrive at the destination",
+            ]
+        );
+        assert_eq!(
+            directions(&map, 6, 7),
+            vec![
+                "Head west on East Street, then continue for 100 m",
+                "Turn left, then continue for 100 m",
+                "Arrive at the destination",
+            ]
+        );
+    }
+
+    #[test]
+    fn continues_along_streets() {
+        let map = crossroads();
+
+        // Changing street without turning
+        assert_eq!(
+            directions(&map, 1, 6),
+            vec![
+                "Head east on West Street, then continue for 100 m",
+                "Continue onto East Street, then continue for 200 m",
+                "Arrive at the destination",
+            ]
+        );
+
+        // Ways of the same street are merged, even past junctions
+        assert_eq!(
+            directions(&map, 6, 2),
+            vec![
+                "Head west on East Street, then continue for 200 m",
+                "Arrive at the destination",
+            ]
+        );
+    }
+
+    /// Roundabout (going anticlockwise) with a road leaving it to each side.
+    ///
+    /// ```text
+    ///           7
+    ///           |
+    ///           3
+    ///         /   \
+    /// 8 -- 4         2 -- 6
+    ///         \   /
+    ///           1
+    ///           |
+    ///           5
+    /// ```
+    fn roundabout() -> TestMap {
+        TestMap::new()
+            .node(1, 0.0, -1.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 0.0, 1.0)
+            .node(4, -1.0, 0.0)
+            .node(5, 0.0, -2.0)
+            .node(6, 2.0, 0.0)
+            .node(7, 0.0, 2.0)
+            .node(8, -2.0, 0.0)
+            .road(10, &[1, 2, 3, 4, 1], &[("junction", "roundabout")])
+            .road(11, &[5, 1], &[("name", "South Road")])
+            .road(12, &[2, 6], &[("name", "East Road")])
+            .road(13, &[3, 7], &[("name", "North Road")])
+            .road(14, &[4, 8], &[("name", "West Road")])
+    }
+
+    #[test]
+    fn roundabout_exits() {
+        let map = roundabout();
+
+        assert_eq!(
+            directions(&map, 5, 6),
+            vec![
+                "Head north on South Road, then continue for 100 m",
+                "At the roundabout, take the 1st exit onto East Road, then continue for 240 m",
+                "Arrive at the destination",
+            ]
+        );
+        assert_eq!(
+            directions(&map, 5, 8),
+            vec![
+                "Head north on South Road, then continue for 100 m",
+                "At the roundabout, take the 3rd exit onto West Road, then continue for 530 m",
+                "Arrive at the destination",
+            ]
+        );
+
+        // Finishing on the roundabout
+        assert_eq!(
+            directions(&map, 5, 3),
+            vec![
+                "Head north on South Road, then continue for 100 m",
+                "Enter the roundabout, then continue for 280 m",
+                "Arrive at the destination",
+            ]
+        );
+    }
+
+    #[test]
+    fn starts_on_roundabout() {
+        let map = roundabout();
+
+        assert_eq!(
+            directions(&map, 1, 8),
+            vec![
+                "Head north east, then continue for 430 m",
+                "At the roundabout, take the 3rd exit onto West Road, then continue for 100 m",
+                "Arrive at the destination",
+            ]
+        );
+    }
+}
@@ -0,0 +1,192 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+use renderer::render_steps::canvas::Path;
+
+use super::Network;
+
+/// Travel time (in minutes) that each band of the isochrone reaches up to, with its colour. Bands
+/// are in order, each starting where the previous one finished.
+const BANDS: [(f64, Vec3); 3] = [
+    (10.0, Vec3::new(0.2, 0.7, 0.3)),
+    (20.0, Vec3::new(0.95, 0.75, 0.1)),
+    (30.0, Vec3::new(0.9, 0.3, 0.2)),
+];
+
+const BAND_OPACITY: f32 = 0.35;
+
+/// Size (in world units) of the grid cells that the reachable area is made up of
+const CELL_SIZE: f32 = 40.0;
+
+/// Areas that can be reached from a node within the time of each band, drawn as translucent fills.
+///
+/// Reachable roads are sampled onto a grid, with each cell taking the earliest time that any road
+/// through it (or its neighbours, so that the area doesn't have gaps between roads) is reached.
+pub(crate) fn isochrone_paths(
+    network: &Network,
+    from: i64,
+    node_position: impl Fn(i64) -> Option<Vec3>,
+) -> Vec<Path> {
+    let limit = BANDS[BANDS.len() - 1].0 * 60.0;
+
+    // Earliest time that each cell is reached
+    let mut cells = HashMap::<(i32, i32), f64>::new();
+    let mut reach = |point: Vec3, time: f64| {
+        let cell = (
+            (point.x / CELL_SIZE).floor() as i32,
+            (point.z / CELL_SIZE).floor() as i32,
+        );
+
+        for x in -1..=1 {
+            for z in -1..=1 {
+                let earliest = cells.entry((cell.0 + x, cell.1 + z)).or_insert(time);
+                *earliest = earliest.min(time);
+            }
+        }
+    };
+
+    for (&id, &start_time) in &network.reachable(from, limit) {
+        let edge = network.edge(id);
+        let Some(points) = edge
+            .nodes
+            .iter()
+            .map(|&node| node_position(node))
+            .collect::<Option<Vec<_>>>()
+        else {
+            continue;
+        };
+
+        let length = points
+            .windows(2)
+            .map(|pair| pair[0].distance(pair[1]))
+            .sum::<f32>()
+            .max(f32::EPSILON);
+
+        // Sample along the edge often enough that no cell is skipped, with the time at each point
+        // assuming a constant speed along the edge
+        let mut distance = 0.0;
+        'edge: for pair in points.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            let steps = (start.distance(end) / (CELL_SIZE / 2.0)).ceil().max(1.0) as usize;
+
+            for step in 0..=steps {
+                let point = start.lerp(end, step as f32 / steps as f32);
+                let time = start_time
+                    + edge.travel_time * ((distance + start.distance(point)) / length) as f64;
+
+                if time > limit {
+                    break 'edge;
+                }
+
+                reach(point, time);
+            }
+
+            distance += start.distance(end);
+        }
+    }
+
+    // Cells in each band for each row of the grid
+    let mut rows = vec![HashMap::<i32, Vec<i32>>::new(); BANDS.len()];
+    for (&(x, z), &time) in &cells {
+        if let Some(band) = band(time) {
+            rows[band].entry(z).or_default().push(x);
+        }
+    }
+
+    let mut paths = Vec::new();
+
+    for ((_, color), rows) in BANDS.into_iter().zip(rows) {
+        // Join neighbouring cells in a row into a single rectangle, to keep the number of fills
+        // down
+        for (z, mut row) in rows {
+            row.sort_unstable();
+
+            let mut runs = Vec::<(i32, i32)>::new();
+            for x in row {
+                match runs.last_mut() {
+                    Some((_, end)) if *end + 1 == x => *end = x,
+                    _ => runs.push((x, x)),
+                }
+            }
+
+            for (start, end) in runs {
+                let (x0, x1) = (start as f32 * CELL_SIZE, (end + 1) as f32 * CELL_SIZE);
+                let (z0, z1) = (z as f32 * CELL_SIZE, (z + 1) as f32 * CELL_SIZE);
+
+                paths.push(
+                    Path::new(vec![
+                        Vec3::new(x0, 0.0, z0),
+                        Vec3::new(x1, 0.0, z0),
+                        Vec3::new(x1, 0.0, z1),
+                        Vec3::new(x0, 0.0, z1),
+                        Vec3::new(x0, 0.0, z0),
+                    ])
+                    .with_fill(color)
+                    .with_fill_opacity(BAND_OPACITY)
+                    .with_z_index(-1),
+                );
+            }
+        }
+    }
+
+    paths
+}
+
+/// Index of the band that a travel time (in seconds) is in, with the first band including the
+/// starting point.
+fn band(time: f64) -> Option<usize> {
+    BANDS
+        .iter()
+        .position(|&(minutes, _)| time <= minutes * 60.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::test_map::TestMap, band};
+
+    #[test]
+    fn bands() {
+        assert_eq!(band(0.0), Some(0));
+        assert_eq!(band(10.0 * 60.0), Some(0));
+        assert_eq!(band(10.0 * 60.0 + 0.1), Some(1));
+        assert_eq!(band(20.0 * 60.0), Some(1));
+        assert_eq!(band(30.0 * 60.0), Some(2));
+        assert_eq!(band(30.0 * 60.0 + 0.1), None);
+    }
+
+    #[test]
+    fn reachable_within_limit() {
+        // Road with nodes 100 m apart
+        let network = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 2.0, 0.0)
+            .node(4, 3.0, 0.0)
+            .road(10, &[1, 2], &[])
+            .road(11, &[2, 3], &[])
+            .road(12, &[3, 4], &[])
+            .network();
+
+        let edge = |from: i64, to: i64| {
+            network
+                .adjacent(from)
+                .find(|(_, edge)| edge.to == to)
+                .map(|(id, _)| id)
+                .unwrap()
+        };
+        let travel_time = |from: i64, to: i64| network.edge(edge(from, to)).travel_time;
+        let (first, second) = (travel_time(1, 2), travel_time(1, 2) + travel_time(2, 3));
+
+        // Only the starts of the first two edges can be reached in time
+        let reachable = network.reachable(1, (first + second) / 2.0);
+
+        assert_eq!(reachable.get(&edge(1, 2)), Some(&0.0));
+        assert_eq!(reachable.get(&edge(2, 3)), Some(&first));
+        assert_eq!(reachable.get(&edge(3, 4)), None);
+
+        // Further edges can be reached given longer
+        let reachable = network.reachable(1, second * 2.0);
+
+        assert_eq!(reachable.get(&edge(3, 4)), Some(&second));
+    }
+}
@@ -0,0 +1,413 @@
+use std::collections::{BinaryHeap, HashMap};
+
+use crate::osm::{Node, Osm};
+
+use super::{
+    index::{EdgeIndex, Projection},
+    restriction::EdgeState,
+    Candidate, EdgeId, Network, Route,
+};
+
+/// Standard deviation (in metres) of GPS positions from the road they were recorded on.
+const GPS_SIGMA: f64 = 10.0;
+
+/// Distance (in metres) from a GPS position to look for roads that it could have been recorded on.
+const SEARCH_RADIUS: f64 = 50.0;
+
+/// Most roads to consider for each GPS position, keeping the closest.
+const MAX_CANDIDATES: usize = 8;
+
+/// Scale (in metres) of the difference between the distance along the roads and the straight line
+/// distance between positions. Smaller values favour more direct routes.
+const TRANSITION_BETA: f64 = 10.0;
+
+/// Distance (in metres) from the end of an edge that a position is considered to be at the end.
+const ENDPOINT_TOLERANCE: f64 = 1.0;
+
+/// Position of a trace while matching it.
+struct Step {
+    /// Roads the position may have been recorded on
+    candidates: Vec<Projection>,
+
+    /// Log probability of the most likely way of getting to each candidate
+    scores: Vec<f64>,
+
+    /// Candidate of the previous step that each candidate is most likely reached from, and the
+    /// edges in between
+    back: Vec<(usize, Vec<EdgeId>)>,
+}
+
+/// Snaps GPS traces onto the roads of a network, using a hidden Markov model.
+///
+/// Each GPS position may have been recorded on any nearby road (more likely the closer it is), and
+/// consecutive positions are more likely to be connected by roads when the distance along the roads
+/// is close to the straight line distance between them. The most likely sequence of roads is found
+/// with the Viterbi algorithm.
+// Newson and Krumm, "Hidden Markov Map Matching Through Noise and Sparseness"
+pub(crate) struct MapMatcher<'a> {
+    network: &'a Network,
+
+    /// Roads near each position
+    index: EdgeIndex<'a>,
+}
+
+impl<'a> MapMatcher<'a> {
+    pub fn new(data: &'a Osm, network: &'a Network) -> Self {
+        Self {
+            network,
+            index: EdgeIndex::new(data, network),
+        }
+    }
+
+    /// Routes most likely to have been driven along to record a trace of positions (as longitude
+    /// and latitude). The trace is split into several routes wherever it can't be matched, such as
+    /// where it strays away from the roads.
+    pub fn match_trace(&self, trace: &[(f64, f64)]) -> Vec<Route> {
+        // Positions too close together add nothing but noise, as the vehicle may not have moved
+        let mut positions = Vec::<Node>::new();
+        for &(lon, lat) in trace {
+            let node = Node::from_lon_lat(lon, lat);
+
+            if positions
+                .last()
+                .map(|last| last.distance(&node) > 2.0 * GPS_SIGMA)
+                .unwrap_or(true)
+            {
+                positions.push(node);
+            }
+        }
+
+        let mut routes = Vec::new();
+
+        // Positions of the part of the trace that's currently being matched
+        let mut steps = Vec::<Step>::new();
+        let mut previous_position: Option<&Node> = None;
+
+        for position in &positions {
+            let candidates = self.candidates(position);
+            if candidates.is_empty() {
+                // The position is too far from any road, so treat it as a gap in the trace
+                routes.extend(self.finish(&mut steps));
+                previous_position = None;
+
+                continue;
+            }
+
+            let emissions = candidates
+                .iter()
+                .map(|candidate| -0.5 * (candidate.distance / GPS_SIGMA).powi(2))
+                .collect::<Vec<_>>();
+
+            let Some(previous) = previous_position.filter(|_| !steps.is_empty()) else {
+                steps.push(Step {
+                    candidates,
+                    scores: emissions,
+                    back: Vec::new(),
+                });
+                previous_position = Some(position);
+
+                continue;
+            };
+
+            let straight_distance = previous.distance(position);
+            let last = steps.last().expect("there should be a previous step");
+
+            let mut scores = vec![f64::NEG_INFINITY; candidates.len()];
+            let mut back = vec![(0, Vec::new()); candidates.len()];
+
+            for (i, (from, &score)) in last.candidates.iter().zip(&last.scores).enumerate() {
+                for (j, transition) in self
+                    .transitions(*from, &candidates, straight_distance)
+                    .into_iter()
+                    .enumerate()
+                {
+                    let Some((distance, edges)) = transition else {
+                        continue;
+                    };
+
+                    let transition = -(distance - straight_distance).abs() / TRANSITION_BETA;
+                    if score + transition + emissions[j] > scores[j] {
+                        scores[j] = score + transition + emissions[j];
+                        back[j] = (i, edges);
+                    }
+                }
+            }
+
+            if scores.iter().all(|score| score.is_infinite()) {
+                // No way to get between the positions along the roads, so start matching again
+                routes.extend(self.finish(&mut steps));
+                steps.push(Step {
+                    candidates,
+                    scores: emissions,
+                    back: Vec::new(),
+                });
+            } else {
+                steps.push(Step {
+                    candidates,
+                    scores,
+                    back,
+                });
+            }
+
+            previous_position = Some(position);
+        }
+
+        routes.extend(self.finish(&mut steps));
+
+        routes
+    }
+
+    /// Roads that a position may have been recorded on, closest first.
+    fn candidates(&self, position: &Node) -> Vec<Projection> {
+        let mut candidates = self.index.nearby(position, SEARCH_RADIUS);
+        candidates.truncate(MAX_CANDIDATES);
+
+        candidates
+    }
+
+    /// Shortest distance along the roads from a candidate to each of the next candidates, with the
+    /// edges travelled along in between. Candidates much further away than the straight line
+    /// distance aren't searched for, as they're too unlikely to matter.
+    fn transitions(
+        &self,
+        from: Projection,
+        to: &[Projection],
+        straight_distance: f64,
+    ) -> Vec<Option<(f64, Vec<EdgeId>)>> {
+        let mut transitions = to
+            .iter()
+            .map(|to| {
+                // Further along the same edge. Noisy positions can appear to go backwards a little,
+                // which is treated as standing still rather than driving around the block.
+                (to.edge == from.edge && to.offset + 2.0 * GPS_SIGMA >= from.offset)
+                    .then(|| ((to.offset - from.offset).max(0.0), Vec::new()))
+            })
+            .collect::<Vec<_>>();
+
+        // Indexes of the candidates that haven't been reached yet, for each edge
+        let mut remaining = HashMap::<EdgeId, Vec<usize>>::new();
+        for (i, to) in to.iter().enumerate() {
+            if transitions[i].is_none() {
+                remaining.entry(to.edge).or_default().push(i);
+            }
+        }
+
+        let limit = 2.0 * straight_distance + 4.0 * SEARCH_RADIUS;
+
+        // Distance to the end of each state's edge, and the state it was reached from
+        let start = self.network.restrictions.enter(from.edge);
+        let mut visited = HashMap::<EdgeState, (f64, Option<EdgeState>)>::new();
+        let mut queue = BinaryHeap::new();
+
+        let cost = self.network.edge(from.edge).length - from.offset;
+        visited.insert(start, (cost, None));
+        queue.push(Candidate {
+            estimate: cost,
+            state: start,
+        });
+
+        while let Some(Candidate {
+            estimate: cost,
+            state,
+        }) = queue.pop()
+        {
+            if remaining.is_empty() || cost > limit {
+                break;
+            }
+
+            // Skip outdated entries
+            if cost > visited[&state].0 {
+                continue;
+            }
+
+            for (next, _) in self.network.turns(state) {
+                // States are visited in order of distance, so this is the shortest way onto the
+                // edge
+                if let Some(indexes) = remaining.remove(&next.edge) {
+                    let mut edges = Vec::new();
+                    let mut current = Some(state);
+                    while let Some(previous) = current.filter(|&previous| previous != start) {
+                        edges.push(previous.edge);
+                        current = visited[&previous].1;
+                    }
+                    edges.reverse();
+
+                    for i in indexes {
+                        transitions[i] = Some((cost + to[i].offset, edges.clone()));
+                    }
+                }
+
+                let next_cost = cost + self.network.edge(next.edge).length;
+                if visited
+                    .get(&next)
+                    .map(|&(known_cost, _)| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    visited.insert(next, (next_cost, Some(state)));
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        transitions
+    }
+
+    /// Route along the most likely candidates of the matched positions, clearing them so that
+    /// matching can start again.
+    fn finish(&self, steps: &mut Vec<Step>) -> Option<Route> {
+        let last = steps.last()?;
+
+        // Follow the most likely candidates back from the end, with the edges leading to each
+        let (mut index, _) = last
+            .scores
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))?;
+
+        let mut chosen = Vec::new();
+        for step in steps.iter().rev() {
+            let (previous, between) = step.back.get(index).cloned().unwrap_or_default();
+
+            chosen.push((step.candidates[index], between));
+            index = previous;
+        }
+
+        chosen.reverse();
+        let (first, last) = (chosen[0].0, chosen[chosen.len() - 1].0);
+
+        let mut edges = Vec::<EdgeId>::new();
+        for (candidate, between) in chosen {
+            // Candidates on the same edge only add it once, unless the route looped back onto it
+            let moved = !between.is_empty();
+            edges.extend(between);
+
+            if moved || edges.last() != Some(&candidate.edge) {
+                edges.push(candidate.edge);
+            }
+        }
+
+        // Positions at a junction also match the very end (or start) of the roads around it, which
+        // the route only touches
+        let length = |candidate: Projection| self.network.edge(candidate.edge).length;
+        if edges.len() > 1 && first.offset >= length(first) - ENDPOINT_TOLERANCE {
+            edges.remove(0);
+        }
+        if edges.len() > 1 && last.offset <= ENDPOINT_TOLERANCE {
+            edges.pop();
+        }
+
+        steps.clear();
+
+        Some(Route {
+            distance: edges.iter().map(|&id| self.network.edge(id).length).sum(),
+            time: edges
+                .iter()
+                .map(|&id| self.network.edge(id).travel_time)
+                .sum(),
+            edges,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::test_map::{grid_lon_lat, route_nodes, TestMap},
+        MapMatcher,
+    };
+
+    /// GPS positions recorded every 25 m or so along a path through points of the grid, each
+    /// jittered by up to 8 m.
+    fn trace(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+        // Simple pseudo random numbers (between -1 and 1), so that the trace is the same every time
+        let mut seed = 12345_u64;
+        let mut random = move || {
+            seed = seed
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (seed >> 11) as f64 / (1_u64 << 53) as f64 * 2.0 - 1.0
+        };
+
+        // Grid points are 100 m apart
+        let (steps, jitter) = (4.0, 0.08);
+
+        points
+            .windows(2)
+            .flat_map(|pair| {
+                let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+                let length = ((x1 - x0).powi(2) + (y1 - y0).powi(2)).sqrt();
+                let count = (length * steps).round() as usize;
+
+                (0..count).map(move |i| {
+                    let t = i as f64 / count as f64;
+                    (x0 + (x1 - x0) * t, y0 + (y1 - y0) * t)
+                })
+            })
+            .chain(points.last().cloned())
+            .map(|(x, y)| grid_lon_lat(x + random() * jitter, y + random() * jitter))
+            .collect()
+    }
+
+    #[test]
+    fn matches_jittered_trace() {
+        let map = TestMap::grid(3);
+        let network = map.network();
+        let matcher = MapMatcher::new(map.osm(), &network);
+
+        // East along the bottom, then north up the east side
+        let routes = matcher.match_trace(&trace(&[(0.1, 0.0), (2.0, 0.0), (2.0, 1.9)]));
+        let routes = routes
+            .iter()
+            .map(|route| route_nodes(&network, &route.edges))
+            .collect::<Vec<_>>();
+
+        assert_eq!(routes, vec![vec![1, 2, 3, 6, 9]]);
+    }
+
+    #[test]
+    fn matches_turns_through_the_middle() {
+        let map = TestMap::grid(3);
+        let network = map.network();
+        let matcher = MapMatcher::new(map.osm(), &network);
+
+        let routes = matcher.match_trace(&trace(&[
+            (0.0, 0.1),
+            (0.0, 1.0),
+            (1.0, 1.0),
+            (1.0, 2.0),
+            (1.9, 2.0),
+        ]));
+        let routes = routes
+            .iter()
+            .map(|route| route_nodes(&network, &route.edges))
+            .collect::<Vec<_>>();
+
+        assert_eq!(routes, vec![vec![1, 4, 5, 8, 9]]);
+    }
+
+    #[test]
+    fn splits_trace_at_gap() {
+        let map = TestMap::grid(3);
+        let network = map.network();
+        let matcher = MapMatcher::new(map.osm(), &network);
+
+        // Along the bottom, then off to the south (far from any road), and then back along the top
+        let trace = [
+            trace(&[(0.1, 0.0), (0.9, 0.0)]),
+            trace(&[(1.0, -1.0), (1.0, -2.0)]),
+            trace(&[(0.1, 2.0), (1.9, 2.0)]),
+        ]
+        .concat();
+
+        let routes = matcher
+            .match_trace(&trace)
+            .iter()
+            .map(|route| route_nodes(&network, &route.edges))
+            .collect::<Vec<_>>();
+
+        assert_eq!(routes, vec![vec![1, 2], vec![7, 8, 9]]);
+    }
+}
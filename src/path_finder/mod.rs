@@ -0,0 +1,904 @@
+mod analysis;
+mod hierarchy;
+mod index;
+mod instruction;
+mod isochrone;
+mod matching;
+mod profile;
+mod restriction;
+#[cfg(test)]
+mod test_map;
+mod tour;
+
+use std::{
+    cell::RefCell,
+    cmp::Ordering,
+    collections::{BTreeMap, BinaryHeap, HashMap, HashSet},
+    path::PathBuf,
+    rc::Rc,
+    time::Instant,
+};
+
+use crate::{
+    map_data::MapData,
+    osm::{Node, Osm},
+    plugin::Plugin,
+};
+pub(crate) use analysis::Analysis;
+use glam::Vec3;
+use hierarchy::ContractionHierarchy;
+pub(crate) use instruction::instructions;
+use isochrone::isochrone_paths;
+pub(crate) use matching::MapMatcher;
+pub(crate) use profile::Profile;
+use renderer::{
+    render_steps::canvas::{CanvasProgram, LineCap, LineJoin, Path, Stroke},
+    Event, RenderStep, VirtualKeyCode,
+};
+use restriction::{EdgeState, Restrictions};
+pub(crate) use tour::order_stops;
+
+/// Index of an edge within the network.
+pub(crate) type EdgeId = usize;
+
+/// Directed edge between two intersections (or ends) of the road network, following a way.
+pub(crate) struct Edge {
+    pub from: i64,
+    pub to: i64,
+
+    /// Way that the edge is part of
+    pub way: i64,
+
+    /// Nodes along the edge, including both ends
+    pub nodes: Vec<i64>,
+
+    /// Length in metres
+    pub length: f64,
+
+    /// Travel time in seconds
+    pub travel_time: f64,
+}
+
+/// Directions that a way can be travelled in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub(crate) enum Direction {
+    Both,
+    Forward,
+    Backward,
+}
+
+impl Direction {
+    pub fn forward(&self) -> bool {
+        *self != Direction::Backward
+    }
+
+    pub fn backward(&self) -> bool {
+        *self != Direction::Forward
+    }
+}
+
+pub(crate) struct Network {
+    edges: Vec<Edge>,
+
+    /// Outgoing edges for every node in the graph
+    adjacency: HashMap<i64, Vec<EdgeId>>,
+
+    /// Fastest speed (in m/s) of any edge, so that the A* heuristic never overestimates
+    max_speed: f64,
+
+    /// Turns between edges that can't be made
+    restrictions: Restrictions,
+
+    /// Hierarchy to speed up routing, if the network has been contracted
+    hierarchy: Option<ContractionHierarchy>,
+}
+
+/// Route through the network, made up of consecutive edges.
+pub(crate) struct Route {
+    pub edges: Vec<EdgeId>,
+
+    /// Length in metres
+    pub distance: f64,
+
+    /// Travel time in seconds
+    pub time: f64,
+}
+
+/// State waiting to be visited by the path finding, ordered so that the lowest cost is visited
+/// first.
+struct Candidate<T = EdgeState> {
+    /// Cost so far, plus the estimated cost to the destination
+    estimate: f64,
+    state: T,
+}
+
+impl<T> PartialEq for Candidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl<T> Eq for Candidate<T> {}
+
+impl<T> PartialOrd for Candidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for Candidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Reversed, as `BinaryHeap` is a max heap
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl Network {
+    /// Builds the graph of ways that can be used with a routing profile.
+    pub fn new(data: &Osm, profile: &Profile) -> Self {
+        // Sorted, so that edges are numbered the same every time the network is built
+        let route_ways = data
+            .ways
+            .iter()
+            .filter_map(|(&way_id, way)| Some((way_id, (way, profile.speed(&way.tags)?))))
+            .collect::<BTreeMap<_, _>>();
+
+        // Ways are split into edges at nodes that appear more than once, either in several ways
+        // (intersections) or in the same way (eg loops)
+        let mut occurrences = HashMap::<i64, usize>::new();
+        for (way, _) in route_ways.values() {
+            for &node_id in &way.nodes {
+                *occurrences.entry(node_id).or_default() += 1;
+            }
+        }
+
+        let mut network = Network {
+            edges: Vec::new(),
+            adjacency: HashMap::new(),
+            max_speed: 0.0,
+            restrictions: Restrictions::default(),
+            hierarchy: None,
+        };
+
+        for (&way_id, &(way, speed)) in &route_ways {
+            let direction = profile.direction(&way.tags);
+            let speed = speed / 3.6;
+            network.max_speed = network.max_speed.max(speed);
+
+            let mut nodes = Vec::new();
+            for (i, &node_id) in way.nodes.iter().enumerate() {
+                // Ways in an extract may reference nodes outside of it, so break the edge there
+                if !data.nodes.contains_key(&node_id) {
+                    nodes.clear();
+                    continue;
+                }
+
+                nodes.push(node_id);
+
+                let is_split = occurrences[&node_id] > 1
+                    || way
+                        .nodes
+                        .get(i + 1)
+                        .is_none_or(|next| !data.nodes.contains_key(next));
+                if nodes.len() > 1 && is_split {
+                    let length = nodes
+                        .windows(2)
+                        .map(|pair| data.nodes[&pair[0]].distance(&data.nodes[&pair[1]]))
+                        .sum::<f64>();
+
+                    let nodes = std::mem::replace(&mut nodes, vec![node_id]);
+
+                    if direction.backward() {
+                        network.add_edge(Edge {
+                            from: node_id,
+                            to: nodes[0],
+                            way: way_id,
+                            nodes: nodes.iter().rev().cloned().collect(),
+                            length,
+                            travel_time: length / speed,
+                        });
+                    }
+
+                    if direction.forward() {
+                        network.add_edge(Edge {
+                            from: nodes[0],
+                            to: node_id,
+                            way: way_id,
+                            nodes,
+                            length,
+                            travel_time: length / speed,
+                        });
+                    }
+                }
+            }
+        }
+
+        network.restrictions = Restrictions::new(data, profile, &network);
+
+        network
+    }
+
+    fn add_edge(&mut self, edge: Edge) {
+        let id = self.edges.len();
+
+        self.adjacency.entry(edge.from).or_default().push(id);
+        self.adjacency.entry(edge.to).or_default();
+
+        self.edges.push(edge);
+    }
+
+    pub fn edge(&self, id: EdgeId) -> &Edge {
+        &self.edges[id]
+    }
+
+    /// Whether the second edge carries on from the end of the first.
+    fn is_turn(&self, from: EdgeId, to: EdgeId) -> bool {
+        self.edges[from].to == self.edges[to].from
+    }
+
+    /// Whether the second edge heads straight back along the first.
+    fn is_u_turn(&self, from: EdgeId, to: EdgeId) -> bool {
+        let (from, to) = (&self.edges[from], &self.edges[to]);
+
+        from.way == to.way && from.nodes.iter().rev().eq(to.nodes.iter())
+    }
+
+    /// All nodes that are part of the graph (intersections and the ends of ways).
+    pub fn nodes(&self) -> impl Iterator<Item = i64> + '_ {
+        self.adjacency.keys().cloned()
+    }
+
+    /// Edges that can be travelled along from a node.
+    pub fn adjacent(&self, node: i64) -> impl Iterator<Item = (EdgeId, &Edge)> {
+        self.adjacency
+            .get(&node)
+            .into_iter()
+            .flatten()
+            .map(|&id| (id, &self.edges[id]))
+    }
+
+    /// Node in the graph closest to a point (in projected coordinates).
+    pub fn nearest_node(&self, data: &Osm, x: f64, y: f64) -> Option<i64> {
+        self.nodes()
+            .filter_map(|id| Some((id, data.nodes.get(&id)?)))
+            .min_by(|(_, a), (_, b)| {
+                let distance = |node: &Node| (node.x - x).powi(2) + (node.y - y).powi(2);
+
+                distance(a).total_cmp(&distance(b))
+            })
+            .map(|(id, _)| id)
+    }
+
+    /// Builds a contraction hierarchy so that routes are found much faster, loading it from a
+    /// previous run if there is one saved at the path (otherwise saving it there).
+    pub fn contract(&mut self, path: impl AsRef<std::path::Path>) {
+        let path = path.as_ref();
+
+        let hierarchy = match ContractionHierarchy::load(path, self) {
+            Ok(hierarchy) => hierarchy,
+            Err(e) => {
+                if path.exists() {
+                    eprintln!("{e}, rebuilding it");
+                }
+
+                let hierarchy = ContractionHierarchy::new(self);
+                if let Err(e) = hierarchy.save(path) {
+                    eprintln!("Problem saving contraction hierarchy: {e}");
+                }
+
+                hierarchy
+            }
+        };
+
+        self.hierarchy = Some(hierarchy);
+    }
+
+    /// States that can be reached by turning off the end of a state's edge, with the travel time of
+    /// the next edge.
+    fn turns(&self, state: EdgeState) -> impl Iterator<Item = (EdgeState, f64)> + '_ {
+        self.adjacent(self.edge(state.edge).to)
+            .filter_map(move |(id, edge)| {
+                Some((self.restrictions.turn(state, id)?, edge.travel_time))
+            })
+    }
+
+    /// Fastest route between two nodes in the graph.
+    pub fn route(&self, data: &Osm, from: i64, to: i64) -> Option<Route> {
+        let edges = if from == to {
+            Vec::new()
+        } else if let Some(hierarchy) = &self.hierarchy {
+            hierarchy.route(self, from, to)?
+        } else {
+            self.search(data, from, to)?
+        };
+
+        Some(Route {
+            distance: edges.iter().map(|&id| self.edge(id).length).sum(),
+            time: edges.iter().map(|&id| self.edge(id).travel_time).sum(),
+            edges,
+        })
+    }
+
+    /// Edges along the fastest route between two nodes, using A* with the straight line travel
+    /// time as the heuristic. The search is over edges rather than nodes, so that turn restrictions
+    /// (which depend on the edge a node was reached from) are respected.
+    fn search(&self, data: &Osm, from: i64, to: i64) -> Option<Vec<EdgeId>> {
+        let destination = data.nodes.get(&to)?;
+        let heuristic = |edge: EdgeId| {
+            data.nodes
+                .get(&self.edge(edge).to)
+                .map(|node| node.distance(destination) / self.max_speed)
+                .unwrap_or_default()
+        };
+
+        // Lowest known cost to each state, and the state it was reached from
+        let mut costs = HashMap::<EdgeState, (f64, Option<EdgeState>)>::new();
+        let mut queue = BinaryHeap::new();
+
+        for (id, edge) in self.adjacent(from) {
+            let state = self.restrictions.enter(id);
+
+            costs.insert(state, (edge.travel_time, None));
+            queue.push(Candidate {
+                estimate: edge.travel_time + heuristic(id),
+                state,
+            });
+        }
+
+        let mut visited = HashSet::new();
+        let mut last = None;
+
+        while let Some(Candidate { state, .. }) = queue.pop() {
+            if self.edge(state.edge).to == to {
+                last = Some(state);
+                break;
+            }
+
+            if !visited.insert(state) {
+                continue;
+            }
+
+            let cost = costs[&state].0;
+
+            for (next, travel_time) in self.turns(state) {
+                let next_cost = cost + travel_time;
+
+                if costs
+                    .get(&next)
+                    .map(|&(known_cost, _)| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(next, (next_cost, Some(state)));
+                    queue.push(Candidate {
+                        estimate: next_cost + heuristic(next.edge),
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        // Walk backwards from the destination to find the edges that were taken
+        let mut edges = Vec::new();
+        let mut state = Some(last?);
+        while let Some(current) = state {
+            edges.push(current.edge);
+            state = costs[&current].1;
+        }
+        edges.reverse();
+
+        Some(edges)
+    }
+
+    /// Travel times (in seconds) from every source to every target node, or `None` where there's
+    /// no route between them.
+    pub fn matrix(&self, sources: &[i64], targets: &[i64]) -> Vec<Vec<Option<f64>>> {
+        match &self.hierarchy {
+            Some(hierarchy) => hierarchy.matrix(self, sources, targets),
+            None => sources
+                .iter()
+                .map(|&source| self.travel_times(source, targets))
+                .collect(),
+        }
+    }
+
+    /// Travel times from a node to each target, searching outwards until every target is reached.
+    fn travel_times(&self, from: i64, targets: &[i64]) -> Vec<Option<f64>> {
+        let mut times = targets
+            .iter()
+            .map(|&target| (target == from).then_some(0.0))
+            .collect::<Vec<_>>();
+
+        // Indexes of the targets that haven't been reached yet, for each node
+        let mut remaining = HashMap::<i64, Vec<usize>>::new();
+        for (i, &target) in targets.iter().enumerate() {
+            if target != from {
+                remaining.entry(target).or_default().push(i);
+            }
+        }
+
+        let mut costs = HashMap::<EdgeState, f64>::new();
+        let mut queue = BinaryHeap::new();
+
+        for (id, edge) in self.adjacent(from) {
+            let state = self.restrictions.enter(id);
+
+            costs.insert(state, edge.travel_time);
+            queue.push(Candidate {
+                estimate: edge.travel_time,
+                state,
+            });
+        }
+
+        while let Some(Candidate {
+            estimate: cost,
+            state,
+        }) = queue.pop()
+        {
+            if remaining.is_empty() {
+                break;
+            }
+
+            // Skip outdated entries
+            if cost > costs[&state] {
+                continue;
+            }
+
+            // States are visited in order of cost, so this is the fastest way to the node
+            for i in remaining
+                .remove(&self.edge(state.edge).to)
+                .into_iter()
+                .flatten()
+            {
+                times[i] = Some(cost);
+            }
+
+            for (next, travel_time) in self.turns(state) {
+                let next_cost = cost + travel_time;
+
+                if costs
+                    .get(&next)
+                    .map(|&known_cost| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(next, next_cost);
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        times
+    }
+
+    /// Earliest time (in seconds) that the start of each edge can be reached from a node, for
+    /// edges that can be reached within the time limit.
+    pub fn reachable(&self, from: i64, limit: f64) -> HashMap<EdgeId, f64> {
+        let mut reached = HashMap::<EdgeId, f64>::new();
+        let mut reach = |edge: EdgeId, time: f64| {
+            let earliest = reached.entry(edge).or_insert(time);
+            *earliest = earliest.min(time);
+        };
+
+        // Lowest known cost (to the end of the state's edge) of each state
+        let mut costs = HashMap::<EdgeState, f64>::new();
+        let mut queue = BinaryHeap::new();
+
+        for (id, edge) in self.adjacent(from) {
+            let state = self.restrictions.enter(id);
+
+            reach(id, 0.0);
+            costs.insert(state, edge.travel_time);
+            queue.push(Candidate {
+                estimate: edge.travel_time,
+                state,
+            });
+        }
+
+        while let Some(Candidate {
+            estimate: cost,
+            state,
+        }) = queue.pop()
+        {
+            if cost > limit {
+                break;
+            }
+
+            // Skip outdated entries
+            if cost > costs[&state] {
+                continue;
+            }
+
+            for (next, travel_time) in self.turns(state) {
+                reach(next.edge, cost);
+
+                let next_cost = cost + travel_time;
+                if costs
+                    .get(&next)
+                    .map(|&known_cost| next_cost < known_cost)
+                    .unwrap_or(true)
+                {
+                    costs.insert(next, next_cost);
+                    queue.push(Candidate {
+                        estimate: next_cost,
+                        state: next,
+                    });
+                }
+            }
+        }
+
+        reached
+    }
+
+    /// Nodes along a route, from start to finish.
+    pub fn route_nodes(&self, route: &Route) -> Vec<i64> {
+        route
+            .edges
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &id)| {
+                // Skip the first node of each edge, as it was the last node of the previous edge
+                let skip = if i == 0 { 0 } else { 1 };
+
+                self.edge(id).nodes.iter().skip(skip).cloned()
+            })
+            .collect()
+    }
+}
+
+/// Finds routes between two clicked locations, drawing the route over the map.
+pub(crate) struct PathFinder {
+    canvas: Rc<RefCell<CanvasProgram>>,
+    map_data: Option<Rc<MapData>>,
+
+    profiles: Vec<Profile>,
+
+    /// Network for each profile, only built once the profile is used
+    networks: Vec<Option<Network>>,
+
+    /// Currently selected profile
+    profile: usize,
+
+    /// Data file that contraction hierarchies are saved next to, if networks should be contracted
+    contract: Option<PathBuf>,
+
+    /// Whether clicking shows the areas reachable from a location, rather than finding a route
+    isochrone: bool,
+
+    /// Whether problems with how the roads connect are drawn over the map (toggled with N)
+    analysis: bool,
+
+    /// Connectivity problems of each profile's network, only found once they're drawn
+    analyses: Vec<Option<Analysis>>,
+
+    /// Locations clicked for the start and end of the route
+    start: Option<Vec3>,
+    end: Option<Vec3>,
+}
+
+impl PathFinder {
+    /// Path finder that can switch between the given profiles, starting with the first.
+    pub fn new(profiles: Vec<Profile>) -> Self {
+        assert!(!profiles.is_empty(), "at least one profile is required");
+
+        Self {
+            canvas: Rc::new(RefCell::new(CanvasProgram::default())),
+            map_data: None,
+            networks: profiles.iter().map(|_| None).collect(),
+            analyses: profiles.iter().map(|_| None).collect(),
+            profiles,
+            profile: 0,
+            contract: None,
+            isochrone: false,
+            analysis: false,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Contract each network once it's built, saving the hierarchies next to the data file so that
+    /// they only need to be built once.
+    pub fn with_contraction(mut self, data_file: impl Into<PathBuf>) -> Self {
+        self.contract = Some(data_file.into());
+
+        self
+    }
+
+    /// Network for the selected profile, building it if required.
+    fn network(&mut self) -> Option<&Network> {
+        let map_data = self.map_data.as_ref()?;
+        let profile = &self.profiles[self.profile];
+
+        Some(self.networks[self.profile].get_or_insert_with(|| {
+            let mut network = Network::new(&map_data.osm_data, profile);
+
+            if let Some(data_file) = &self.contract {
+                println!("Preparing contraction hierarchy for {}", profile.name);
+                network.contract(hierarchy_path(data_file, profile));
+            }
+
+            network
+        }))
+    }
+
+    /// Re-draws the route between the clicked locations with the current profile.
+    fn update_route(&mut self) {
+        let mut paths = self.analysis_paths().unwrap_or_default();
+        paths.extend(self.route_paths().unwrap_or_default());
+
+        let mut canvas = self.canvas.borrow_mut();
+        canvas.clear();
+        for path in paths {
+            canvas.add_object(Box::new(path));
+        }
+    }
+
+    /// Problems with the current profile's network, if they should be drawn.
+    fn analysis_paths(&mut self) -> Option<Vec<Path>> {
+        if !self.analysis {
+            return None;
+        }
+
+        let map_data = Rc::clone(self.map_data.as_ref()?);
+        self.network()?;
+        let network = self.networks[self.profile].as_ref()?;
+
+        let analysis = self.analyses[self.profile].get_or_insert_with(|| {
+            let analysis = Analysis::new(&map_data.osm_data, network);
+            println!("{analysis}");
+
+            analysis
+        });
+
+        Some(analysis.paths(network, |node| {
+            Some((&map_data.node_point(map_data.osm_data.nodes.get(&node)?)).into())
+        }))
+    }
+
+    fn route_paths(&mut self) -> Option<Vec<Path>> {
+        let map_data = Rc::clone(self.map_data.as_ref()?);
+        let (start, end, isochrone) = (self.start, self.end, self.isochrone);
+        let network = self.network()?;
+
+        let node_position = |node: i64| -> Option<Vec3> {
+            Some((&map_data.node_point(map_data.osm_data.nodes.get(&node)?)).into())
+        };
+
+        // Find the nodes closest to the clicked points
+        let snap = |point: Vec3| {
+            // Undo the translation into world space
+            let x = point.x as f64 + map_data.bounding.center_x;
+            let y = point.z as f64 + map_data.bounding.center_y;
+
+            network.nearest_node(&map_data.osm_data, x, y)
+        };
+
+        let start = snap(start?)?;
+        let mut paths = marker(node_position(start)?, Vec3::new(0.2, 0.7, 0.3));
+
+        if isochrone {
+            paths.extend(isochrone_paths(network, start, node_position));
+
+            return Some(paths);
+        }
+
+        let Some(end) = end.and_then(snap) else {
+            return Some(paths);
+        };
+
+        let search_start = Instant::now();
+        match network.route(&map_data.osm_data, start, end) {
+            Some(route) => {
+                println!(
+                    "Route: {:.2} km, {:.0} min (found in {:.1} ms)",
+                    route.distance / 1000.0,
+                    route.time / 60.0,
+                    search_start.elapsed().as_secs_f64() * 1000.0
+                );
+
+                for (i, instruction) in instructions(&map_data.osm_data, network, &route)
+                    .iter()
+                    .enumerate()
+                {
+                    println!("{:>3}. {instruction}", i + 1);
+                }
+
+                let points = network
+                    .route_nodes(&route)
+                    .into_iter()
+                    .filter_map(node_position)
+                    .collect();
+
+                paths.extend(route_paths(points));
+            }
+            None => println!("No route found"),
+        }
+
+        paths.extend(marker(node_position(end)?, Vec3::new(0.9, 0.2, 0.2)));
+
+        Some(paths)
+    }
+}
+
+/// Path that the contraction hierarchy of a profile's network is saved at, next to the data file.
+pub(crate) fn hierarchy_path(data_file: &std::path::Path, profile: &Profile) -> PathBuf {
+    let mut path = data_file.to_path_buf().into_os_string();
+    path.push(format!(".{}.ch", profile.name));
+
+    path.into()
+}
+
+/// Dot (in screen space) marking the ends of the route, drawn as a very short line with round caps.
+fn marker(position: Vec3, color: Vec3) -> Vec<Path> {
+    let points = vec![position, position + Vec3::new(0.001, 0.0, 0.0)];
+
+    vec![
+        Path::new(points.clone())
+            .with_stroke(Stroke::new(14.0, Vec3::ONE).with_cap(LineCap::Round))
+            .with_z_index(2),
+        Path::new(points)
+            .with_stroke(Stroke::new(10.0, color).with_cap(LineCap::Round))
+            .with_z_index(3),
+    ]
+}
+
+/// Route highlighted over the map, with a white casing so that it stands out from the roads.
+fn route_paths(points: Vec<Vec3>) -> Vec<Path> {
+    vec![
+        Path::new(points.clone())
+            .with_stroke(
+                Stroke::new(10.0, Vec3::ONE)
+                    .with_join(LineJoin::Round)
+                    .with_cap(LineCap::Round),
+            )
+            .with_z_index(0),
+        Path::new(points)
+            .with_stroke(
+                Stroke::new(6.0, Vec3::new(0.1, 0.45, 0.95))
+                    .with_join(LineJoin::Round)
+                    .with_cap(LineCap::Round),
+            )
+            .with_z_index(1),
+    ]
+}
+
+impl Plugin<()> for PathFinder {
+    fn with_map_data(&mut self, map_data: Rc<MapData>) {
+        self.map_data = Some(map_data);
+        self.networks = self.profiles.iter().map(|_| None).collect();
+        self.analyses = self.profiles.iter().map(|_| None).collect();
+
+        self.start = None;
+        self.end = None;
+        self.update_route();
+    }
+
+    fn get_render_step(&self) -> Rc<RefCell<dyn RenderStep>> {
+        Rc::clone(&self.canvas) as Rc<RefCell<dyn RenderStep>>
+    }
+
+    fn handle_event(&mut self, _app_state: (), event: Event) -> bool {
+        match event {
+            Event::Click(point) => {
+                // Start a new route once the previous one is complete
+                if self.isochrone || self.start.is_none() || self.end.is_some() {
+                    self.start = Some(point);
+                    self.end = None;
+                } else {
+                    self.end = Some(point);
+                }
+            }
+            Event::Keyboard(VirtualKeyCode::P) => {
+                self.profile = (self.profile + 1) % self.profiles.len();
+
+                println!("Routing profile: {}", self.profiles[self.profile].name);
+            }
+            Event::Keyboard(VirtualKeyCode::I) => {
+                self.isochrone = !self.isochrone;
+                self.end = None;
+
+                println!(
+                    "Isochrones {}",
+                    if self.isochrone {
+                        "enabled"
+                    } else {
+                        "disabled"
+                    }
+                );
+            }
+            Event::Keyboard(VirtualKeyCode::N) => {
+                self.analysis = !self.analysis;
+
+                println!(
+                    "Network analysis {}",
+                    if self.analysis { "enabled" } else { "disabled" }
+                );
+            }
+            _ => return false,
+        }
+
+        self.update_route();
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_map::TestMap;
+
+    /// Nodes along each edge of the network, sorted so that they can be compared.
+    fn edge_nodes(map: &TestMap) -> Vec<Vec<i64>> {
+        let network = map.network();
+
+        let mut edges = network
+            .edges
+            .iter()
+            .map(|edge| edge.nodes.clone())
+            .collect::<Vec<_>>();
+        edges.sort();
+
+        edges
+    }
+
+    #[test]
+    fn splits_ways_at_intersections() {
+        let map = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 2.0, 0.0)
+            .node(4, 1.0, 1.0)
+            .road(10, &[1, 2, 3], &[])
+            .road(11, &[2, 4], &[]);
+
+        assert_eq!(
+            edge_nodes(&map),
+            vec![
+                vec![1, 2],
+                vec![2, 1],
+                vec![2, 3],
+                vec![2, 4],
+                vec![3, 2],
+                vec![4, 2],
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_ways_at_repeated_nodes() {
+        // Way that goes round a loop (2, 3, 4) and back out along itself
+        let map = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 2.0, 0.0)
+            .node(4, 2.0, 1.0)
+            .road(10, &[1, 2, 3, 4, 2], &[("oneway", "yes")]);
+
+        assert_eq!(edge_nodes(&map), vec![vec![1, 2], vec![2, 3, 4, 2]]);
+    }
+
+    #[test]
+    fn keeps_nodes_between_intersections() {
+        let map = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 2.0, 0.0)
+            .road(10, &[1, 2, 3], &[("oneway", "-1")]);
+
+        assert_eq!(edge_nodes(&map), vec![vec![3, 2, 1]]);
+    }
+
+    #[test]
+    fn splits_ways_at_missing_nodes() {
+        // Node 3 is outside of the extract
+        let map = TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(4, 3.0, 0.0)
+            .node(5, 4.0, 0.0)
+            .road(10, &[1, 2, 3, 4, 5], &[("oneway", "yes")]);
+
+        assert_eq!(edge_nodes(&map), vec![vec![1, 2], vec![4, 5]]);
+    }
+}
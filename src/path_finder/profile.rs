@@ -0,0 +1,199 @@
+use std::{collections::HashMap, error::Error, fmt::Display, fs, path::Path};
+
+use serde::Deserialize;
+
+use super::Direction;
+use crate::osm::Tags;
+
+/// Values of access tags that allow a way to be used.
+// https://wiki.openstreetmap.org/wiki/Key:access
+const ALLOWED: &[&str] = &[
+    "yes",
+    "permissive",
+    "designated",
+    "destination",
+    "customers",
+    "official",
+    "dismount",
+];
+
+/// Rules deciding which ways can be travelled along, how fast and in which directions, for a
+/// mode of transport. Profiles are written as TOML (see the built in profiles in `profiles/`).
+#[derive(Deserialize, Clone, Debug)]
+pub(crate) struct Profile {
+    pub name: String,
+
+    /// Access tags, from most to least specific. The first one present on a way decides whether it
+    /// can be used.
+    access: Vec<String>,
+
+    /// Oneway tags, from most to least specific. When empty, oneway restrictions are ignored.
+    #[serde(default)]
+    oneway: Vec<String>,
+
+    /// Turn restriction tags, from most to least specific (eg `restriction:motorcar`). When empty,
+    /// turn restrictions are ignored.
+    #[serde(default)]
+    restrictions: Vec<String>,
+
+    /// Whether the `maxspeed` tag is used in place of the speed for the highway class
+    #[serde(default)]
+    use_maxspeed: bool,
+
+    /// Speed (in km/h) for each highway class that can be used
+    speeds: HashMap<String, f64>,
+
+    /// Speed (in km/h) for highway classes that can only be used when an access tag allows it
+    #[serde(default)]
+    restricted_speeds: HashMap<String, f64>,
+}
+
+#[derive(Debug)]
+pub(crate) enum ProfileError {
+    IoError(std::io::Error),
+    ParseError(toml::de::Error),
+}
+impl Display for ProfileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::IoError(e) => write!(f, "Problem reading routing profile: {e}"),
+            Self::ParseError(e) => write!(f, "Problem parsing routing profile: {e}"),
+        }
+    }
+}
+impl Error for ProfileError {}
+
+impl Profile {
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ProfileError> {
+        let source = fs::read_to_string(path).map_err(ProfileError::IoError)?;
+
+        toml::from_str(&source).map_err(ProfileError::ParseError)
+    }
+
+    fn built_in(source: &str) -> Self {
+        toml::from_str(source).expect("built in profile should be valid")
+    }
+
+    pub fn car() -> Self {
+        Self::built_in(include_str!("profiles/car.toml"))
+    }
+
+    pub fn bike() -> Self {
+        Self::built_in(include_str!("profiles/bike.toml"))
+    }
+
+    pub fn foot() -> Self {
+        Self::built_in(include_str!("profiles/foot.toml"))
+    }
+
+    /// Whether the access tags allow the way to be used, or `None` if none of them are present.
+    fn access(&self, tags: &Tags) -> Option<bool> {
+        self.access
+            .iter()
+            .find_map(|key| tags.get(key))
+            .map(|value| ALLOWED.contains(&value.as_str()))
+    }
+
+    /// Speed (in km/h) that a way can be travelled at, or `None` if it can't be used.
+    pub fn speed(&self, tags: &Tags) -> Option<f64> {
+        let highway = tags.get("highway")?;
+        let access = self.access(tags);
+
+        let speed = match (self.speeds.get(highway), access) {
+            (_, Some(false)) => return None,
+            (Some(&speed), _) => speed,
+            (None, Some(true)) => *self.restricted_speeds.get(highway)?,
+            (None, None) => return None,
+        };
+
+        if self.use_maxspeed {
+            if let Some(maxspeed) = tags.get("maxspeed").and_then(|value| parse_speed(value)) {
+                return Some(maxspeed);
+            }
+        }
+
+        Some(speed)
+    }
+
+    /// Turn restriction (eg `no_left_turn`) from the tags of a restriction relation, if it applies
+    /// to the profile.
+    // https://wiki.openstreetmap.org/wiki/Relation:restriction
+    pub fn restriction<'a>(&self, tags: &'a Tags) -> Option<&'a str> {
+        // Modes of transport that the profile is for, from the suffixes of the restriction tags
+        let modes = self
+            .restrictions
+            .iter()
+            .filter_map(|key| key.strip_prefix("restriction:"))
+            .collect::<Vec<_>>();
+
+        let exempt = tags
+            .get("except")
+            .map(|except| except.split(';').any(|mode| modes.contains(&mode.trim())))
+            .unwrap_or(false);
+        if exempt {
+            return None;
+        }
+
+        self.restrictions
+            .iter()
+            .find_map(|key| tags.get(key))
+            .map(|value| value.as_str())
+    }
+
+    /// Directions that a way can be travelled in.
+    pub fn direction(&self, tags: &Tags) -> Direction {
+        if self.oneway.is_empty() {
+            return Direction::Both;
+        }
+
+        // https://wiki.openstreetmap.org/wiki/Key:oneway
+        match self.oneway.iter().find_map(|key| tags.get(key)) {
+            Some(value) => match value.as_str() {
+                "yes" | "true" | "1" => Direction::Forward,
+                "-1" | "reverse" => Direction::Backward,
+                _ => Direction::Both,
+            },
+            None => {
+                // Motorways and roundabouts are implicitly one way
+                let implied = tags
+                    .get("highway")
+                    .filter(|&value| value == "motorway")
+                    .is_some()
+                    || tags
+                        .get("junction")
+                        .filter(|&value| value == "roundabout" || value == "circular")
+                        .is_some();
+
+                if implied {
+                    Direction::Forward
+                } else {
+                    Direction::Both
+                }
+            }
+        }
+    }
+}
+
+// https://wiki.openstreetmap.org/wiki/Key:maxspeed
+fn parse_speed(value: &str) -> Option<f64> {
+    let value = value.trim();
+
+    let (number, is_mph) = if let Some(number) = value.strip_suffix("mph") {
+        (number, true)
+    } else if let Some(number) = value
+        .strip_suffix("km/h")
+        .or_else(|| value.strip_suffix("kmh"))
+    {
+        (number, false)
+    } else {
+        (value, false)
+    };
+
+    let speed = number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|&speed| speed > 0.0)?;
+
+    Some(if is_mph { speed * 1.609344 } else { speed })
+}
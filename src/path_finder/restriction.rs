@@ -0,0 +1,447 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{EdgeId, Network, Profile};
+use crate::osm::{Osm, Relation, RelationMemberType};
+
+/// Whether a restriction forbids a turn (`no_*`), or forbids every turn except one (`only_*`).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Kind {
+    Prohibitory,
+    Mandatory,
+}
+
+/// Where a restriction applies, between the `from` and `to` ways.
+enum Via {
+    Node(i64),
+
+    /// Ways connecting the `from` way to the `to` way
+    Ways(Vec<i64>),
+}
+
+/// Turn restriction relation that applies to a profile.
+struct TurnRestriction {
+    kind: Kind,
+
+    /// Whether the restriction is about turning back the way that was travelled
+    u_turn: bool,
+
+    from: Vec<i64>,
+    via: Via,
+    to: Vec<i64>,
+}
+
+impl TurnRestriction {
+    fn from_relation(relation: &Relation, profile: &Profile) -> Option<Self> {
+        if relation.tags.get("type")? != "restriction" {
+            return None;
+        }
+
+        let restriction = profile.restriction(&relation.tags)?;
+        let kind = if restriction.starts_with("no_") {
+            Kind::Prohibitory
+        } else if restriction.starts_with("only_") {
+            Kind::Mandatory
+        } else {
+            return None;
+        };
+
+        let ways = |role: &str| {
+            relation
+                .members
+                .iter()
+                .filter(|member| member.role.as_deref() == Some(role))
+                .filter(|member| matches!(member.member_type, RelationMemberType::Way))
+                .map(|member| member.id)
+                .collect::<Vec<_>>()
+        };
+
+        let via = relation
+            .members
+            .iter()
+            .find(|member| member.role.as_deref() == Some("via"))?;
+        let via = match via.member_type {
+            RelationMemberType::Node => Via::Node(via.id),
+            RelationMemberType::Way => Via::Ways(ways("via")),
+            RelationMemberType::Relation => return None,
+        };
+
+        Some(Self {
+            kind,
+            u_turn: restriction.ends_with("u_turn"),
+            from: ways("from"),
+            via,
+            to: ways("to"),
+        })
+    }
+}
+
+/// Restriction that goes through one or more via ways, so spans several turns.
+struct ViaWayRestriction {
+    kind: Kind,
+
+    /// Edges from the `from` way to the `to` way, including both
+    edges: Vec<EdgeId>,
+}
+
+/// Most via way restrictions that can start with the same edge, as they're followed as bits.
+const MAX_VIA_WAY_STARTS: usize = u64::BITS as usize;
+
+/// Via way restrictions that are being followed, which all start with the same edge.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+struct ViaWayProgress {
+    /// Edge that the restrictions start with
+    start: EdgeId,
+
+    /// Restrictions that are still being followed, as a bit for each of the restrictions starting
+    /// with the edge
+    following: u64,
+
+    /// Index of the next edge within the restrictions
+    position: usize,
+}
+
+/// Position while routing: the edge that was last travelled along, and how much of any via way
+/// restrictions have been followed to get there.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) struct EdgeState {
+    pub edge: EdgeId,
+
+    via: Option<ViaWayProgress>,
+}
+
+/// Turn restrictions that apply to a network, as turns between its edges.
+#[derive(Default)]
+pub(crate) struct Restrictions {
+    /// Turns (from one edge onto another) that can't be made
+    banned: HashSet<(EdgeId, EdgeId)>,
+
+    /// Edges that must be turned onto, for edges with a mandatory turn at their end
+    only: HashMap<EdgeId, HashSet<EdgeId>>,
+
+    via_ways: Vec<ViaWayRestriction>,
+
+    /// Via way restrictions starting with each edge, which are all followed at once. Only the
+    /// restrictions starting with one edge can be followed at a time, so restrictions starting part
+    /// way along others are missed.
+    via_way_starts: HashMap<EdgeId, Vec<usize>>,
+}
+
+impl Restrictions {
+    pub fn new(data: &Osm, profile: &Profile, network: &Network) -> Self {
+        let mut restrictions = Self::default();
+
+        // Edges for each way, as restrictions refer to ways
+        let mut way_edges = HashMap::<i64, Vec<EdgeId>>::new();
+        for (id, edge) in network.edges.iter().enumerate() {
+            way_edges.entry(edge.way).or_default().push(id);
+        }
+        let edges_of = |way: i64| way_edges.get(&way).into_iter().flatten().cloned();
+
+        // Relations are visited in order, so that the restrictions are the same every time the
+        // network is built (which saved contraction hierarchies rely on)
+        let mut relations = data.relations.iter().collect::<Vec<_>>();
+        relations.sort_unstable_by_key(|(&id, _)| id);
+
+        for restriction in relations
+            .into_iter()
+            .filter_map(|(_, relation)| TurnRestriction::from_relation(relation, profile))
+        {
+            match &restriction.via {
+                Via::Node(node) => {
+                    let u_turn = restriction.u_turn;
+
+                    for (from, to) in restriction.from.iter().flat_map(|&from_way| {
+                        restriction.to.iter().flat_map(move |&to_way| {
+                            edges_of(from_way)
+                                .filter(|&from| network.edge(from).to == *node)
+                                .flat_map(move |from| {
+                                    edges_of(to_way)
+                                        .filter(move |&to| {
+                                            network.is_turn(from, to)
+                                                // When the turn is back onto the same way, only
+                                                // u-turns or carrying on along it are meant
+                                                && (from_way != to_way
+                                                    || network.is_u_turn(from, to) == u_turn)
+                                        })
+                                        .map(move |to| (from, to))
+                                })
+                        })
+                    }) {
+                        match restriction.kind {
+                            Kind::Prohibitory => {
+                                restrictions.banned.insert((from, to));
+                            }
+                            Kind::Mandatory => {
+                                restrictions.only.entry(from).or_default().insert(to);
+                            }
+                        }
+                    }
+                }
+                Via::Ways(via_ways) => {
+                    for &from in restriction
+                        .from
+                        .iter()
+                        .flat_map(|&way| way_edges.get(&way).into_iter().flatten())
+                    {
+                        for &to_way in &restriction.to {
+                            let Some(edges) =
+                                via_way_edges(network, from, via_ways, to_way, edges_of)
+                            else {
+                                continue;
+                            };
+
+                            let starts = restrictions.via_way_starts.entry(from).or_default();
+                            if starts.len() == MAX_VIA_WAY_STARTS {
+                                continue;
+                            }
+
+                            starts.push(restrictions.via_ways.len());
+                            restrictions.via_ways.push(ViaWayRestriction {
+                                kind: restriction.kind,
+                                edges,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+
+        restrictions
+    }
+
+    /// State after starting a route along an edge.
+    pub fn enter(&self, edge: EdgeId) -> EdgeState {
+        EdgeState {
+            edge,
+            via: self.via_way_starts.get(&edge).map(|starts| ViaWayProgress {
+                start: edge,
+                following: u64::MAX >> (MAX_VIA_WAY_STARTS - starts.len()),
+                position: 1,
+            }),
+        }
+    }
+
+    /// State after turning onto the next edge, or `None` if the turn isn't allowed.
+    pub fn turn(&self, state: EdgeState, next: EdgeId) -> Option<EdgeState> {
+        if self.banned.contains(&(state.edge, next)) {
+            return None;
+        }
+
+        if let Some(only) = self.only.get(&state.edge) {
+            if !only.contains(&next) {
+                return None;
+            }
+        }
+
+        if let Some(progress) = state.via {
+            let mut following = 0;
+
+            for (bit, &index) in self.via_way_starts[&progress.start].iter().enumerate() {
+                if progress.following & (1 << bit) == 0 {
+                    continue;
+                }
+
+                let restriction = &self.via_ways[index];
+                let is_next = restriction.edges[progress.position] == next;
+                let is_last = progress.position == restriction.edges.len() - 1;
+
+                match (restriction.kind, is_next) {
+                    // Still following the restriction
+                    (_, true) if !is_last => following |= 1 << bit,
+                    (Kind::Prohibitory, true) | (Kind::Mandatory, false) => return None,
+
+                    // Either turned off a prohibitory restriction, or completed a mandatory one
+                    _ => (),
+                }
+            }
+
+            if following != 0 {
+                return Some(EdgeState {
+                    edge: next,
+                    via: Some(ViaWayProgress {
+                        following,
+                        position: progress.position + 1,
+                        ..progress
+                    }),
+                });
+            }
+        }
+
+        Some(self.enter(next))
+    }
+}
+
+/// Edges that a via way restriction is made up of, starting with an edge along the `from` way and
+/// following the via ways to an edge along the `to` way. `None` if the via ways can't be travelled
+/// along from the edge.
+fn via_way_edges<I: Iterator<Item = EdgeId>>(
+    network: &Network,
+    from: EdgeId,
+    via_ways: &[i64],
+    to_way: i64,
+    edges_of: impl Fn(i64) -> I,
+) -> Option<Vec<EdgeId>> {
+    let via_edges = via_ways
+        .iter()
+        .flat_map(|&way| edges_of(way))
+        .collect::<HashSet<_>>();
+
+    // Breadth first search along the via ways, so that the fewest edges are used
+    let mut previous = HashMap::<EdgeId, EdgeId>::new();
+    let mut queue = VecDeque::from([from]);
+
+    while let Some(edge) = queue.pop_front() {
+        if edge != from {
+            if let Some(to) = edges_of(to_way).find(|&to| network.is_turn(edge, to)) {
+                let mut edges = vec![to, edge];
+                let mut edge = edge;
+                while let Some(&prev) = previous.get(&edge) {
+                    edges.push(prev);
+                    edge = prev;
+                }
+                edges.reverse();
+
+                return Some(edges);
+            }
+        }
+
+        for next in via_edges.iter().cloned() {
+            if network.is_turn(edge, next)
+                && !network.is_u_turn(edge, next)
+                && next != from
+                && !previous.contains_key(&next)
+            {
+                previous.insert(next, edge);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_map::{TestMap, Via};
+
+    /// Crossroads at node 2, with a way heading out to each side.
+    ///
+    /// ```text
+    ///      3
+    ///      |
+    /// 1 -- 2 -- 4
+    ///      |
+    ///      5
+    /// ```
+    fn crossroads() -> TestMap {
+        TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 1.0, 1.0)
+            .node(4, 2.0, 0.0)
+            .node(5, 1.0, -1.0)
+            .road(10, &[1, 2], &[])
+            .road(11, &[2, 3], &[])
+            .road(12, &[2, 4], &[])
+            .road(13, &[2, 5], &[])
+    }
+
+    /// Crossroads at node 2, followed by a junction at node 6 that turning right leads to. Node 8
+    /// can also be reached the long way round through node 3.
+    ///
+    /// ```text
+    /// 1 -- 2 -- 3
+    ///      |    |
+    ///      6 -- 8
+    ///      |
+    ///      9
+    /// ```
+    fn junctions() -> TestMap {
+        TestMap::new()
+            .node(1, 0.0, 0.0)
+            .node(2, 1.0, 0.0)
+            .node(3, 2.0, 0.0)
+            .node(6, 1.0, -1.0)
+            .node(8, 1.5, -1.0)
+            .node(9, 1.0, -2.0)
+            .road(10, &[1, 2], &[])
+            .road(11, &[2, 6], &[])
+            .road(12, &[2, 3], &[])
+            .road(14, &[6, 8], &[])
+            .road(15, &[3, 8], &[])
+            .road(16, &[6, 9], &[])
+    }
+
+    fn route(map: &TestMap, from: i64, to: i64) -> Option<Vec<i64>> {
+        let network = map.network();
+
+        network
+            .route(map.osm(), from, to)
+            .map(|route| network.route_nodes(&route))
+    }
+
+    #[test]
+    fn unrestricted() {
+        let map = crossroads();
+
+        assert_eq!(route(&map, 1, 3), Some(vec![1, 2, 3]));
+        assert_eq!(route(&map, 1, 5), Some(vec![1, 2, 5]));
+    }
+
+    #[test]
+    fn no_turn() {
+        let map = crossroads().restriction(100, "no_left_turn", 10, Via::Node(2), 11);
+
+        // Turning around at the end of a way is the only way left
+        assert_eq!(route(&map, 1, 3), Some(vec![1, 2, 4, 2, 3]));
+        assert_eq!(route(&map, 1, 5), Some(vec![1, 2, 5]));
+
+        // Only applies when coming from the `from` way
+        assert_eq!(route(&map, 4, 3), Some(vec![4, 2, 3]));
+    }
+
+    #[test]
+    fn only_turn() {
+        let map = crossroads().restriction(100, "only_straight_on", 10, Via::Node(2), 12);
+
+        assert_eq!(route(&map, 1, 4), Some(vec![1, 2, 4]));
+        assert_eq!(route(&map, 1, 3), Some(vec![1, 2, 4, 2, 3]));
+        assert_eq!(route(&map, 1, 5), Some(vec![1, 2, 4, 2, 5]));
+
+        assert_eq!(route(&map, 5, 3), Some(vec![5, 2, 3]));
+    }
+
+    #[test]
+    fn no_turn_via_ways() {
+        let map = junctions().restriction(100, "no_left_turn", 10, Via::Ways(&[11]), 14);
+
+        assert_eq!(route(&map, 1, 8), Some(vec![1, 2, 3, 8]));
+        assert_eq!(route(&map, 1, 9), Some(vec![1, 2, 6, 9]));
+
+        // Only applies when the via way is reached from the `from` way
+        assert_eq!(route(&map, 2, 8), Some(vec![2, 6, 8]));
+    }
+
+    #[test]
+    fn several_restrictions_via_the_same_ways() {
+        let map = junctions()
+            .restriction(100, "no_left_turn", 10, Via::Ways(&[11]), 14)
+            .restriction(101, "no_straight_on", 10, Via::Ways(&[11]), 16);
+
+        assert_eq!(route(&map, 1, 8), Some(vec![1, 2, 3, 8]));
+        assert_eq!(route(&map, 1, 9), Some(vec![1, 2, 3, 8, 6, 9]));
+
+        assert_eq!(route(&map, 2, 9), Some(vec![2, 6, 9]));
+    }
+
+    #[test]
+    fn only_turn_via_ways() {
+        let map = junctions().restriction(100, "only_left_turn", 10, Via::Ways(&[11]), 14);
+
+        // Has to carry on to node 8 and turn back, as that's shorter than going round through 3
+        assert_eq!(route(&map, 1, 9), Some(vec![1, 2, 6, 8, 6, 9]));
+        assert_eq!(route(&map, 1, 8), Some(vec![1, 2, 6, 8]));
+
+        assert_eq!(route(&map, 2, 9), Some(vec![2, 6, 9]));
+    }
+}
@@ -0,0 +1,170 @@
+use std::collections::HashMap;
+
+use super::{EdgeId, Network, Profile};
+use crate::osm::{Node, Osm, Relation, RelationMember, RelationMemberType, Tags, Way};
+
+/// Distance (in degrees) between neighbouring points of the grid that nodes are placed on, which
+/// is roughly 100 m.
+const GRID_SPACING: f64 = 0.0009;
+
+/// Small hand-built map for testing routing, with nodes placed on a grid.
+pub(crate) struct TestMap {
+    osm: Osm,
+}
+
+impl TestMap {
+    pub fn new() -> Self {
+        Self {
+            osm: Osm {
+                nodes: HashMap::new(),
+                ways: HashMap::new(),
+                relations: HashMap::new(),
+            },
+        }
+    }
+
+    /// Square grid of residential streets, with `size` nodes along each side and each block being a
+    /// separate way. Node ids count along each row from the south west (eg for a size of 3):
+    ///
+    /// ```text
+    /// 7 -- 8 -- 9
+    /// |    |    |
+    /// 4 -- 5 -- 6
+    /// |    |    |
+    /// 1 -- 2 -- 3
+    /// ```
+    ///
+    /// Ways running east from a node at (x, y) have ids from 100 (`100 + y * size + x`), and ways
+    /// running north have ids from 200 (`200 + x * size + y`).
+    pub fn grid(size: i64) -> Self {
+        let mut map = Self::new();
+        for y in 0..size {
+            for x in 0..size {
+                map = map.node(grid_node(size, x, y), x as f64, y as f64);
+            }
+        }
+
+        for y in 0..size {
+            for x in 0..size - 1 {
+                let nodes = [grid_node(size, x, y), grid_node(size, x + 1, y)];
+                map = map.road(100 + y * size + x, &nodes, &[]);
+            }
+        }
+        for x in 0..size {
+            for y in 0..size - 1 {
+                let nodes = [grid_node(size, x, y), grid_node(size, x, y + 1)];
+                map = map.road(200 + x * size + y, &nodes, &[]);
+            }
+        }
+
+        map
+    }
+
+    /// Adds a node at a position on the grid, with x to the east and y to the north.
+    pub fn node(mut self, id: i64, x: f64, y: f64) -> Self {
+        let (lon, lat) = grid_lon_lat(x, y);
+        self.osm.nodes.insert(id, Node::from_lon_lat(lon, lat));
+
+        self
+    }
+
+    /// Adds a residential road along the nodes, with any other tags.
+    pub fn road(mut self, id: i64, nodes: &[i64], tags: &[(&str, &str)]) -> Self {
+        let tags = [("highway", "residential")]
+            .into_iter()
+            .chain(tags.iter().cloned())
+            .collect();
+
+        self.osm.ways.insert(
+            id,
+            Way {
+                tags,
+                nodes: nodes.to_vec(),
+            },
+        );
+
+        self
+    }
+
+    /// Replaces the tags of a road (which is still residential unless overridden), keeping its
+    /// nodes.
+    pub fn retag(self, id: i64, tags: &[(&str, &str)]) -> Self {
+        let nodes = self.osm.ways[&id].nodes.clone();
+
+        self.road(id, &nodes, tags)
+    }
+
+    /// Adds a turn restriction (eg `no_left_turn`) from one way to another, through either a node
+    /// or several ways.
+    pub fn restriction(mut self, id: i64, restriction: &str, from: i64, via: Via, to: i64) -> Self {
+        let member = |role: &str, id: i64, member_type: RelationMemberType| RelationMember {
+            role: Some(role.to_string()),
+            id,
+            member_type,
+        };
+
+        let via = match via {
+            Via::Node(node) => vec![member("via", node, RelationMemberType::Node)],
+            Via::Ways(ways) => ways
+                .iter()
+                .map(|&way| member("via", way, RelationMemberType::Way))
+                .collect(),
+        };
+
+        let members = [member("from", from, RelationMemberType::Way)]
+            .into_iter()
+            .chain(via)
+            .chain([member("to", to, RelationMemberType::Way)])
+            .collect();
+
+        self.osm.relations.insert(
+            id,
+            Relation {
+                tags: Tags::from_iter([("type", "restriction"), ("restriction", restriction)]),
+                members,
+            },
+        );
+
+        self
+    }
+
+    /// Removes all but one of the turn restrictions.
+    pub fn remove_restrictions_except(&mut self, id: i64) {
+        self.osm.relations.retain(|&relation, _| relation == id);
+    }
+
+    pub fn osm(&self) -> &Osm {
+        &self.osm
+    }
+
+    /// Routing network for cars.
+    pub fn network(&self) -> Network {
+        Network::new(&self.osm, &Profile::car())
+    }
+}
+
+/// Where a turn restriction applies.
+pub(crate) enum Via<'a> {
+    Node(i64),
+    Ways(&'a [i64]),
+}
+
+/// Id of the node at a position of a grid made by `TestMap::grid`.
+pub(crate) fn grid_node(size: i64, x: i64, y: i64) -> i64 {
+    y * size + x + 1
+}
+
+/// Longitude and latitude of a position on the grid.
+pub(crate) fn grid_lon_lat(x: f64, y: f64) -> (f64, f64) {
+    (x * GRID_SPACING, y * GRID_SPACING)
+}
+
+/// Nodes that a route passes through, from the start of the first edge to the end of the last.
+pub(crate) fn route_nodes(network: &Network, edges: &[EdgeId]) -> Vec<i64> {
+    edges
+        .first()
+        .map(|&edge| network.edge(edge).from)
+        .into_iter()
+        .chain(edges.iter().map(|&edge| network.edge(edge).to))
+        .collect()
+}
@@ -0,0 +1,64 @@
+/// Order to visit stops in so that the total travel time is as short as possible, given the travel
+/// times between every pair of stops. The tour always starts at the first stop, and returns to it
+/// at the end when `round_trip` is set.
+///
+/// The order is found with a nearest neighbour tour, which is then improved with 2-opt (reversing
+/// parts of the tour) until no reversal makes it any faster. This isn't guaranteed to find the
+/// best order, but is close for the few dozen stops of a delivery run.
+pub(crate) fn order_stops(matrix: &[Vec<Option<f64>>], round_trip: bool) -> Vec<usize> {
+    if matrix.is_empty() {
+        return Vec::new();
+    }
+
+    let cost = |from: usize, to: usize| matrix[from][to].unwrap_or(f64::INFINITY);
+    let total = |order: &[usize]| {
+        let legs = order
+            .windows(2)
+            .map(|leg| cost(leg[0], leg[1]))
+            .sum::<f64>();
+
+        if round_trip {
+            legs + cost(order[order.len() - 1], order[0])
+        } else {
+            legs
+        }
+    };
+
+    // Nearest neighbour
+    let mut order = vec![0];
+    let mut remaining = (1..matrix.len()).collect::<Vec<_>>();
+    while !remaining.is_empty() {
+        let last = order[order.len() - 1];
+        let (i, _) = remaining
+            .iter()
+            .enumerate()
+            .min_by(|(_, &a), (_, &b)| cost(last, a).total_cmp(&cost(last, b)))
+            .expect("remaining stops should not be empty");
+
+        order.push(remaining.swap_remove(i));
+    }
+
+    // 2-opt, keeping the first stop in place. Travel times may differ in each direction (eg one
+    // way streets), so the whole tour is costed rather than only the changed legs.
+    let mut best = total(&order);
+    let mut improved = true;
+    while improved {
+        improved = false;
+
+        for i in 1..order.len() {
+            for j in i + 1..order.len() {
+                order[i..=j].reverse();
+
+                let cost = total(&order);
+                if cost < best - 1e-9 {
+                    best = cost;
+                    improved = true;
+                } else {
+                    order[i..=j].reverse();
+                }
+            }
+        }
+    }
+
+    order
+}
@@ -1,6 +1,7 @@
 use std::{cell::RefCell, rc::Rc};
 
-use renderer::{ RenderStep, Event};
+use glam::Vec3;
+use renderer::{Event, RenderStep};
 
 use crate::map_data::MapData;
 
@@ -14,4 +15,16 @@ pub(crate) trait Plugin<A> {
     /// Called every time an event on the window occurs, returning true or false depending on
     /// whether the plugin state changed (and a re-render is requried).
     fn handle_event(&mut self, app_state: A, event: Event) -> bool;
+
+    /// Whether the plugin is taking text input (such as a search prompt), in which case it's the
+    /// only plugin that key presses are sent to.
+    fn captures_keyboard(&self) -> bool {
+        false
+    }
+
+    /// Called after every event to collect a point on the map (and the height to look at it from)
+    /// that the plugin wants the camera to fly to.
+    fn take_camera_target(&mut self) -> Option<(Vec3, f32)> {
+        None
+    }
 }